@@ -5,6 +5,13 @@ use anchor_lang::AccountsExit;
 use anchor_lang::Bumps;
 use anchor_lang::system_program;
 use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
 #[cfg(feature = "poseidon")]
 use ark_bn254::Fr;
 #[cfg(feature = "poseidon")]
@@ -21,7 +28,37 @@ const MIN_RATE_LIMIT_SECONDS: i64 = 10;
 const MIN_AUTHORITY_DELAY_SECONDS: i64 = 15 * 60; // 15 minutes
 const MAX_AUTHORITY_DELAY_SECONDS: i64 = 7 * 24 * 60 * 60; // 7 days
 const DEFAULT_AUTHORITY_DELAY_SECONDS: i64 = 24 * 60 * 60; // 24 hours
+const MIN_ACCEPTANCE_WINDOW_SECONDS: i64 = 60 * 60; // 1 hour
+const MAX_ACCEPTANCE_WINDOW_SECONDS: i64 = 30 * 24 * 60 * 60; // 30 days
+const DEFAULT_ACCEPTANCE_WINDOW_SECONDS: i64 = 7 * 24 * 60 * 60; // 7 days
 const MAX_FEE_BPS: u16 = 1000; // 10%
+// Hard ceilings for PolicyParams; the consts above are the policy defaults.
+const POLICY_MAX_EXPIRATION_WINDOW: i64 = 365 * 24 * 60 * 60; // 1 year
+const POLICY_MAX_RATE_LIMIT_SECONDS: i64 = 24 * 60 * 60; // 24 hours
+const POLICY_MAX_FEE_BPS: u16 = 1000; // 10%
+const POLICY_MAX_QUOTA_WINDOW: i64 = 30 * 24 * 60 * 60; // 30 days
+const MAX_OUTFLOW_WINDOW: i64 = 30 * 24 * 60 * 60; // 30 days
+const MAX_DISTRIBUTOR_RECIPIENTS: u32 = 65_536; // keeps the claim bitmap at 8 KiB
+const MAX_MULTISIG_MEMBERS: usize = 16; // approvals are a u16 bitmask
+// Config.paused bits
+const PAUSE_CREATE: u8 = 1 << 0; // create_drop
+const PAUSE_DEPOSIT: u8 = 1 << 1; // deposit_pool, deposit_multi_claim, deposit_vesting, create_distributor
+const PAUSE_CLAIM: u8 = 1 << 2; // claim_drop, reveal_claim, claim_drop_to_shielded, claim_distributor
+const PAUSE_ALL: u8 = PAUSE_CREATE | PAUSE_DEPOSIT | PAUSE_CLAIM;
+const ASSET_TYPE_SOL: u8 = 0;
+const ASSET_TYPE_TOKEN: u8 = 1;
+/// Token-2022 mint extensions that cannot move or lock tokens held by the vault.
+const SUPPORTED_MINT_EXTENSIONS: [ExtensionType; 9] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::TransferHook,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+    ExtensionType::GroupPointer,
+    ExtensionType::TokenGroup,
+    ExtensionType::GroupMemberPointer,
+    ExtensionType::TokenGroupMember,
+    ExtensionType::InterestBearingConfig,
+];
 const SHIELDED_TREE_MAX_DEPTH: u8 = 20;
 const SHIELDED_TREE_MAX_DEPTH_USIZE: usize = 20;
 const SHIELDED_ZEROES: [[u8; 32]; 20] = [
@@ -56,7 +93,11 @@ pub mod darkpool {
 
     pub fn initialize(ctx: Context<Initialize>, treasury: Pubkey, fee_bps: u16) -> Result<()> {
         require!(treasury != Pubkey::default(), DarkPoolError::InvalidTreasury);
-        require!(fee_bps <= MAX_FEE_BPS, DarkPoolError::InvalidFeeBps);
+        // No policy account exists yet, so the built-in policy bounds the fee.
+        require!(
+            fee_bps <= PolicyValues::default().max_fee_bps,
+            DarkPoolError::InvalidFeeBps
+        );
 
         let program_id = ctx.program_id;
         let config_info = ctx.accounts.config.to_account_info();
@@ -130,6 +171,29 @@ pub mod darkpool {
             treasury,
             fee_bps,
             sol_vault_bump,
+            outstanding_liabilities: 0,
+            accrued_fees: 0,
+            pending_fee_bps: 0,
+            pending_fee_effective_at: 0,
+            pending_treasury: Pubkey::default(),
+            pending_treasury_effective_at: 0,
+            sol_fee_schedule: FeeSchedule::default(),
+            token_fee_schedule: FeeSchedule::default(),
+            pending_fee_schedule: FeeSchedule::default(),
+            pending_fee_schedule_asset_type: 0,
+            pending_fee_schedule_effective_at: 0,
+            paused: 0,
+            pause_guardian: Pubkey::default(),
+            outflow_window_seconds: 0,
+            outflow_limit: 0,
+            outflow_window_start: 0,
+            outflow_in_window: 0,
+            outflow_tripped: false,
+            version: Config::VERSION,
+            authority_acceptance_window_seconds: DEFAULT_ACCEPTANCE_WINDOW_SECONDS,
+            admin_action_count: 0,
+            partner_accrued_fees: 0,
+            pending_authority_expires_at: 0,
         };
         let mut data = config_info.try_borrow_mut_data()?;
         let mut cursor: &mut [u8] = &mut data;
@@ -282,6 +346,7 @@ pub mod darkpool {
             vault_bump,
             current_root: root,
             next_leaf_index: 0,
+            version: ShieldedConfig::VERSION,
         };
         let mut config_data = config_info.try_borrow_mut_data()?;
         let mut config_cursor: &mut [u8] = &mut config_data;
@@ -416,10 +481,12 @@ pub mod darkpool {
         amount: u64,
         asset_type: u8,
         expires_at: i64,
+        claim_policy: ClaimPolicy,
     ) -> Result<()> {
         let clock = Clock::get()?;
         let now = clock.unix_timestamp;
         let program_id = ctx.program_id;
+        require_not_paused(&ctx.accounts.config, PAUSE_CREATE)?;
 
         let (drop_pda, drop_bump) =
             Pubkey::find_program_address(&[b"drop", nullifier.as_ref()], program_id);
@@ -434,6 +501,7 @@ pub mod darkpool {
         let nullifier_info = ctx.accounts.nullifier_account.to_account_info();
         let rate_limit_info = ctx.accounts.rate_limit_account.to_account_info();
         let config_info = ctx.accounts.config.to_account_info();
+        let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
         let system_program_info = ctx.accounts.system_program.to_account_info();
 
         require_keys_eq!(drop_pda, *drop_info.key, ErrorCode::ConstraintSeeds);
@@ -456,6 +524,7 @@ pub mod darkpool {
         require!(drop_info.is_writable, ErrorCode::ConstraintMut);
         require!(nullifier_info.is_writable, ErrorCode::ConstraintMut);
         require!(rate_limit_info.is_writable, ErrorCode::ConstraintMut);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
         require!(
             ctx.accounts.payer.to_account_info().is_writable,
             ErrorCode::ConstraintMut
@@ -465,70 +534,86 @@ pub mod darkpool {
             ctx.accounts.config.is_initialized,
             DarkPoolError::ConfigNotInitialized
         );
+        // The authority may always create drops; anyone else needs an enabled creator role.
+        let mut role_limits = None;
+        if ctx.accounts.config.authority != payer_key {
+            let role_info = ctx
+                .accounts
+                .creator_role
+                .as_ref()
+                .ok_or(DarkPoolError::UnauthorizedCreator)?
+                .to_account_info();
+            require!(role_info.is_writable, ErrorCode::ConstraintMut);
+            let mut role_state = load_creator_role(program_id, &role_info)?;
+            require_keys_eq!(
+                role_state.creator,
+                payer_key,
+                DarkPoolError::UnauthorizedCreator
+            );
+            require!(role_state.enabled, DarkPoolError::CreatorRoleDisabled);
+            require!(
+                role_state.quota == 0 || role_state.drops_created < role_state.quota,
+                DarkPoolError::CreatorQuotaExceeded
+            );
+            role_state.drops_created = role_state
+                .drops_created
+                .checked_add(1)
+                .ok_or(DarkPoolError::NumericalOverflow)?;
+            if role_state.limits.is_set() {
+                role_limits = Some(role_state.limits);
+            }
+            let mut role_data = role_info.try_borrow_mut_data()?;
+            let mut role_cursor: &mut [u8] = &mut role_data;
+            role_state.try_serialize(&mut role_cursor)?;
+        }
+        require!(amount > 0, DarkPoolError::InvalidAmount);
+        // create_drop escrows nothing, so it may only draw on SOL surplus the authority
+        // has already sent to the vault. Token drops go through `deposit_pool`.
         require!(
-            ctx.accounts.config.authority == ctx.accounts.payer.key(),
-            DarkPoolError::UnauthorizedCreator
+            asset_type == ASSET_TYPE_SOL,
+            DarkPoolError::InvalidAssetType
         );
-        require!(amount > 0, DarkPoolError::InvalidAmount);
-        require!(asset_type == 0, DarkPoolError::InvalidAssetType);
+        let referrer = drop_referrer(
+            program_id,
+            ctx.accounts
+                .referrer_partner
+                .as_ref()
+                .map(|partner| partner.as_ref()),
+        )?;
         require!(
             recipient != ctx.accounts.payer.key(),
             DarkPoolError::InvalidRecipient
         );
+        let policy = load_policy(program_id, ctx.accounts.policy.as_ref())?;
         require!(
             expires_at > now
-                && expires_at - now >= MIN_EXPIRATION_WINDOW
-                && expires_at - now <= MAX_EXPIRATION_WINDOW,
+                && expires_at - now >= policy.min_expiration_window
+                && expires_at - now <= policy.max_expiration_window,
             DarkPoolError::InvalidExpiration
         );
 
-        let mut rate_limit_state: RateLimitAccount;
-        if rate_limit_info.owner == program_id {
-            let mut data_slice: &[u8] = &rate_limit_info.try_borrow_data()?;
-            rate_limit_state = RateLimitAccount::try_deserialize(&mut data_slice)?;
-            require_eq!(
-                rate_limit_state.bump,
-                rate_limit_bump,
-                ErrorCode::ConstraintSeeds
-            );
-        } else {
-            require!(
-                rate_limit_info.owner == &system_program::ID && rate_limit_info.lamports() == 0,
-                DarkPoolError::AccountAlreadyInitialized
-            );
-            let space = (8 + RateLimitAccount::LEN) as u64;
-            let lamports = Rent::get()?.minimum_balance(space as usize);
-            let seeds: &[&[u8]] = &[b"rate_limit", payer_key.as_ref(), &[rate_limit_bump]];
-            system_program::create_account(
-                CpiContext::new_with_signer(
-                    system_program_info.clone(),
-                    system_program::CreateAccount {
-                        from: ctx.accounts.payer.to_account_info(),
-                        to: rate_limit_info.clone(),
-                    },
-                    &[seeds],
-                ),
-                lamports,
-                space,
-                program_id,
-            )?;
-            rate_limit_state = RateLimitAccount {
-                last_drop_at: 0,
-                bump: rate_limit_bump,
-            };
-        }
+        enforce_rate_limit(
+            program_id,
+            RateLimitAccounts {
+                rate_limit: &rate_limit_info,
+                payer: &ctx.accounts.payer.to_account_info(),
+                system_program: &system_program_info,
+            },
+            rate_limit_bump,
+            &policy,
+            &role_limits.unwrap_or(policy.default_quota),
+            amount,
+            now,
+        )?;
 
-        if rate_limit_state.last_drop_at != 0 {
-            require!(
-                now - rate_limit_state.last_drop_at >= MIN_RATE_LIMIT_SECONDS,
-                DarkPoolError::RateLimitExceeded
-            );
-        }
-        rate_limit_state.last_drop_at = now;
-        rate_limit_state.bump = rate_limit_bump;
-        let mut rate_limit_data = rate_limit_info.try_borrow_mut_data()?;
-        let mut rate_limit_cursor: &mut [u8] = &mut rate_limit_data;
-        rate_limit_state.try_serialize(&mut rate_limit_cursor)?;
+        // create_drop moves no funds, so the vault must already hold the new obligation.
+        let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+        require_keys_eq!(
+            sol_vault_pda,
+            *sol_vault_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        add_sol_liability(&mut ctx.accounts.config, &sol_vault_info, amount)?;
 
         require!(
             drop_info.owner == &system_program::ID && drop_info.lamports() == 0,
@@ -562,6 +647,21 @@ pub mod darkpool {
             claimed_at: 0,
             claimer: Pubkey::default(),
             bump: drop_bump,
+            mint: Pubkey::default(),
+            funder: payer_key,
+            claim_policy,
+            max_claims: 0,
+            claimed_count: 0,
+            per_claim_amount: 0,
+            claimed_amount: 0,
+            cliff_at: 0,
+            vest_start: 0,
+            vest_end: 0,
+            fee_prepaid: false,
+            referrer,
+            version: DropAccount::VERSION,
+            escrowed: false,
+            legacy_seed: false,
         };
         let mut drop_data = drop_info.try_borrow_mut_data()?;
         let mut drop_cursor: &mut [u8] = &mut drop_data;
@@ -603,6 +703,7 @@ pub mod darkpool {
                 claimer: Pubkey::default(),
                 used_at: 0,
                 bump: nullifier_bump,
+                version: NullifierAccount::VERSION,
             };
         }
 
@@ -617,6 +718,10 @@ pub mod darkpool {
                 nullifier_state.nullifier == nullifier,
                 DarkPoolError::InvalidNullifier
             );
+            require!(
+                !nullifier_state.is_used,
+                DarkPoolError::NullifierAlreadyUsed
+            );
         }
         let mut nullifier_data = nullifier_info.try_borrow_mut_data()?;
         let mut nullifier_cursor: &mut [u8] = &mut nullifier_data;
@@ -629,6 +734,7 @@ pub mod darkpool {
             asset_type,
             expires_at,
             payer: ctx.accounts.payer.key(),
+            mint: Pubkey::default(),
         });
 
         msg!(
@@ -639,50 +745,123 @@ pub mod darkpool {
         Ok(())
     }
 
-    pub fn deposit_pool(
-        ctx: Context<DepositPool>,
+    /// `recipient` is the wallet the `RecipientSigner` and `RecipientSignature` policies
+    /// check; it must differ from the depositor for those policies.
+    pub fn deposit_pool<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositPool<'info>>,
         nullifier: [u8; 32],
+        recipient: Pubkey,
         amount: u64,
         asset_type: u8,
         expires_at: i64,
+        claim_policy: ClaimPolicy,
     ) -> Result<()> {
-        let clock = Clock::get()?;
-        let now = clock.unix_timestamp;
-        let program_id = ctx.program_id;
+        if claim_policy.is_recipient_bound() {
+            require!(
+                recipient != Pubkey::default() && recipient != ctx.accounts.payer.key(),
+                DarkPoolError::InvalidRecipient
+            );
+        }
+        process_deposit_pool(
+            ctx,
+            nullifier,
+            amount,
+            asset_type,
+            expires_at,
+            claim_policy,
+            DropTerms {
+                recipient,
+                ..DropTerms::default()
+            },
+        )
+    }
 
-        let (drop_pda, drop_bump) =
-            Pubkey::find_program_address(&[b"drop", nullifier.as_ref()], program_id);
-        let (nullifier_pda, nullifier_bump) =
-            Pubkey::find_program_address(&[b"nullifier", nullifier.as_ref()], program_id);
-        let (rate_limit_pda, rate_limit_bump) = Pubkey::find_program_address(
-            &[b"rate_limit", ctx.accounts.payer.key().as_ref()],
-            program_id,
+    /// Red-envelope drop: the first `max_claims` distinct wallets share `amount`,
+    /// `per_claim_amount` each.
+    pub fn deposit_multi_claim<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositPool<'info>>,
+        nullifier: [u8; 32],
+        amount: u64,
+        expires_at: i64,
+        claim_policy: ClaimPolicy,
+        max_claims: u32,
+        per_claim_amount: u64,
+    ) -> Result<()> {
+        require!(max_claims > 0, DarkPoolError::InvalidClaimSplit);
+        // Shares go to distinct wallets, so a single recipient cannot bind the claims.
+        require!(
+            !claim_policy.is_recipient_bound(),
+            DarkPoolError::UnsupportedClaimPolicy
         );
-        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
-        let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+        let recipient = ctx.accounts.payer.key();
+        process_deposit_pool(
+            ctx,
+            nullifier,
+            amount,
+            ASSET_TYPE_SOL,
+            expires_at,
+            claim_policy,
+            DropTerms {
+                recipient,
+                max_claims,
+                per_claim_amount,
+                ..DropTerms::default()
+            },
+        )
+    }
+
+    /// Drop that unlocks linearly between `vest_start` and `vest_end`, after `cliff_at`.
+    /// `grantee` is stored as the recipient and the drop uses `RecipientSigner`, so only the
+    /// grantee can claim its tranches.
+    pub fn deposit_vesting<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositPool<'info>>,
+        nullifier: [u8; 32],
+        amount: u64,
+        asset_type: u8,
+        expires_at: i64,
+        schedule: VestingSchedule,
+        grantee: Pubkey,
+    ) -> Result<()> {
+        require!(schedule.vest_end != 0, DarkPoolError::InvalidVestingSchedule);
+        require!(grantee != Pubkey::default(), DarkPoolError::MissingGrantee);
+        process_deposit_pool(
+            ctx,
+            nullifier,
+            amount,
+            asset_type,
+            expires_at,
+            ClaimPolicy::RecipientSigner,
+            DropTerms {
+                recipient: grantee,
+                vesting: schedule,
+                ..DropTerms::default()
+            },
+        )
+    }
+
+    pub fn claim_drop<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimDrop<'info>>,
+        secret: [u8; 32],
+    ) -> Result<()> {
+        process_claim_drop(ctx, secret, false)
+    }
+
+    pub fn commit_claim(ctx: Context<CommitClaim>, commitment: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let program_id = ctx.program_id;
 
         let drop_info = ctx.accounts.drop.to_account_info();
-        let nullifier_info = ctx.accounts.nullifier_account.to_account_info();
-        let rate_limit_info = ctx.accounts.rate_limit_account.to_account_info();
-        let config_info = ctx.accounts.config.to_account_info();
-        let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+        let commitment_info = ctx.accounts.claim_commitment.to_account_info();
         let system_program_info = ctx.accounts.system_program.to_account_info();
-
-        require_keys_eq!(drop_pda, *drop_info.key, ErrorCode::ConstraintSeeds);
-        require_keys_eq!(
-            nullifier_pda,
-            *nullifier_info.key,
-            ErrorCode::ConstraintSeeds
-        );
-        require_keys_eq!(
-            rate_limit_pda,
-            *rate_limit_info.key,
-            ErrorCode::ConstraintSeeds
+        let claimer_key = ctx.accounts.claimer.key();
+        let (commitment_pda, commitment_bump) = Pubkey::find_program_address(
+            &[b"claim_commit", drop_info.key.as_ref(), claimer_key.as_ref()],
+            program_id,
         );
-        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+
         require_keys_eq!(
-            sol_vault_pda,
-            *sol_vault_info.key,
+            commitment_pda,
+            *commitment_info.key,
             ErrorCode::ConstraintSeeds
         );
         require_keys_eq!(
@@ -690,54 +869,49 @@ pub mod darkpool {
             *system_program_info.key,
             ErrorCode::ConstraintAddress
         );
-        require!(drop_info.is_writable, ErrorCode::ConstraintMut);
-        require!(nullifier_info.is_writable, ErrorCode::ConstraintMut);
-        require!(rate_limit_info.is_writable, ErrorCode::ConstraintMut);
-        require!(sol_vault_info.is_writable, ErrorCode::ConstraintMut);
+        require!(commitment_info.is_writable, ErrorCode::ConstraintMut);
         require!(
-            ctx.accounts.payer.to_account_info().is_writable,
+            ctx.accounts.claimer.to_account_info().is_writable,
             ErrorCode::ConstraintMut
         );
+        require!(drop_info.owner == program_id, ErrorCode::ConstraintOwner);
 
+        let drop_state = {
+            let drop_data = drop_info.try_borrow_data()?;
+            DropAccount::try_deserialize(&mut &drop_data[..])?
+        };
+        let (drop_pda, _) =
+            Pubkey::find_program_address(&[b"drop", drop_state.nullifier.as_ref()], program_id);
+        require_keys_eq!(drop_pda, *drop_info.key, ErrorCode::ConstraintSeeds);
         require!(
-            ctx.accounts.config.is_initialized,
-            DarkPoolError::ConfigNotInitialized
+            drop_state.status == DropStatus::Active,
+            DarkPoolError::DropNotActive
+        );
+        require!(
+            drop_state.claim_policy == ClaimPolicy::CommitReveal,
+            DarkPoolError::CommitRevealNotEnabled
         );
-        require!(amount > 0, DarkPoolError::InvalidAmount);
-        require!(asset_type <= 1, DarkPoolError::InvalidAssetType);
-        if expires_at != 0 {
-            require!(
-                expires_at > now
-                    && expires_at - now >= MIN_EXPIRATION_WINDOW
-                    && expires_at - now <= MAX_EXPIRATION_WINDOW,
-                DarkPoolError::InvalidExpiration
-            );
-        }
 
-        let mut rate_limit_state: RateLimitAccount;
-        if rate_limit_info.owner == program_id {
-            let mut data_slice: &[u8] = &rate_limit_info.try_borrow_data()?;
-            rate_limit_state = RateLimitAccount::try_deserialize(&mut data_slice)?;
-            require_eq!(
-                rate_limit_state.bump,
-                rate_limit_bump,
-                ErrorCode::ConstraintSeeds
-            );
-        } else {
+        // A pending commitment may be replaced; otherwise create the PDA.
+        if commitment_info.owner != program_id {
             require!(
-                rate_limit_info.owner == &system_program::ID && rate_limit_info.lamports() == 0,
+                commitment_info.owner == &system_program::ID && commitment_info.lamports() == 0,
                 DarkPoolError::AccountAlreadyInitialized
             );
-            let space = (8 + RateLimitAccount::LEN) as u64;
+            let space = (8 + ClaimCommitment::LEN) as u64;
             let lamports = Rent::get()?.minimum_balance(space as usize);
-            let payer_key = ctx.accounts.payer.key();
-            let seeds: &[&[u8]] = &[b"rate_limit", payer_key.as_ref(), &[rate_limit_bump]];
+            let seeds: &[&[u8]] = &[
+                b"claim_commit",
+                drop_info.key.as_ref(),
+                claimer_key.as_ref(),
+                &[commitment_bump],
+            ];
             system_program::create_account(
                 CpiContext::new_with_signer(
-                    system_program_info.clone(),
+                    system_program_info,
                     system_program::CreateAccount {
-                        from: ctx.accounts.payer.to_account_info(),
-                        to: rate_limit_info.clone(),
+                        from: ctx.accounts.claimer.to_account_info(),
+                        to: commitment_info.clone(),
                     },
                     &[seeds],
                 ),
@@ -745,315 +919,363 @@ pub mod darkpool {
                 space,
                 program_id,
             )?;
-            rate_limit_state = RateLimitAccount {
-                last_drop_at: 0,
-                bump: rate_limit_bump,
-            };
         }
 
-        if rate_limit_state.last_drop_at != 0 {
-            require!(
-                now - rate_limit_state.last_drop_at >= MIN_RATE_LIMIT_SECONDS,
-                DarkPoolError::RateLimitExceeded
-            );
-        }
-        rate_limit_state.last_drop_at = now;
-        rate_limit_state.bump = rate_limit_bump;
-        let mut rate_limit_data = rate_limit_info.try_borrow_mut_data()?;
-        let mut rate_limit_cursor: &mut [u8] = &mut rate_limit_data;
-        rate_limit_state.try_serialize(&mut rate_limit_cursor)?;
+        let commitment_state = ClaimCommitment {
+            drop: *drop_info.key,
+            claimer: claimer_key,
+            commitment,
+            committed_slot: clock.slot,
+            bump: commitment_bump,
+        };
+        let mut commitment_data = commitment_info.try_borrow_mut_data()?;
+        let mut commitment_cursor: &mut [u8] = &mut commitment_data;
+        commitment_state.try_serialize(&mut commitment_cursor)?;
+
+        emit!(ClaimCommitted {
+            drop: *drop_info.key,
+            claimer: claimer_key,
+            slot: clock.slot,
+        });
 
-        let ix = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.payer.key(),
-            &ctx.accounts.sol_vault.key(),
-            amount,
+        Ok(())
+    }
+
+    pub fn reveal_claim<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimDrop<'info>>,
+        secret: [u8; 32],
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let drop_info = ctx.accounts.drop.to_account_info();
+        let claimer_info = ctx.accounts.claimer.to_account_info();
+        let commitment_info = ctx
+            .accounts
+            .claim_commitment
+            .as_ref()
+            .ok_or(DarkPoolError::MissingClaimCommitment)?
+            .to_account_info();
+        let (commitment_pda, commitment_bump) = Pubkey::find_program_address(
+            &[b"claim_commit", drop_info.key.as_ref(), claimer_info.key.as_ref()],
+            program_id,
         );
-        anchor_lang::solana_program::program::invoke(
-            &ix,
-            &[
-                ctx.accounts.payer.to_account_info(),
-                ctx.accounts.sol_vault.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
 
+        require_keys_eq!(
+            commitment_pda,
+            *commitment_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require!(commitment_info.is_writable, ErrorCode::ConstraintMut);
+        require!(claimer_info.is_writable, ErrorCode::ConstraintMut);
         require!(
-            drop_info.owner == &system_program::ID && drop_info.lamports() == 0,
-            DarkPoolError::AccountAlreadyInitialized
+            commitment_info.owner == program_id,
+            ErrorCode::ConstraintOwner
         );
-        let drop_space = (8 + DropAccount::LEN) as u64;
-        let drop_lamports = Rent::get()?.minimum_balance(drop_space as usize);
-        let drop_seeds: &[&[u8]] = &[b"drop", nullifier.as_ref(), &[drop_bump]];
-        system_program::create_account(
-            CpiContext::new_with_signer(
-                system_program_info.clone(),
-                system_program::CreateAccount {
-                    from: ctx.accounts.payer.to_account_info(),
-                    to: drop_info.clone(),
-                },
-                &[drop_seeds],
-            ),
-            drop_lamports,
-            drop_space,
-            program_id,
-        )?;
 
-        let drop_state = DropAccount {
-            nullifier,
-            recipient: ctx.accounts.payer.key(),
-            amount,
-            asset_type,
-            created_at: now,
-            expires_at,
-            status: DropStatus::Active,
-            claimed_at: 0,
-            claimer: Pubkey::default(),
-            bump: drop_bump,
+        let commitment_state = {
+            let commitment_data = commitment_info.try_borrow_data()?;
+            ClaimCommitment::try_deserialize(&mut &commitment_data[..])?
         };
-        let mut drop_data = drop_info.try_borrow_mut_data()?;
-        let mut drop_cursor: &mut [u8] = &mut drop_data;
-        drop_state.try_serialize(&mut drop_cursor)?;
+        require_eq!(
+            commitment_state.bump,
+            commitment_bump,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            commitment_state.drop,
+            *drop_info.key,
+            DarkPoolError::InvalidClaimCommitment
+        );
+        require_keys_eq!(
+            commitment_state.claimer,
+            *claimer_info.key,
+            DarkPoolError::InvalidClaimCommitment
+        );
+        require!(
+            Clock::get()?.slot > commitment_state.committed_slot,
+            DarkPoolError::RevealTooEarly
+        );
+        require!(
+            claim_commitment_hash(&secret, claimer_info.key) == commitment_state.commitment,
+            DarkPoolError::InvalidClaimCommitment
+        );
 
-        let mut nullifier_state: NullifierAccount;
-        if nullifier_info.owner == program_id {
-            let mut data_slice: &[u8] = &nullifier_info.try_borrow_data()?;
-            nullifier_state = NullifierAccount::try_deserialize(&mut data_slice)?;
-            require_eq!(
-                nullifier_state.bump,
-                nullifier_bump,
-                ErrorCode::ConstraintSeeds
-            );
-        } else {
-            require!(
-                nullifier_info.owner == &system_program::ID && nullifier_info.lamports() == 0,
-                DarkPoolError::AccountAlreadyInitialized
-            );
-            let space = (8 + NullifierAccount::LEN) as u64;
-            let lamports = Rent::get()?.minimum_balance(space as usize);
-            let seeds: &[&[u8]] = &[b"nullifier", nullifier.as_ref(), &[nullifier_bump]];
-            system_program::create_account(
-                CpiContext::new_with_signer(
-                    system_program_info,
-                    system_program::CreateAccount {
-                        from: ctx.accounts.payer.to_account_info(),
-                        to: nullifier_info.clone(),
-                    },
-                    &[seeds],
-                ),
-                lamports,
-                space,
-                program_id,
-            )?;
-            nullifier_state = NullifierAccount {
-                nullifier: [0u8; 32],
-                is_used: false,
-                claimer: Pubkey::default(),
-                used_at: 0,
-                bump: nullifier_bump,
-            };
+        // The commitment is single-use: close it back to the claimer.
+        {
+            let mut claimer_lamports = claimer_info.try_borrow_mut_lamports()?;
+            let mut commitment_lamports = commitment_info.try_borrow_mut_lamports()?;
+            let new_claimer_lamports = claimer_lamports
+                .checked_add(**commitment_lamports)
+                .ok_or(DarkPoolError::NumericalOverflow)?;
+            **claimer_lamports = new_claimer_lamports;
+            **commitment_lamports = 0;
+            commitment_info.try_borrow_mut_data()?.fill(0);
         }
 
-        if nullifier_state.nullifier == [0u8; 32] {
-            nullifier_state.nullifier = nullifier;
-            nullifier_state.is_used = false;
-            nullifier_state.claimer = Pubkey::default();
-            nullifier_state.used_at = 0;
-            nullifier_state.bump = nullifier_bump;
-        } else {
-            require!(
-                nullifier_state.nullifier == nullifier,
-                DarkPoolError::InvalidNullifier
-            );
+        process_claim_drop(ctx, secret, true)
+    }
+
+    /// Claimer-only: closes the caller's commitment, for example after someone else claimed
+    /// the drop, and returns its rent.
+    pub fn close_claim_commitment(ctx: Context<CloseClaimCommitment>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let commitment_info = ctx.accounts.claim_commitment.to_account_info();
+        let claimer_info = ctx.accounts.claimer.to_account_info();
+
+        require!(commitment_info.is_writable, ErrorCode::ConstraintMut);
+        require!(claimer_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            commitment_info.owner == program_id,
+            ErrorCode::ConstraintOwner
+        );
+
+        let commitment_state = {
+            let commitment_data = commitment_info.try_borrow_data()?;
+            ClaimCommitment::try_deserialize(&mut &commitment_data[..])?
+        };
+        let (commitment_pda, commitment_bump) = Pubkey::find_program_address(
+            &[
+                b"claim_commit",
+                commitment_state.drop.as_ref(),
+                claimer_info.key.as_ref(),
+            ],
+            program_id,
+        );
+        require_keys_eq!(
+            commitment_pda,
+            *commitment_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_eq!(
+            commitment_state.bump,
+            commitment_bump,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            commitment_state.claimer,
+            *claimer_info.key,
+            DarkPoolError::InvalidClaimCommitment
+        );
+
+        {
+            let mut claimer_lamports = claimer_info.try_borrow_mut_lamports()?;
+            let mut commitment_lamports = commitment_info.try_borrow_mut_lamports()?;
+            let new_claimer_lamports = claimer_lamports
+                .checked_add(**commitment_lamports)
+                .ok_or(DarkPoolError::NumericalOverflow)?;
+            **claimer_lamports = new_claimer_lamports;
+            **commitment_lamports = 0;
         }
-        let mut nullifier_data = nullifier_info.try_borrow_mut_data()?;
-        let mut nullifier_cursor: &mut [u8] = &mut nullifier_data;
-        nullifier_state.try_serialize(&mut nullifier_cursor)?;
+        commitment_info.try_borrow_mut_data()?.fill(0);
 
-        emit!(DropCreated {
-            nullifier,
-            recipient: ctx.accounts.payer.key(),
-            amount,
-            asset_type,
-            expires_at,
-            payer: ctx.accounts.payer.key(),
+        emit!(ClaimCommitmentClosed {
+            drop: commitment_state.drop,
+            claimer: commitment_state.claimer,
         });
 
         Ok(())
     }
 
-    pub fn claim_drop(ctx: Context<ClaimDrop>, nullifier: [u8; 32]) -> Result<()> {
+    pub fn claim_drop_to_shielded(
+        ctx: Context<ClaimDropToShielded>,
+        secret: [u8; 32],
+        commitment: [u8; 32],
+    ) -> Result<()> {
         let clock = Clock::get()?;
         let now = clock.unix_timestamp;
         let program_id = ctx.program_id;
+        require_not_paused(&ctx.accounts.config, PAUSE_CLAIM)?;
+        require_outflow_open(&ctx.accounts.config)?;
 
-        let (drop_pda, drop_bump) =
-            Pubkey::find_program_address(&[b"drop", nullifier.as_ref()], program_id);
-        let (nullifier_pda, nullifier_bump) =
-            Pubkey::find_program_address(&[b"nullifier", nullifier.as_ref()], program_id);
         let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
         let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+        let (shielded_config_pda, _) =
+            Pubkey::find_program_address(&[b"shielded_config"], program_id);
+        let (shielded_tree_pda, _) = Pubkey::find_program_address(&[b"shielded_tree"], program_id);
+        let (shielded_vault_pda, _) =
+            Pubkey::find_program_address(&[b"shielded_vault"], program_id);
 
         let drop_info = ctx.accounts.drop.to_account_info();
         let nullifier_info = ctx.accounts.nullifier_account.to_account_info();
         let config_info = ctx.accounts.config.to_account_info();
         let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
-        let treasury_info = ctx.accounts.treasury.to_account_info();
+        let shielded_config_info = ctx.accounts.shielded_config.to_account_info();
+        let shielded_tree_info = ctx.accounts.shielded_tree.to_account_info();
+        let shielded_vault_info = ctx.accounts.shielded_vault.to_account_info();
         let system_program_info = ctx.accounts.system_program.to_account_info();
 
-        require_keys_eq!(drop_pda, *drop_info.key, ErrorCode::ConstraintSeeds);
-        require_keys_eq!(
-            nullifier_pda,
-            *nullifier_info.key,
-            ErrorCode::ConstraintSeeds
-        );
         require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
         require_keys_eq!(
             sol_vault_pda,
             *sol_vault_info.key,
             ErrorCode::ConstraintSeeds
         );
+        require_keys_eq!(
+            shielded_config_pda,
+            *shielded_config_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            shielded_tree_pda,
+            *shielded_tree_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            shielded_vault_pda,
+            *shielded_vault_info.key,
+            ErrorCode::ConstraintSeeds
+        );
         require_keys_eq!(
             system_program::ID,
             *system_program_info.key,
             ErrorCode::ConstraintAddress
         );
-        require!(drop_info.is_writable, ErrorCode::ConstraintMut);
-        require!(nullifier_info.is_writable, ErrorCode::ConstraintMut);
         require!(sol_vault_info.is_writable, ErrorCode::ConstraintMut);
-        require!(treasury_info.is_writable, ErrorCode::ConstraintMut);
-        require!(
-            ctx.accounts.claimer.to_account_info().is_writable,
-            ErrorCode::ConstraintMut
-        );
+        require!(shielded_config_info.is_writable, ErrorCode::ConstraintMut);
+        require!(shielded_tree_info.is_writable, ErrorCode::ConstraintMut);
+        require!(shielded_vault_info.is_writable, ErrorCode::ConstraintMut);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
 
-        require!(drop_info.owner == program_id, ErrorCode::ConstraintOwner);
+        let (mut drop_state, mut nullifier_state) =
+            load_claimable_drop(program_id, &drop_info, &nullifier_info, &secret, now)?;
+        let nullifier = drop_state.nullifier;
+        enforce_claim_policy(
+            drop_info.key,
+            &drop_state,
+            ctx.accounts.claimer.key,
+            ctx.accounts
+                .instructions_sysvar
+                .as_ref()
+                .map(|sysvar| sysvar.as_ref()),
+            false,
+        )?;
         require!(
-            nullifier_info.owner == program_id,
-            ErrorCode::ConstraintOwner
+            drop_state.max_claims == 0 && !drop_state.is_vesting(),
+            DarkPoolError::PartialClaimNotSupported
         );
 
-        let mut drop_state = {
-            let drop_data = drop_info.try_borrow_data()?;
-            DropAccount::try_deserialize(&mut &drop_data[..])?
-        };
-        require_eq!(drop_state.bump, drop_bump, ErrorCode::ConstraintSeeds);
-
-        let mut nullifier_state = {
-            let nullifier_data = nullifier_info.try_borrow_data()?;
-            NullifierAccount::try_deserialize(&mut &nullifier_data[..])?
-        };
-        require_eq!(
-            nullifier_state.bump,
-            nullifier_bump,
-            ErrorCode::ConstraintSeeds
-        );
+        let config_state = &ctx.accounts.config;
         require!(
-            nullifier_state.nullifier == nullifier,
-            DarkPoolError::InvalidNullifier
+            config_state.is_initialized,
+            DarkPoolError::ConfigNotInitialized
         );
 
         require!(
-            drop_state.status == DropStatus::Active,
-            DarkPoolError::DropNotActive
+            ctx.accounts.shielded_config.is_initialized,
+            DarkPoolError::ShieldedConfigNotInitialized
         );
-
+        require!(commitment != [0u8; 32], DarkPoolError::InvalidCommitment);
         require!(
-            !nullifier_state.is_used,
-            DarkPoolError::NullifierAlreadyUsed
+            drop_state.asset_type == ASSET_TYPE_SOL,
+            DarkPoolError::InvalidAssetType
         );
-
         require!(
-            drop_state.nullifier == nullifier,
-            DarkPoolError::InvalidNullifier
+            ctx.accounts.shielded_tree.depth == ctx.accounts.shielded_config.tree_depth,
+            DarkPoolError::InvalidTreeDepth
         );
-
-        if drop_state.expires_at != 0 && now > drop_state.expires_at {
-            drop_state.status = DropStatus::Expired;
-            let mut drop_data = drop_info.try_borrow_mut_data()?;
-            let mut drop_cursor: &mut [u8] = &mut drop_data;
-            drop_state.try_serialize(&mut drop_cursor)?;
-            return err!(DarkPoolError::DropExpired);
-        }
-
-        let config_state = &ctx.accounts.config;
+        let max_leaves = max_leaves_for_depth(ctx.accounts.shielded_config.tree_depth)?;
         require!(
-            config_state.is_initialized,
-            DarkPoolError::ConfigNotInitialized
+            ctx.accounts.shielded_config.next_leaf_index < max_leaves,
+            DarkPoolError::ShieldedTreeFull
         );
         require!(
-            treasury_info.key() == config_state.treasury,
-            DarkPoolError::InvalidTreasury
+            ctx.accounts.shielded_tree.next_leaf_index
+                == ctx.accounts.shielded_config.next_leaf_index,
+            DarkPoolError::ShieldedStateMismatch
         );
-        let fee = (drop_state.amount as u128)
-            .checked_mul(config_state.fee_bps as u128)
-            .unwrap_or(0)
-            .checked_div(10_000)
-            .unwrap_or(0) as u64;
+
+        let fee = if drop_state.fee_prepaid {
+            0
+        } else {
+            config_state
+                .fee_schedule(ASSET_TYPE_SOL)
+                .fee_for(drop_state.amount)
+        };
         let payout = drop_state.amount.saturating_sub(fee);
+        require!(payout > 0, DarkPoolError::InvalidAmount);
+        let partner_info = ctx
+            .accounts
+            .partner
+            .as_ref()
+            .map(|partner| partner.to_account_info());
+        let mut partner_state =
+            load_referrer_partner(program_id, &drop_state, partner_info.as_ref())?;
+        let partner_share = partner_state
+            .as_ref()
+            .map_or(0, |partner| drop_fee(fee, partner.share_bps));
 
         require!(
             sol_vault_info.lamports() >= drop_state.amount,
             DarkPoolError::InsufficientVaultBalance
         );
-        let vault_seeds = &[b"sol_vault".as_ref(), &[config_state.sol_vault_bump]];
-        let ix = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.sol_vault.key(),
-            &ctx.accounts.claimer.key(),
+        transfer_from_sol_vault(
+            &sol_vault_info,
+            &shielded_vault_info,
+            &system_program_info,
+            config_state.sol_vault_bump,
             payout,
-        );
-        anchor_lang::solana_program::program::invoke_signed(
-            &ix,
-            &[
-                sol_vault_info.clone(),
-                ctx.accounts.claimer.to_account_info(),
-                system_program_info.clone(),
-            ],
-            &[vault_seeds],
         )?;
-        if fee > 0 {
-            let fee_ix = anchor_lang::solana_program::system_instruction::transfer(
-                &ctx.accounts.sol_vault.key(),
-                &ctx.accounts.treasury.key(),
-                fee,
-            );
-            anchor_lang::solana_program::program::invoke_signed(
-                &fee_ix,
-                &[
-                    sol_vault_info,
-                    treasury_info,
-                    system_program_info,
-                ],
-                &[vault_seeds],
-            )?;
+        if let (Some(partner_info), Some(partner_state)) =
+            (partner_info.as_ref(), partner_state.as_mut())
+        {
+            if partner_share > 0 {
+                record_partner_payment(
+                    &mut ctx.accounts.config,
+                    partner_info,
+                    partner_state,
+                    ASSET_TYPE_SOL,
+                    partner_share,
+                )?;
+                emit!(PartnerPaid {
+                    wallet: partner_state.wallet,
+                    nullifier,
+                    asset_type: ASSET_TYPE_SOL,
+                    amount: partner_share,
+                });
+            }
         }
+        release_sol_liability(&mut ctx.accounts.config, drop_state.amount)?;
+        accrue_sol_fee(&mut ctx.accounts.config, fee - partner_share)?;
+        record_sol_outflow(&mut ctx.accounts.config, payout, now)?;
 
-        drop_state.status = DropStatus::Claimed;
-        drop_state.claimed_at = now;
-        drop_state.claimer = ctx.accounts.claimer.key();
-
-        nullifier_state.is_used = true;
-        nullifier_state.used_at = now;
-        nullifier_state.claimer = ctx.accounts.claimer.key();
-
-        let mut drop_data = drop_info.try_borrow_mut_data()?;
-        let mut drop_cursor: &mut [u8] = &mut drop_data;
-        drop_state.try_serialize(&mut drop_cursor)?;
+        let leaf_index;
+        let new_root;
+        {
+            let tree_state = &mut ctx.accounts.shielded_tree;
+            let insert = merkle_insert(tree_state, commitment)?;
+            leaf_index = insert.0;
+            new_root = insert.1;
+        }
+        ctx.accounts.shielded_config.current_root = new_root;
+        ctx.accounts.shielded_config.next_leaf_index =
+            ctx.accounts.shielded_tree.next_leaf_index;
 
-        let mut nullifier_data = nullifier_info.try_borrow_mut_data()?;
-        let mut nullifier_cursor: &mut [u8] = &mut nullifier_data;
-        nullifier_state.try_serialize(&mut nullifier_cursor)?;
+        // The claimer is left out of the drop, nullifier and event. It still signs and pays for
+        // this transaction, though, so the note is only as unlinkable as that wallet.
+        mark_drop_claimed(
+            &drop_info,
+            &nullifier_info,
+            &mut drop_state,
+            &mut nullifier_state,
+            Pubkey::default(),
+            now,
+        )?;
 
         emit!(DropClaimed {
             nullifier,
-            claimer: ctx.accounts.claimer.key(),
+            claimer: Pubkey::default(),
             claimed_at: now,
+            amount: drop_state.amount,
+            fee,
+            payout,
+            mint_fee: 0,
+        });
+        emit!(ShieldedDeposit {
+            commitment,
+            amount: payout,
+            leaf_index,
+            new_root,
         });
 
-        msg!(
-            "Drop claimed: nullifier={:?}, claimer={}",
-            nullifier,
-            ctx.accounts.claimer.key()
-        );
         Ok(())
     }
 
@@ -1084,11 +1306,14 @@ pub mod darkpool {
 
         config.pending_authority = new_authority;
         config.pending_authority_set_at = Clock::get()?.unix_timestamp;
+        config.pending_authority_expires_at =
+            config.proposal_expiry_from(config.pending_authority_set_at);
 
         emit!(AuthorityProposed {
             current_authority: ctx.accounts.authority.key(),
             pending_authority: new_authority,
             delay_seconds: config.authority_delay_seconds,
+            expires_at: config.authority_proposal_expires_at(),
         });
 
         Ok(())
@@ -1118,6 +1343,7 @@ pub mod darkpool {
         let cancelled_authority = config.pending_authority;
         config.pending_authority = Pubkey::default();
         config.pending_authority_set_at = 0;
+        config.pending_authority_expires_at = 0;
 
         emit!(AuthorityProposalCancelled {
             authority: ctx.accounts.authority.key(),
@@ -1152,11 +1378,16 @@ pub mod darkpool {
             now - config.pending_authority_set_at >= config.authority_delay_seconds,
             DarkPoolError::AuthorityDelayNotElapsed
         );
+        require!(
+            now < config.authority_proposal_expires_at(),
+            DarkPoolError::ProposalExpired
+        );
 
         let previous_authority = config.authority;
         config.authority = ctx.accounts.pending_authority.key();
         config.pending_authority = Pubkey::default();
         config.pending_authority_set_at = 0;
+        config.pending_authority_expires_at = 0;
 
         emit!(AuthorityAccepted {
             previous_authority,
@@ -1166,6 +1397,40 @@ pub mod darkpool {
         Ok(())
     }
 
+    /// Permissionless: drops a pending authority whose acceptance window has closed.
+    pub fn clear_expired_proposal(ctx: Context<ApplyConfigChange>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            config.pending_authority != Pubkey::default(),
+            DarkPoolError::NoPendingAuthority
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let expired_at = config.authority_proposal_expires_at();
+        require!(now >= expired_at, DarkPoolError::ProposalNotExpired);
+
+        let expired_authority = config.pending_authority;
+        config.pending_authority = Pubkey::default();
+        config.pending_authority_set_at = 0;
+        config.pending_authority_expires_at = 0;
+
+        emit!(AuthorityProposalExpired {
+            expired_authority,
+            expired_at,
+        });
+
+        Ok(())
+    }
+
     pub fn update_authority_delay(
         ctx: Context<UpdateAuthorityDelay>,
         new_delay_seconds: i64,
@@ -1201,1688 +1466,13886 @@ pub mod darkpool {
         Ok(())
     }
 
-    pub fn expire_drop(ctx: Context<ExpireDrop>, nullifier: [u8; 32]) -> Result<()> {
-        let clock = Clock::get()?;
-        let now = clock.unix_timestamp;
+    /// Authority-only: sets how long a pending authority may accept after the delay.
+    pub fn update_acceptance_window(
+        ctx: Context<UpdateAuthorityDelay>,
+        new_window_seconds: i64,
+    ) -> Result<()> {
         let program_id = ctx.program_id;
-
-        let (drop_pda, drop_bump) =
-            Pubkey::find_program_address(&[b"drop", nullifier.as_ref()], program_id);
-        let (nullifier_pda, nullifier_bump) =
-            Pubkey::find_program_address(&[b"nullifier", nullifier.as_ref()], program_id);
         let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
-
-        let drop_info = ctx.accounts.drop.to_account_info();
-        let nullifier_info = ctx.accounts.nullifier_account.to_account_info();
         let config_info = ctx.accounts.config.to_account_info();
-        let rent_collector_info = ctx.accounts.rent_collector.to_account_info();
-
-        require_keys_eq!(drop_pda, *drop_info.key, ErrorCode::ConstraintSeeds);
-        require_keys_eq!(
-            nullifier_pda,
-            *nullifier_info.key,
-            ErrorCode::ConstraintSeeds
-        );
         require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
-        require_keys_neq!(*drop_info.key, *nullifier_info.key, ErrorCode::ConstraintAddress);
-        require_keys_neq!(
-            *rent_collector_info.key,
-            *drop_info.key,
-            ErrorCode::ConstraintAddress
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
         );
-        require_keys_neq!(
-            *rent_collector_info.key,
-            *nullifier_info.key,
-            ErrorCode::ConstraintAddress
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
         );
-        require!(drop_info.is_writable, ErrorCode::ConstraintMut);
-        require!(nullifier_info.is_writable, ErrorCode::ConstraintMut);
-        require!(rent_collector_info.is_writable, ErrorCode::ConstraintMut);
-
-        require!(drop_info.owner == program_id, ErrorCode::ConstraintOwner);
         require!(
-            nullifier_info.owner == program_id,
-            ErrorCode::ConstraintOwner
+            (MIN_ACCEPTANCE_WINDOW_SECONDS..=MAX_ACCEPTANCE_WINDOW_SECONDS)
+                .contains(&new_window_seconds),
+            DarkPoolError::InvalidAcceptanceWindow
         );
 
-        let drop_state = {
-            let drop_data = drop_info.try_borrow_data()?;
-            DropAccount::try_deserialize(&mut &drop_data[..])?
-        };
-        require_eq!(drop_state.bump, drop_bump, ErrorCode::ConstraintSeeds);
+        let config = &mut ctx.accounts.config;
+        config.authority_acceptance_window_seconds = new_window_seconds;
 
-        let nullifier_state = {
-            let nullifier_data = nullifier_info.try_borrow_data()?;
-            NullifierAccount::try_deserialize(&mut &nullifier_data[..])?
-        };
-        require_eq!(
-            nullifier_state.bump,
-            nullifier_bump,
-            ErrorCode::ConstraintSeeds
-        );
+        emit!(AcceptanceWindowUpdated {
+            authority: ctx.accounts.authority.key(),
+            new_window_seconds,
+        });
+
+        Ok(())
+    }
+
+    /// Queues a new `fee_bps`; it takes effect once `authority_delay_seconds` has passed.
+    pub fn propose_fee_change(ctx: Context<ProposeConfigChange>, new_fee_bps: u16) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config = &mut ctx.accounts.config;
         require!(
-            nullifier_state.nullifier == nullifier,
-            DarkPoolError::InvalidNullifier
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
         );
-
         require!(
-            ctx.accounts.config.authority == ctx.accounts.authority.key(),
+            ctx.accounts.authority.key() == config.authority,
             DarkPoolError::UnauthorizedCreator
         );
+        let policy = load_policy(program_id, ctx.accounts.policy.as_ref())?;
         require!(
-            ctx.accounts.config.is_initialized,
-            DarkPoolError::ConfigNotInitialized
+            new_fee_bps <= policy.max_fee_bps,
+            DarkPoolError::InvalidFeeBps
         );
+
+        if config.pending_fee_effective_at > 0 {
+            emit!(FeeChangeCancelled {
+                cancelled_fee_bps: config.pending_fee_bps,
+            });
+        }
+        let effective_at = Clock::get()?
+            .unix_timestamp
+            .saturating_add(config.authority_delay_seconds);
+        config.pending_fee_bps = new_fee_bps;
+        config.pending_fee_effective_at = effective_at;
+
+        emit!(FeeChangeProposed {
+            current_fee_bps: config.fee_bps,
+            new_fee_bps,
+            effective_at,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: applies the pending `fee_bps` after the delay if it is still within the
+    /// policy cap.
+    pub fn apply_fee_change(ctx: Context<ApplyFeeChange>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config = &mut ctx.accounts.config;
         require!(
-            drop_state.nullifier == nullifier,
-            DarkPoolError::InvalidNullifier
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
         );
         require!(
-            drop_state.status == DropStatus::Active,
-            DarkPoolError::DropNotActive
+            config.pending_fee_effective_at > 0,
+            DarkPoolError::NoPendingConfigChange
         );
         require!(
-            drop_state.expires_at != 0 && now > drop_state.expires_at,
-            DarkPoolError::DropNotExpired
+            Clock::get()?.unix_timestamp >= config.pending_fee_effective_at,
+            DarkPoolError::AuthorityDelayNotElapsed
         );
+        // The policy cap may have been lowered since the change was proposed.
+        let policy = load_policy(program_id, ctx.accounts.policy.as_ref())?;
         require!(
-            !nullifier_state.is_used,
-            DarkPoolError::NullifierAlreadyUsed
+            config.pending_fee_bps <= policy.max_fee_bps,
+            DarkPoolError::InvalidFeeBps
         );
 
-        emit!(DropExpired {
-            nullifier,
-            recipient: drop_state.recipient,
-            expires_at: drop_state.expires_at,
+        let previous_fee_bps = config.fee_bps;
+        config.fee_bps = config.pending_fee_bps;
+        config.pending_fee_bps = 0;
+        config.pending_fee_effective_at = 0;
+
+        emit!(FeeChangeApplied {
+            previous_fee_bps,
+            new_fee_bps: config.fee_bps,
         });
 
-        // Close the drop and nullifier accounts to the rent collector.
-        let mut rent_lamports = rent_collector_info.try_borrow_mut_lamports()?;
-        let mut drop_lamports = drop_info.try_borrow_mut_lamports()?;
-        let new_rent_lamports = rent_lamports
-            .checked_add(**drop_lamports)
-            .ok_or(DarkPoolError::NumericalOverflow)?;
-        **rent_lamports = new_rent_lamports;
-        **drop_lamports = 0;
-        drop_info.try_borrow_mut_data()?.fill(0);
+        Ok(())
+    }
 
-        let mut nullifier_lamports = nullifier_info.try_borrow_mut_lamports()?;
-        let new_rent_lamports = rent_lamports
-            .checked_add(**nullifier_lamports)
-            .ok_or(DarkPoolError::NumericalOverflow)?;
-        **rent_lamports = new_rent_lamports;
-        **nullifier_lamports = 0;
-        nullifier_info.try_borrow_mut_data()?.fill(0);
+    /// Queues a new treasury; it takes effect once `authority_delay_seconds` has passed.
+    pub fn propose_treasury_change(
+        ctx: Context<ProposeConfigChange>,
+        new_treasury: Pubkey,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        require!(
+            new_treasury != Pubkey::default(),
+            DarkPoolError::InvalidTreasury
+        );
+
+        if config.pending_treasury_effective_at > 0 {
+            emit!(TreasuryChangeCancelled {
+                cancelled_treasury: config.pending_treasury,
+            });
+        }
+        let effective_at = Clock::get()?
+            .unix_timestamp
+            .saturating_add(config.authority_delay_seconds);
+        config.pending_treasury = new_treasury;
+        config.pending_treasury_effective_at = effective_at;
+
+        emit!(TreasuryChangeProposed {
+            current_treasury: config.treasury,
+            new_treasury,
+            effective_at,
+        });
 
         Ok(())
     }
-}
 
-pub struct Initialize<'info> {
-    pub config: UncheckedAccount<'info>,
-    pub authority: Signer<'info>,
-    pub sol_vault: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-}
+    /// Permissionless: applies the pending treasury after the delay.
+    pub fn apply_treasury_change(ctx: Context<ApplyConfigChange>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
 
-pub struct InitializeShielded<'info> {
-    pub shielded_config: UncheckedAccount<'info>,
-    pub authority: Signer<'info>,
-    pub shielded_vault: UncheckedAccount<'info>,
-    pub shielded_tree: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-}
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            config.pending_treasury_effective_at > 0,
+            DarkPoolError::NoPendingConfigChange
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= config.pending_treasury_effective_at,
+            DarkPoolError::AuthorityDelayNotElapsed
+        );
 
-impl<'info> Bumps for InitializeShielded<'info> {
-    type Bumps = ();
-}
+        let previous_treasury = config.treasury;
+        config.treasury = config.pending_treasury;
+        config.pending_treasury = Pubkey::default();
+        config.pending_treasury_effective_at = 0;
 
-impl<'info> Accounts<'info, ()> for InitializeShielded<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let shielded_config =
-            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let shielded_vault =
-            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let shielded_tree =
-            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let system_program =
-            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self {
-            shielded_config,
-            authority,
-            shielded_vault,
-            shielded_tree,
-            system_program,
-        })
-    }
-}
+        emit!(TreasuryChangeApplied {
+            previous_treasury,
+            new_treasury: config.treasury,
+        });
 
-impl<'info> ToAccountMetas for InitializeShielded<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.shielded_config.to_account_metas(override_signer));
-        metas.extend(self.authority.to_account_metas(override_signer));
-        metas.extend(self.shielded_vault.to_account_metas(override_signer));
-        metas.extend(self.shielded_tree.to_account_metas(override_signer));
-        metas.extend(self.system_program.to_account_metas(override_signer));
-        metas
+        Ok(())
     }
-}
 
-impl<'info> ToAccountInfos<'info> for InitializeShielded<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.shielded_config.to_account_infos());
-        infos.extend(self.authority.to_account_infos());
-        infos.extend(self.shielded_vault.to_account_infos());
-        infos.extend(self.shielded_tree.to_account_infos());
-        infos.extend(self.system_program.to_account_infos());
-        infos
-    }
-}
+    /// Queues a fee schedule for one asset type behind `authority_delay_seconds`.
+    pub fn propose_fee_schedule(
+        ctx: Context<ProposeConfigChange>,
+        asset_type: u8,
+        schedule: FeeSchedule,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
 
-impl<'info> AccountsExit<'info> for InitializeShielded<'info> {}
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        require!(
+            asset_type == ASSET_TYPE_SOL || asset_type == ASSET_TYPE_TOKEN,
+            DarkPoolError::InvalidAssetType
+        );
+        schedule.validate(load_policy(program_id, ctx.accounts.policy.as_ref())?.max_fee_bps)?;
 
-pub(crate) mod __client_accounts_initialize_shielded {
-    use super::*;
-    use anchor_lang::prelude::borsh;
+        if config.pending_fee_schedule_effective_at > 0 {
+            emit!(FeeScheduleCancelled {
+                asset_type: config.pending_fee_schedule_asset_type,
+                schedule: config.pending_fee_schedule,
+            });
+        }
+        let effective_at = Clock::get()?
+            .unix_timestamp
+            .saturating_add(config.authority_delay_seconds);
+        config.pending_fee_schedule = schedule;
+        config.pending_fee_schedule_asset_type = asset_type;
+        config.pending_fee_schedule_effective_at = effective_at;
+
+        emit!(FeeScheduleProposed {
+            asset_type,
+            schedule,
+            effective_at,
+        });
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct InitializeShielded {
-        pub shielded_config: Pubkey,
-        pub authority: Pubkey,
-        pub shielded_vault: Pubkey,
-        pub shielded_tree: Pubkey,
-        pub system_program: Pubkey,
+        Ok(())
     }
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for InitializeShielded {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.shielded_config,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.shielded_vault,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.shielded_tree,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    self.system_program,
-                    false,
-                ),
-            ]
+    /// Permissionless: applies the pending fee schedule after the delay if it is still within
+    /// the policy cap.
+    pub fn apply_fee_schedule(ctx: Context<ApplyFeeChange>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            config.pending_fee_schedule_effective_at > 0,
+            DarkPoolError::NoPendingConfigChange
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= config.pending_fee_schedule_effective_at,
+            DarkPoolError::AuthorityDelayNotElapsed
+        );
+
+        let asset_type = config.pending_fee_schedule_asset_type;
+        let schedule = config.pending_fee_schedule;
+        schedule.validate(load_policy(program_id, ctx.accounts.policy.as_ref())?.max_fee_bps)?;
+        if asset_type == ASSET_TYPE_TOKEN {
+            config.token_fee_schedule = schedule;
+        } else {
+            config.sol_fee_schedule = schedule;
         }
+        config.pending_fee_schedule = FeeSchedule::default();
+        config.pending_fee_schedule_asset_type = 0;
+        config.pending_fee_schedule_effective_at = 0;
+
+        emit!(FeeScheduleApplied {
+            asset_type,
+            schedule,
+        });
+
+        Ok(())
     }
-}
+
+    /// Authority-only: sets the key allowed to pause without the timelock; default clears it.
+    pub fn set_pause_guardian(ctx: Context<SetPauseGuardian>, guardian: Pubkey) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+
+        let previous_guardian = config.pause_guardian;
+        config.pause_guardian = guardian;
+
+        emit!(PauseGuardianUpdated {
+            previous_guardian,
+            new_guardian: guardian,
+        });
+
+        Ok(())
+    }
+
+    /// Guardian or authority: halts the instruction groups in `flags` immediately.
+    pub fn pause(ctx: Context<SetPause>, flags: u8) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == config.authority
+                || (config.pause_guardian != Pubkey::default() && signer == config.pause_guardian),
+            DarkPoolError::UnauthorizedPauser
+        );
+        require!(
+            flags != 0 && flags & !PAUSE_ALL == 0,
+            DarkPoolError::InvalidPauseFlags
+        );
+
+        config.paused |= flags;
+
+        emit!(ProgramPaused {
+            paused_by: signer,
+            flags,
+            paused: config.paused,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: resumes the instruction groups in `flags`.
+    pub fn unpause(ctx: Context<SetPause>, flags: u8) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.signer.key() == config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        require!(
+            flags != 0 && flags & !PAUSE_ALL == 0,
+            DarkPoolError::InvalidPauseFlags
+        );
+
+        config.paused &= !flags;
+
+        emit!(ProgramUnpaused {
+            unpaused_by: ctx.accounts.signer.key(),
+            flags,
+            paused: config.paused,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: sets the outflow velocity limit; a zero limit disables the breaker.
+    pub fn set_outflow_limit(
+        ctx: Context<ConfigureOutflowLimit>,
+        window_seconds: i64,
+        limit: u64,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        require!(
+            limit == 0 || (window_seconds > 0 && window_seconds <= MAX_OUTFLOW_WINDOW),
+            DarkPoolError::InvalidOutflowLimit
+        );
+
+        config.outflow_window_seconds = window_seconds;
+        config.outflow_limit = limit;
+
+        emit!(OutflowLimitUpdated {
+            window_seconds,
+            limit,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: re-enables claims after the breaker tripped and starts a fresh window.
+    pub fn reset_outflow_breaker(ctx: Context<ConfigureOutflowLimit>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+
+        let outflow = config.outflow_in_window;
+        config.outflow_tripped = false;
+        config.outflow_window_start = now;
+        config.outflow_in_window = 0;
+
+        emit!(OutflowBreakerReset {
+            reset_by: ctx.accounts.authority.key(),
+            outflow,
+            reset_at: now,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: queues new policy values behind `authority_delay_seconds`, creating the
+    /// policy account with the built-in defaults on first use.
+    pub fn propose_policy_update(
+        ctx: Context<ProposePolicyUpdate>,
+        values: PolicyValues,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (policy_pda, policy_bump) = Pubkey::find_program_address(&[b"policy"], program_id);
+
+        let policy_info = ctx.accounts.policy.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.config.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(policy_pda, *policy_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(policy_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.payer.to_account_info().is_writable,
+            ErrorCode::ConstraintMut
+        );
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        values.validate()?;
+
+        let mut policy_state = if policy_info.owner == program_id {
+            let policy_data = policy_info.try_borrow_data()?;
+            PolicyParams::try_deserialize(&mut &policy_data[..])?
+        } else {
+            create_policy_account(
+                program_id,
+                &policy_info,
+                &ctx.accounts.payer.to_account_info(),
+                &system_program_info,
+                policy_bump,
+            )?
+        };
+
+        if policy_state.pending_effective_at > 0 {
+            emit!(PolicyUpdateCancelled {
+                values: policy_state.pending,
+            });
+        }
+        let effective_at = Clock::get()?
+            .unix_timestamp
+            .saturating_add(ctx.accounts.config.authority_delay_seconds);
+        policy_state.pending = values;
+        policy_state.pending_effective_at = effective_at;
+        let mut policy_data = policy_info.try_borrow_mut_data()?;
+        let mut policy_cursor: &mut [u8] = &mut policy_data;
+        policy_state.try_serialize(&mut policy_cursor)?;
+
+        emit!(PolicyUpdateProposed {
+            values,
+            effective_at,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: applies the pending policy values after the delay. Fails while the fees
+    /// in force exceed the new `max_fee_bps`; lower them first.
+    pub fn apply_policy_update(ctx: Context<ApplyPolicyUpdate>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (policy_pda, policy_bump) = Pubkey::find_program_address(&[b"policy"], program_id);
+        let policy_info = ctx.accounts.policy.to_account_info();
+
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.config.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(policy_pda, *policy_info.key, ErrorCode::ConstraintSeeds);
+        require!(policy_info.is_writable, ErrorCode::ConstraintMut);
+        require!(policy_info.owner == program_id, ErrorCode::ConstraintOwner);
+
+        let mut policy_state = {
+            let policy_data = policy_info.try_borrow_data()?;
+            PolicyParams::try_deserialize(&mut &policy_data[..])?
+        };
+        require_eq!(policy_state.bump, policy_bump, ErrorCode::ConstraintSeeds);
+        require!(
+            policy_state.pending_effective_at > 0,
+            DarkPoolError::NoPendingConfigChange
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= policy_state.pending_effective_at,
+            DarkPoolError::AuthorityDelayNotElapsed
+        );
+        require_fees_within_cap(&ctx.accounts.config, policy_state.pending.max_fee_bps)?;
+
+        let previous = policy_state.values;
+        policy_state.values = policy_state.pending;
+        policy_state.pending = PolicyValues::default();
+        policy_state.pending_effective_at = 0;
+        let mut policy_data = policy_info.try_borrow_mut_data()?;
+        let mut policy_cursor: &mut [u8] = &mut policy_data;
+        policy_state.try_serialize(&mut policy_cursor)?;
+
+        emit!(PolicyUpdateApplied {
+            previous,
+            values: policy_state.values,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: queues a typed admin action that anyone may execute once
+    /// `authority_delay_seconds` has passed.
+    pub fn enqueue_admin_action(
+        ctx: Context<EnqueueAdminAction>,
+        action: AdminAction,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let now = Clock::get()?.unix_timestamp;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        let admin_action_info = ctx.accounts.admin_action.to_account_info();
+        let policy_info = ctx.accounts.policy.to_account_info();
+        let payer_info = ctx.accounts.payer.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+        require!(admin_action_info.is_writable, ErrorCode::ConstraintMut);
+        require!(payer_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        let policy = load_policy(program_id, &policy_info)?;
+        validate_admin_action(&action, &policy)?;
+
+        // A policy update needs the policy account in place by the time it executes.
+        if let AdminAction::SetPolicy { .. } = action {
+            if policy_info.owner != program_id {
+                require!(policy_info.is_writable, ErrorCode::ConstraintMut);
+                let (_, policy_bump) = Pubkey::find_program_address(&[b"policy"], program_id);
+                create_policy_account(
+                    program_id,
+                    &policy_info,
+                    &payer_info,
+                    &system_program_info,
+                    policy_bump,
+                )?;
+            }
+        }
+
+        let index = ctx.accounts.config.admin_action_count;
+        let index_bytes = index.to_le_bytes();
+        let (admin_action_pda, admin_action_bump) =
+            Pubkey::find_program_address(&[b"admin_action", &index_bytes], program_id);
+        require_keys_eq!(
+            admin_action_pda,
+            *admin_action_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require!(
+            admin_action_info.owner == &system_program::ID && admin_action_info.lamports() == 0,
+            DarkPoolError::AccountAlreadyInitialized
+        );
+        let space = (8 + QueuedAdminAction::LEN) as u64;
+        let lamports = Rent::get()?.minimum_balance(space as usize);
+        let seeds: &[&[u8]] = &[b"admin_action", &index_bytes, &[admin_action_bump]];
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program_info,
+                system_program::CreateAccount {
+                    from: payer_info,
+                    to: admin_action_info.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space,
+            program_id,
+        )?;
+
+        let eta = now.saturating_add(ctx.accounts.config.authority_delay_seconds);
+        let admin_action_state = QueuedAdminAction {
+            index,
+            action,
+            payer: ctx.accounts.payer.key(),
+            queued_at: now,
+            eta,
+            bump: admin_action_bump,
+            authority: ctx.accounts.authority.key(),
+            expires_at: eta.saturating_add(ctx.accounts.config.acceptance_window()),
+        };
+        let mut admin_action_data = admin_action_info.try_borrow_mut_data()?;
+        let mut admin_action_cursor: &mut [u8] = &mut admin_action_data;
+        admin_action_state.try_serialize(&mut admin_action_cursor)?;
+
+        let config = &mut ctx.accounts.config;
+        config.admin_action_count = index
+            .checked_add(1)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+
+        emit!(AdminActionQueued {
+            admin_action: admin_action_pda,
+            index,
+            action,
+            proposer: ctx.accounts.authority.key(),
+            eta,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: applies a queued admin action between its eta and expiry and closes it.
+    pub fn execute_admin_action(ctx: Context<ExecuteAdminAction>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let now = Clock::get()?.unix_timestamp;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        let admin_action_info = ctx.accounts.admin_action.to_account_info();
+        let policy_info = ctx.accounts.policy.to_account_info();
+
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        let admin_action_state = load_admin_action(program_id, &admin_action_info)?;
+        require!(
+            now >= admin_action_state.eta,
+            DarkPoolError::AuthorityDelayNotElapsed
+        );
+        require!(
+            now < admin_action_state.expires_at,
+            DarkPoolError::AdminActionExpired
+        );
+        require!(
+            admin_action_state.authority == ctx.accounts.config.authority,
+            DarkPoolError::AdminActionAuthorityChanged
+        );
+        // Policy bounds may have changed since the action was queued.
+        let policy = load_policy(program_id, &policy_info)?;
+        validate_admin_action(&admin_action_state.action, &policy)?;
+
+        let executor = ctx.accounts.executor.key();
+        let config = &mut ctx.accounts.config;
+        // A pending proposal for the same setting would otherwise revert the action when
+        // applied, so executing cancels it.
+        match admin_action_state.action {
+            AdminAction::SetFee { fee_bps } => {
+                if config.pending_fee_effective_at > 0 {
+                    emit!(FeeChangeCancelled {
+                        cancelled_fee_bps: config.pending_fee_bps,
+                    });
+                    config.pending_fee_bps = 0;
+                    config.pending_fee_effective_at = 0;
+                }
+                config.fee_bps = fee_bps;
+            }
+            AdminAction::SetTreasury { treasury } => {
+                if config.pending_treasury_effective_at > 0 {
+                    emit!(TreasuryChangeCancelled {
+                        cancelled_treasury: config.pending_treasury,
+                    });
+                    config.pending_treasury = Pubkey::default();
+                    config.pending_treasury_effective_at = 0;
+                }
+                config.treasury = treasury;
+            }
+            AdminAction::SetPolicy { values } => {
+                require_fees_within_cap(config, values.max_fee_bps)?;
+                require!(policy_info.is_writable, ErrorCode::ConstraintMut);
+                require!(policy_info.owner == program_id, ErrorCode::ConstraintOwner);
+                let mut policy_state = {
+                    let policy_data = policy_info.try_borrow_data()?;
+                    PolicyParams::try_deserialize(&mut &policy_data[..])?
+                };
+                if policy_state.pending_effective_at > 0 {
+                    emit!(PolicyUpdateCancelled {
+                        values: policy_state.pending,
+                    });
+                    policy_state.pending = PolicyValues::default();
+                    policy_state.pending_effective_at = 0;
+                }
+                policy_state.values = values;
+                let mut policy_data = policy_info.try_borrow_mut_data()?;
+                let mut policy_cursor: &mut [u8] = &mut policy_data;
+                policy_state.try_serialize(&mut policy_cursor)?;
+            }
+            AdminAction::RotateKey { new_authority } => {
+                // The queue already served the delay, so the proposal dates from when it was
+                // queued and `accept_authority` is open for the acceptance window from the eta.
+                if config.pending_authority != Pubkey::default() {
+                    emit!(AuthorityProposalCancelled {
+                        authority: config.authority,
+                        cancelled_authority: config.pending_authority,
+                    });
+                }
+                config.pending_authority = new_authority;
+                config.pending_authority_set_at = admin_action_state.queued_at;
+                config.pending_authority_expires_at =
+                    config.proposal_expiry_from(config.pending_authority_set_at);
+                emit!(AuthorityProposed {
+                    current_authority: config.authority,
+                    pending_authority: new_authority,
+                    delay_seconds: config.authority_delay_seconds,
+                    expires_at: config.authority_proposal_expires_at(),
+                });
+            }
+            AdminAction::Unpause { flags } => {
+                config.paused &= !flags;
+                emit!(ProgramUnpaused {
+                    unpaused_by: executor,
+                    flags,
+                    paused: config.paused,
+                });
+            }
+        }
+
+        close_admin_action(
+            &admin_action_info,
+            &ctx.accounts.payer.to_account_info(),
+            &admin_action_state,
+        )?;
+
+        emit!(AdminActionExecuted {
+            admin_action: *admin_action_info.key,
+            index: admin_action_state.index,
+            action: admin_action_state.action,
+            executed_at: now,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: drops a queued admin action that has not been executed.
+    pub fn cancel_admin_action(ctx: Context<CancelAdminAction>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let admin_action_info = ctx.accounts.admin_action.to_account_info();
+
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.config.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        let admin_action_state = load_admin_action(program_id, &admin_action_info)?;
+
+        close_admin_action(
+            &admin_action_info,
+            &ctx.accounts.payer.to_account_info(),
+            &admin_action_state,
+        )?;
+
+        emit!(AdminActionCancelled {
+            admin_action: *admin_action_info.key,
+            index: admin_action_state.index,
+            action: admin_action_state.action,
+            cancelled_by: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn expire_drop<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExpireDrop<'info>>,
+        nullifier: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let program_id = ctx.program_id;
+
+        let (drop_pda, drop_bump) =
+            Pubkey::find_program_address(&[b"drop", nullifier.as_ref()], program_id);
+        let (nullifier_pda, nullifier_bump) =
+            Pubkey::find_program_address(&[b"nullifier", nullifier.as_ref()], program_id);
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+
+        let drop_info = ctx.accounts.drop.to_account_info();
+        let nullifier_info = ctx.accounts.nullifier_account.to_account_info();
+        let config_info = ctx.accounts.config.to_account_info();
+        let rent_collector_info = ctx.accounts.rent_collector.to_account_info();
+
+        require_keys_eq!(drop_pda, *drop_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            nullifier_pda,
+            *nullifier_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_neq!(*drop_info.key, *nullifier_info.key, ErrorCode::ConstraintAddress);
+        require_keys_neq!(
+            *rent_collector_info.key,
+            *drop_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require_keys_neq!(
+            *rent_collector_info.key,
+            *nullifier_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(drop_info.is_writable, ErrorCode::ConstraintMut);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+        require!(nullifier_info.is_writable, ErrorCode::ConstraintMut);
+        require!(rent_collector_info.is_writable, ErrorCode::ConstraintMut);
+
+        require!(drop_info.owner == program_id, ErrorCode::ConstraintOwner);
+        require!(
+            nullifier_info.owner == program_id,
+            ErrorCode::ConstraintOwner
+        );
+
+        let drop_state = {
+            let drop_data = drop_info.try_borrow_data()?;
+            DropAccount::try_deserialize(&mut &drop_data[..])?
+        };
+        require_eq!(drop_state.bump, drop_bump, ErrorCode::ConstraintSeeds);
+
+        let nullifier_state = {
+            let nullifier_data = nullifier_info.try_borrow_data()?;
+            NullifierAccount::try_deserialize(&mut &nullifier_data[..])?
+        };
+        require_eq!(
+            nullifier_state.bump,
+            nullifier_bump,
+            ErrorCode::ConstraintSeeds
+        );
+        require!(
+            nullifier_state.nullifier == nullifier,
+            DarkPoolError::InvalidNullifier
+        );
+
+        require!(
+            ctx.accounts.config.authority == ctx.accounts.authority.key(),
+            DarkPoolError::UnauthorizedCreator
+        );
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            drop_state.nullifier == nullifier,
+            DarkPoolError::InvalidNullifier
+        );
+        require!(
+            drop_state.status == DropStatus::Active,
+            DarkPoolError::DropNotActive
+        );
+        require!(
+            drop_state.expires_at != 0 && now > drop_state.expires_at,
+            DarkPoolError::DropNotExpired
+        );
+        require!(
+            !nullifier_state.is_used,
+            DarkPoolError::NullifierAlreadyUsed
+        );
+
+        // Return the escrowed amount of deposit_pool drops to whoever funded them.
+        let mut refunded = 0;
+        if drop_state.escrowed {
+            refund_drop_to_funder(
+                program_id,
+                &drop_state,
+                &ctx.accounts.config,
+                &ctx.accounts.sol_vault.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                RefundAccounts {
+                    funder: ctx.accounts.funder.as_ref(),
+                    mint: ctx.accounts.mint.as_deref(),
+                    token_vault: ctx.accounts.token_vault.as_deref(),
+                    funder_token_account: ctx.accounts.funder_token_account.as_deref(),
+                    token_program: ctx.accounts.token_program.as_ref(),
+                    token_ledger: ctx.accounts.token_ledger.as_ref(),
+                    additional_accounts: ctx.remaining_accounts,
+                },
+            )?;
+            refunded = drop_state.remaining_amount();
+        }
+        // The obligation ends whether or not escrow is returned; unescrowed
+        // create_drop funds simply become vault surplus.
+        if drop_state.asset_type == ASSET_TYPE_SOL {
+            release_sol_liability(&mut ctx.accounts.config, drop_state.remaining_amount())?;
+        }
+
+        emit!(DropExpired {
+            nullifier,
+            recipient: drop_state.recipient,
+            expires_at: drop_state.expires_at,
+            funder: drop_state.funder,
+            refunded,
+        });
+
+        // Close the drop and nullifier accounts. Escrowed drops return the rent to the funder
+        // who paid it (checked by the refund above); the rest go to the rent collector.
+        let rent_receiver_info = match ctx.accounts.funder.as_ref() {
+            Some(funder) if drop_state.escrowed => funder.to_account_info(),
+            _ => rent_collector_info,
+        };
+        let mut rent_lamports = rent_receiver_info.try_borrow_mut_lamports()?;
+        let mut drop_lamports = drop_info.try_borrow_mut_lamports()?;
+        let new_rent_lamports = rent_lamports
+            .checked_add(**drop_lamports)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        **rent_lamports = new_rent_lamports;
+        **drop_lamports = 0;
+        drop_info.try_borrow_mut_data()?.fill(0);
+
+        let mut nullifier_lamports = nullifier_info.try_borrow_mut_lamports()?;
+        let new_rent_lamports = rent_lamports
+            .checked_add(**nullifier_lamports)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        **rent_lamports = new_rent_lamports;
+        **nullifier_lamports = 0;
+        nullifier_info.try_borrow_mut_data()?.fill(0);
+
+        Ok(())
+    }
+
+    pub fn cancel_drop<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelDrop<'info>>,
+        nullifier: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let program_id = ctx.program_id;
+
+        let (drop_pda, drop_bump) =
+            Pubkey::find_program_address(&[b"drop", nullifier.as_ref()], program_id);
+        let (nullifier_pda, nullifier_bump) =
+            Pubkey::find_program_address(&[b"nullifier", nullifier.as_ref()], program_id);
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+
+        let drop_info = ctx.accounts.drop.to_account_info();
+        let nullifier_info = ctx.accounts.nullifier_account.to_account_info();
+        let config_info = ctx.accounts.config.to_account_info();
+        let canceller_info = ctx.accounts.canceller.to_account_info();
+
+        require_keys_eq!(drop_pda, *drop_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            nullifier_pda,
+            *nullifier_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require!(drop_info.is_writable, ErrorCode::ConstraintMut);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+        require!(nullifier_info.is_writable, ErrorCode::ConstraintMut);
+        require!(canceller_info.is_writable, ErrorCode::ConstraintMut);
+
+        require!(drop_info.owner == program_id, ErrorCode::ConstraintOwner);
+        require!(
+            nullifier_info.owner == program_id,
+            ErrorCode::ConstraintOwner
+        );
+
+        let drop_state = {
+            let drop_data = drop_info.try_borrow_data()?;
+            DropAccount::try_deserialize(&mut &drop_data[..])?
+        };
+        require_eq!(drop_state.bump, drop_bump, ErrorCode::ConstraintSeeds);
+
+        let mut nullifier_state = {
+            let nullifier_data = nullifier_info.try_borrow_data()?;
+            NullifierAccount::try_deserialize(&mut &nullifier_data[..])?
+        };
+        require_eq!(
+            nullifier_state.bump,
+            nullifier_bump,
+            ErrorCode::ConstraintSeeds
+        );
+
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        // Both `create_drop` and `deposit_pool` record their payer as the drop's funder.
+        let canceller = ctx.accounts.canceller.key();
+        require!(
+            canceller == ctx.accounts.config.authority
+                || (drop_state.funder != Pubkey::default() && canceller == drop_state.funder),
+            DarkPoolError::UnauthorizedCanceller
+        );
+        require!(
+            drop_state.nullifier == nullifier && nullifier_state.nullifier == nullifier,
+            DarkPoolError::InvalidNullifier
+        );
+        require!(
+            drop_state.status == DropStatus::Active,
+            DarkPoolError::DropNotActive
+        );
+        require!(
+            !nullifier_state.is_used,
+            DarkPoolError::NullifierAlreadyUsed
+        );
+        // Whatever has vested belongs to the grantee, so a vesting drop can only be
+        // cancelled while nothing has unlocked.
+        require!(
+            !drop_state.is_vesting() || now < drop_state.cliff_at,
+            DarkPoolError::VestingStarted
+        );
+
+        let mut refunded = 0;
+        if drop_state.escrowed {
+            refund_drop_to_funder(
+                program_id,
+                &drop_state,
+                &ctx.accounts.config,
+                &ctx.accounts.sol_vault.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                RefundAccounts {
+                    funder: ctx.accounts.funder.as_ref(),
+                    mint: ctx.accounts.mint.as_deref(),
+                    token_vault: ctx.accounts.token_vault.as_deref(),
+                    funder_token_account: ctx.accounts.funder_token_account.as_deref(),
+                    token_program: ctx.accounts.token_program.as_ref(),
+                    token_ledger: ctx.accounts.token_ledger.as_ref(),
+                    additional_accounts: ctx.remaining_accounts,
+                },
+            )?;
+            refunded = drop_state.remaining_amount();
+        }
+        // The obligation ends whether or not escrow is returned; unescrowed
+        // create_drop funds simply become vault surplus.
+        if drop_state.asset_type == ASSET_TYPE_SOL {
+            release_sol_liability(&mut ctx.accounts.config, drop_state.remaining_amount())?;
+        }
+
+        // The nullifier stays open and used so the same value can never back a new drop.
+        nullifier_state.is_used = true;
+        nullifier_state.used_at = now;
+        {
+            let mut nullifier_data = nullifier_info.try_borrow_mut_data()?;
+            let mut nullifier_cursor: &mut [u8] = &mut nullifier_data;
+            nullifier_state.try_serialize(&mut nullifier_cursor)?;
+        }
+
+        emit!(DropCancelled {
+            nullifier,
+            cancelled_by: canceller,
+            funder: drop_state.funder,
+            refunded,
+            cancelled_at: now,
+        });
+
+        // Close the drop account. The funder paid its rent and gets it back; drops written
+        // before funders were recorded return it to the canceller.
+        let rent_receiver_info = if drop_state.funder != Pubkey::default() {
+            let funder = ctx
+                .accounts
+                .funder
+                .as_ref()
+                .ok_or(DarkPoolError::InvalidFunder)?;
+            require_keys_eq!(
+                funder.key(),
+                drop_state.funder,
+                DarkPoolError::InvalidFunder
+            );
+            require!(
+                funder.to_account_info().is_writable,
+                ErrorCode::ConstraintMut
+            );
+            funder.to_account_info()
+        } else {
+            canceller_info
+        };
+        let mut rent_lamports = rent_receiver_info.try_borrow_mut_lamports()?;
+        let mut drop_lamports = drop_info.try_borrow_mut_lamports()?;
+        let new_rent_lamports = rent_lamports
+            .checked_add(**drop_lamports)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        **rent_lamports = new_rent_lamports;
+        **drop_lamports = 0;
+        drop_info.try_borrow_mut_data()?.fill(0);
+
+        Ok(())
+    }
+
+    /// Permissionless: moves every accrued claim fee from `sol_vault` to the configured treasury.
+    pub fn sweep_fees(ctx: Context<SweepFees>) -> Result<()> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let program_id = ctx.program_id;
+
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+
+        let config_info = ctx.accounts.config.to_account_info();
+        let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+        let treasury_info = ctx.accounts.treasury.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            sol_vault_pda,
+            *sol_vault_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+        require!(sol_vault_info.is_writable, ErrorCode::ConstraintMut);
+        require!(treasury_info.is_writable, ErrorCode::ConstraintMut);
+
+        let config_state = &mut ctx.accounts.config;
+        require!(
+            config_state.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            treasury_info.key() == config_state.treasury,
+            DarkPoolError::InvalidTreasury
+        );
+        let amount = config_state.accrued_fees;
+        require!(amount > 0, DarkPoolError::NoFeesAccrued);
+        require!(
+            sol_vault_available(&sol_vault_info)? >= amount,
+            DarkPoolError::InsufficientVaultBalance
+        );
+
+        transfer_from_sol_vault(
+            &sol_vault_info,
+            &treasury_info,
+            &system_program_info,
+            config_state.sol_vault_bump,
+            amount,
+        )?;
+        config_state.accrued_fees = 0;
+        record_sol_outflow(config_state, amount, now)?;
+
+        emit!(FeesSwept {
+            treasury: treasury_info.key(),
+            amount,
+            swept_at: now,
+        });
+
+        msg!("Fees swept: amount={}, treasury={}", amount, treasury_info.key());
+        Ok(())
+    }
+
+    /// Authority-only: registers a partner wallet, or updates its fee share.
+    pub fn register_partner(
+        ctx: Context<RegisterPartner>,
+        wallet: Pubkey,
+        share_bps: u16,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (partner_pda, partner_bump) =
+            Pubkey::find_program_address(&[b"partner", wallet.as_ref()], program_id);
+
+        let partner_info = ctx.accounts.partner.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.config.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(partner_pda, *partner_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(partner_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.payer.to_account_info().is_writable,
+            ErrorCode::ConstraintMut
+        );
+
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        require!(wallet != Pubkey::default(), DarkPoolError::InvalidPartner);
+        require!(share_bps <= 10_000, DarkPoolError::InvalidPartnerShare);
+
+        let partner_state = if partner_info.owner == program_id {
+            let mut partner_state = load_partner(program_id, &partner_info)?;
+            partner_state.share_bps = share_bps;
+            partner_state
+        } else {
+            require!(
+                partner_info.owner == &system_program::ID && partner_info.lamports() == 0,
+                DarkPoolError::AccountAlreadyInitialized
+            );
+            let space = (8 + Partner::LEN) as u64;
+            let lamports = Rent::get()?.minimum_balance(space as usize);
+            let seeds: &[&[u8]] = &[b"partner", wallet.as_ref(), &[partner_bump]];
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    system_program_info,
+                    system_program::CreateAccount {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: partner_info.clone(),
+                    },
+                    &[seeds],
+                ),
+                lamports,
+                space,
+                program_id,
+            )?;
+            Partner {
+                wallet,
+                share_bps,
+                sol_earned: 0,
+                sol_accrued: 0,
+                token_earned: 0,
+                registered_at: Clock::get()?.unix_timestamp,
+                bump: partner_bump,
+            }
+        };
+        let mut partner_data = partner_info.try_borrow_mut_data()?;
+        let mut partner_cursor: &mut [u8] = &mut partner_data;
+        partner_state.try_serialize(&mut partner_cursor)?;
+
+        emit!(PartnerRegistered { wallet, share_bps });
+
+        Ok(())
+    }
+
+    /// Authority-only: closes a partner, sending its unwithdrawn earnings and rent to the wallet.
+    /// Drops that still reference it pay their whole fee to the treasury.
+    pub fn remove_partner(ctx: Context<RemovePartner>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        let partner_info = ctx.accounts.partner.to_account_info();
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            sol_vault_pda,
+            *sol_vault_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+        require!(partner_info.is_writable, ErrorCode::ConstraintMut);
+        require!(wallet_info.is_writable, ErrorCode::ConstraintMut);
+        require!(sol_vault_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+
+        let partner_state = load_partner(program_id, &partner_info)?;
+        require_keys_eq!(
+            partner_state.wallet,
+            *wallet_info.key,
+            DarkPoolError::InvalidPartner
+        );
+
+        let earnings = partner_state.sol_accrued;
+        if earnings > 0 {
+            let config_state = &mut ctx.accounts.config;
+            transfer_from_sol_vault(
+                &sol_vault_info,
+                &wallet_info,
+                &system_program_info,
+                config_state.sol_vault_bump,
+                earnings,
+            )?;
+            config_state.partner_accrued_fees = config_state
+                .partner_accrued_fees
+                .checked_sub(earnings)
+                .ok_or(DarkPoolError::InsufficientVaultBalance)?;
+            record_sol_outflow(config_state, earnings, now)?;
+        }
+
+        let rent = partner_info.lamports();
+        {
+            let mut wallet_lamports = wallet_info.try_borrow_mut_lamports()?;
+            let mut partner_lamports = partner_info.try_borrow_mut_lamports()?;
+            let new_wallet_lamports = wallet_lamports
+                .checked_add(**partner_lamports)
+                .ok_or(DarkPoolError::NumericalOverflow)?;
+            **wallet_lamports = new_wallet_lamports;
+            **partner_lamports = 0;
+        }
+        partner_info.try_borrow_mut_data()?.fill(0);
+
+        emit!(PartnerRemoved {
+            wallet: partner_state.wallet,
+            refunded: earnings
+                .checked_add(rent)
+                .ok_or(DarkPoolError::NumericalOverflow)?,
+        });
+
+        Ok(())
+    }
+
+    /// Partner-signed: withdraws the SOL earnings accrued for the partner in `sol_vault`.
+    pub fn withdraw_partner_earnings(ctx: Context<WithdrawPartnerEarnings>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+        let config_info = ctx.accounts.config.to_account_info();
+        let partner_info = ctx.accounts.partner.to_account_info();
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            sol_vault_pda,
+            *sol_vault_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+        require!(partner_info.is_writable, ErrorCode::ConstraintMut);
+        require!(wallet_info.is_writable, ErrorCode::ConstraintMut);
+        require!(sol_vault_info.is_writable, ErrorCode::ConstraintMut);
+
+        let mut partner_state = load_partner(program_id, &partner_info)?;
+        require_keys_eq!(
+            partner_state.wallet,
+            *wallet_info.key,
+            DarkPoolError::InvalidPartner
+        );
+
+        let amount = partner_state.sol_accrued;
+        require!(amount > 0, DarkPoolError::InvalidAmount);
+        let config_state = &mut ctx.accounts.config;
+        transfer_from_sol_vault(
+            &sol_vault_info,
+            &wallet_info,
+            &system_program_info,
+            config_state.sol_vault_bump,
+            amount,
+        )?;
+        config_state.partner_accrued_fees = config_state
+            .partner_accrued_fees
+            .checked_sub(amount)
+            .ok_or(DarkPoolError::InsufficientVaultBalance)?;
+        record_sol_outflow(config_state, amount, now)?;
+
+        partner_state.sol_accrued = 0;
+        let mut partner_data = partner_info.try_borrow_mut_data()?;
+        let mut partner_cursor: &mut [u8] = &mut partner_data;
+        partner_state.try_serialize(&mut partner_cursor)?;
+
+        emit!(PartnerEarningsWithdrawn {
+            wallet: partner_state.wallet,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: grants `creator` the right to call `create_drop`, or updates its quota.
+    pub fn add_creator_role(
+        ctx: Context<AddCreatorRole>,
+        creator: Pubkey,
+        quota: u64,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (role_pda, role_bump) =
+            Pubkey::find_program_address(&[b"creator_role", creator.as_ref()], program_id);
+
+        let role_info = ctx.accounts.creator_role.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.config.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(role_pda, *role_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(role_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.payer.to_account_info().is_writable,
+            ErrorCode::ConstraintMut
+        );
+
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        require!(creator != Pubkey::default(), DarkPoolError::InvalidCreatorRole);
+
+        let role_state = if role_info.owner == program_id {
+            let mut role_state = load_creator_role(program_id, &role_info)?;
+            role_state.quota = quota;
+            role_state.enabled = true;
+            role_state
+        } else {
+            require!(
+                role_info.owner == &system_program::ID && role_info.lamports() == 0,
+                DarkPoolError::AccountAlreadyInitialized
+            );
+            let space = (8 + CreatorRole::LEN) as u64;
+            let lamports = Rent::get()?.minimum_balance(space as usize);
+            let seeds: &[&[u8]] = &[b"creator_role", creator.as_ref(), &[role_bump]];
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    system_program_info,
+                    system_program::CreateAccount {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: role_info.clone(),
+                    },
+                    &[seeds],
+                ),
+                lamports,
+                space,
+                program_id,
+            )?;
+            CreatorRole {
+                creator,
+                enabled: true,
+                quota,
+                drops_created: 0,
+                granted_at: Clock::get()?.unix_timestamp,
+                bump: role_bump,
+                limits: QuotaLimits::default(),
+            }
+        };
+        require!(role_state.is_bounded(), DarkPoolError::UnboundedCreatorRole);
+        let mut role_data = role_info.try_borrow_mut_data()?;
+        let mut role_cursor: &mut [u8] = &mut role_data;
+        role_state.try_serialize(&mut role_cursor)?;
+
+        emit!(CreatorRoleAdded { creator, quota });
+
+        Ok(())
+    }
+
+    /// Authority-only: suspends or resumes a creator without losing its usage count.
+    pub fn set_creator_role_enabled(
+        ctx: Context<SetCreatorRoleEnabled>,
+        enabled: bool,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let role_info = ctx.accounts.creator_role.to_account_info();
+
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.config.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require!(role_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+
+        let mut role_state = load_creator_role(program_id, &role_info)?;
+        role_state.enabled = enabled;
+        let mut role_data = role_info.try_borrow_mut_data()?;
+        let mut role_cursor: &mut [u8] = &mut role_data;
+        role_state.try_serialize(&mut role_cursor)?;
+
+        emit!(CreatorRoleUpdated {
+            creator: role_state.creator,
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: sets a role-specific window quota; default limits restore the policy quota.
+    pub fn set_creator_role_limits(
+        ctx: Context<SetCreatorRoleLimits>,
+        limits: QuotaLimits,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let role_info = ctx.accounts.creator_role.to_account_info();
+
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.config.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require!(role_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        limits.validate()?;
+
+        let mut role_state = load_creator_role(program_id, &role_info)?;
+        role_state.limits = limits;
+        require!(role_state.is_bounded(), DarkPoolError::UnboundedCreatorRole);
+        let mut role_data = role_info.try_borrow_mut_data()?;
+        let mut role_cursor: &mut [u8] = &mut role_data;
+        role_state.try_serialize(&mut role_cursor)?;
+
+        emit!(CreatorRoleLimitsUpdated {
+            creator: role_state.creator,
+            limits,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: revokes a creator role and returns its rent to `rent_receiver`.
+    pub fn remove_creator_role(ctx: Context<RemoveCreatorRole>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let role_info = ctx.accounts.creator_role.to_account_info();
+        let rent_receiver_info = ctx.accounts.rent_receiver.to_account_info();
+
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.config.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require!(role_info.is_writable, ErrorCode::ConstraintMut);
+        require!(rent_receiver_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+
+        let role_state = load_creator_role(program_id, &role_info)?;
+        {
+            let mut receiver_lamports = rent_receiver_info.try_borrow_mut_lamports()?;
+            let mut role_lamports = role_info.try_borrow_mut_lamports()?;
+            let new_receiver_lamports = receiver_lamports
+                .checked_add(**role_lamports)
+                .ok_or(DarkPoolError::NumericalOverflow)?;
+            **receiver_lamports = new_receiver_lamports;
+            **role_lamports = 0;
+        }
+        role_info.try_borrow_mut_data()?.fill(0);
+
+        emit!(CreatorRoleRemoved {
+            creator: role_state.creator,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: creates the M-of-N multisig. Hand the Config authority to it by
+    /// proposing its signer PDA and executing `accept_authority` through the multisig.
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (multisig_pda, multisig_bump) = Pubkey::find_program_address(&[b"multisig"], program_id);
+        let (signer_pda, signer_bump) = Pubkey::find_program_address(
+            &[b"multisig_signer", multisig_pda.as_ref()],
+            program_id,
+        );
+
+        let multisig_info = ctx.accounts.multisig.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.config.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(multisig_pda, *multisig_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(multisig_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.payer.to_account_info().is_writable,
+            ErrorCode::ConstraintMut
+        );
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            DarkPoolError::UnauthorizedCreator
+        );
+        validate_multisig_members(&members, threshold)?;
+        require!(
+            multisig_info.owner == &system_program::ID && multisig_info.lamports() == 0,
+            DarkPoolError::AccountAlreadyInitialized
+        );
+
+        let space = (8 + Multisig::LEN) as u64;
+        let lamports = Rent::get()?.minimum_balance(space as usize);
+        let seeds: &[&[u8]] = &[b"multisig", &[multisig_bump]];
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program_info,
+                system_program::CreateAccount {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: multisig_info.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space,
+            program_id,
+        )?;
+
+        let multisig_state = Multisig {
+            members: members.clone(),
+            threshold,
+            members_version: 0,
+            transaction_count: 0,
+            signer_bump,
+            bump: multisig_bump,
+        };
+        let mut multisig_data = multisig_info.try_borrow_mut_data()?;
+        let mut multisig_cursor: &mut [u8] = &mut multisig_data;
+        multisig_state.try_serialize(&mut multisig_cursor)?;
+
+        emit!(MultisigCreated {
+            multisig: multisig_pda,
+            signer: signer_pda,
+            members,
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Member-only: queues an instruction of this program to be signed by the multisig signer.
+    /// The proposer's approval is recorded immediately.
+    pub fn propose_multisig_transaction(
+        ctx: Context<ProposeMultisigTransaction>,
+        accounts: Vec<MultisigAccountMeta>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let multisig_key = ctx.accounts.multisig.key();
+        let (multisig_pda, _) = Pubkey::find_program_address(&[b"multisig"], program_id);
+        let index = ctx.accounts.multisig.transaction_count;
+        let (transaction_pda, transaction_bump) = Pubkey::find_program_address(
+            &[b"multisig_tx", multisig_key.as_ref(), &index.to_le_bytes()],
+            program_id,
+        );
+
+        let transaction_info = ctx.accounts.transaction.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(multisig_pda, multisig_key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            transaction_pda,
+            *transaction_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(transaction_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.multisig.to_account_info().is_writable,
+            ErrorCode::ConstraintMut
+        );
+        require!(
+            ctx.accounts.proposer.to_account_info().is_writable,
+            ErrorCode::ConstraintMut
+        );
+        let member_index = ctx
+            .accounts
+            .multisig
+            .member_index(ctx.accounts.proposer.key)
+            .ok_or(DarkPoolError::NotMultisigMember)?;
+        require!(
+            transaction_info.owner == &system_program::ID && transaction_info.lamports() == 0,
+            DarkPoolError::AccountAlreadyInitialized
+        );
+
+        let space = (8 + MultisigTransaction::space(accounts.len(), data.len())) as u64;
+        let lamports = Rent::get()?.minimum_balance(space as usize);
+        let index_bytes = index.to_le_bytes();
+        let seeds: &[&[u8]] = &[
+            b"multisig_tx",
+            multisig_key.as_ref(),
+            &index_bytes,
+            &[transaction_bump],
+        ];
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program_info,
+                system_program::CreateAccount {
+                    from: ctx.accounts.proposer.to_account_info(),
+                    to: transaction_info.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space,
+            program_id,
+        )?;
+
+        let transaction_state = MultisigTransaction {
+            multisig: multisig_key,
+            index,
+            proposer: ctx.accounts.proposer.key(),
+            accounts,
+            data,
+            approvals: 1u16 << member_index,
+            members_version: ctx.accounts.multisig.members_version,
+            executed: false,
+            created_at: Clock::get()?.unix_timestamp,
+            bump: transaction_bump,
+        };
+        let mut transaction_data = transaction_info.try_borrow_mut_data()?;
+        let mut transaction_cursor: &mut [u8] = &mut transaction_data;
+        transaction_state.try_serialize(&mut transaction_cursor)?;
+
+        ctx.accounts.multisig.transaction_count = index
+            .checked_add(1)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+
+        emit!(MultisigTransactionProposed {
+            transaction: transaction_pda,
+            index,
+            proposer: ctx.accounts.proposer.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn approve_multisig_transaction(ctx: Context<ApproveMultisigTransaction>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let transaction_info = ctx.accounts.transaction.to_account_info();
+        let multisig = &ctx.accounts.multisig;
+
+        let mut transaction_state =
+            load_multisig_transaction(program_id, multisig, &transaction_info)?;
+        let member_index = multisig
+            .member_index(ctx.accounts.member.key)
+            .ok_or(DarkPoolError::NotMultisigMember)?;
+        transaction_state.approvals |= 1u16 << member_index;
+        let mut transaction_data = transaction_info.try_borrow_mut_data()?;
+        let mut transaction_cursor: &mut [u8] = &mut transaction_data;
+        transaction_state.try_serialize(&mut transaction_cursor)?;
+
+        emit!(MultisigTransactionApproved {
+            transaction: *transaction_info.key,
+            member: ctx.accounts.member.key(),
+            approvals: transaction_state.approvals.count_ones() as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless once the threshold is met: invokes the queued instruction signed by the
+    /// multisig signer, then closes the transaction to its proposer. Remaining accounts: the
+    /// instruction's accounts, then this program.
+    pub fn execute_multisig_transaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteMultisigTransaction<'info>>,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let transaction_info = ctx.accounts.transaction.to_account_info();
+        let multisig = &ctx.accounts.multisig;
+        let multisig_key = multisig.key();
+        let signer_seeds: &[&[u8]] = &[
+            b"multisig_signer",
+            multisig_key.as_ref(),
+            &[multisig.signer_bump],
+        ];
+        let signer_pda = Pubkey::create_program_address(signer_seeds, program_id)
+            .map_err(|_| error!(ErrorCode::ConstraintSeeds))?;
+        require_keys_eq!(
+            signer_pda,
+            ctx.accounts.multisig_signer.key(),
+            ErrorCode::ConstraintSeeds
+        );
+
+        let mut transaction_state =
+            load_multisig_transaction(program_id, multisig, &transaction_info)?;
+        require!(
+            transaction_state.approvals.count_ones() >= multisig.threshold as u32,
+            DarkPoolError::MultisigThresholdNotMet
+        );
+        let proposer_info = ctx.accounts.proposer.to_account_info();
+        require_keys_eq!(
+            *proposer_info.key,
+            transaction_state.proposer,
+            DarkPoolError::InvalidMultisigProposer
+        );
+
+        // Mark executed before invoking so the transaction can never run twice.
+        transaction_state.executed = true;
+        {
+            let mut transaction_data = transaction_info.try_borrow_mut_data()?;
+            let mut transaction_cursor: &mut [u8] = &mut transaction_data;
+            transaction_state.try_serialize(&mut transaction_cursor)?;
+        }
+
+        let ix = anchor_lang::solana_program::instruction::Instruction {
+            program_id: *program_id,
+            accounts: transaction_state
+                .accounts
+                .iter()
+                .map(|meta| anchor_lang::solana_program::instruction::AccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer || meta.pubkey == signer_pda,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: transaction_state.data.clone(),
+        };
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(ctx.accounts.multisig_signer.to_account_info());
+        anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, &[signer_seeds])?;
+
+        // Executed transactions are never read again; return their rent to the proposer.
+        close_multisig_transaction_account(&transaction_info, &proposer_info, &transaction_state)?;
+
+        emit!(MultisigTransactionExecuted {
+            transaction: *transaction_info.key,
+            index: transaction_state.index,
+        });
+
+        Ok(())
+    }
+
+    /// Closes an unexecuted transaction and returns its rent to the proposer. The proposer may
+    /// withdraw it at any time; anyone may close it once the member set has changed under it.
+    pub fn close_multisig_transaction(ctx: Context<CloseMultisigTransaction>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let transaction_info = ctx.accounts.transaction.to_account_info();
+        let multisig = &ctx.accounts.multisig;
+
+        let transaction_state = read_multisig_transaction(program_id, multisig, &transaction_info)?;
+        require!(
+            ctx.accounts.closer.key() == transaction_state.proposer
+                || transaction_state.members_version != multisig.members_version,
+            DarkPoolError::MultisigTransactionActive
+        );
+        close_multisig_transaction_account(
+            &transaction_info,
+            &ctx.accounts.proposer.to_account_info(),
+            &transaction_state,
+        )?;
+
+        emit!(MultisigTransactionClosed {
+            transaction: *transaction_info.key,
+            index: transaction_state.index,
+            closed_by: ctx.accounts.closer.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Multisig-only (via `execute_multisig_transaction`): replaces the member set and threshold.
+    /// Pending transactions from the old member set can no longer be approved or executed.
+    pub fn set_multisig_members(
+        ctx: Context<SetMultisigMembers>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let program_id = ctx.program_id;
+        let multisig_key = ctx.accounts.multisig.key();
+        let (multisig_pda, _) = Pubkey::find_program_address(&[b"multisig"], program_id);
+        require_keys_eq!(multisig_pda, multisig_key, ErrorCode::ConstraintSeeds);
+        require!(
+            ctx.accounts.multisig.to_account_info().is_writable,
+            ErrorCode::ConstraintMut
+        );
+        let signer_pda = Pubkey::create_program_address(
+            &[
+                b"multisig_signer",
+                multisig_key.as_ref(),
+                &[ctx.accounts.multisig.signer_bump],
+            ],
+            program_id,
+        )
+        .map_err(|_| error!(ErrorCode::ConstraintSeeds))?;
+        require_keys_eq!(
+            signer_pda,
+            ctx.accounts.multisig_signer.key(),
+            DarkPoolError::NotMultisigMember
+        );
+        validate_multisig_members(&members, threshold)?;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.members = members.clone();
+        multisig.threshold = threshold;
+        multisig.members_version = multisig
+            .members_version
+            .checked_add(1)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+
+        emit!(MultisigMembersUpdated {
+            members,
+            threshold,
+            members_version: multisig.members_version,
+        });
+
+        Ok(())
+    }
+
+    /// Authority-only: grows a `Config` written under an older layout to the current one and
+    /// fills the new fields with their defaults. Older layouts never tracked liabilities, so
+    /// the authority supplies what the vault owes to the drops already live. The authority
+    /// signs as `payer` and covers the extra rent.
+    pub fn migrate_config(
+        ctx: Context<MigrateAccount>,
+        outstanding_liabilities: u64,
+    ) -> Result<()> {
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], ctx.program_id);
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.account.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        let payer_key = ctx.accounts.payer.key();
+        let previous_len = migrate_account_layout::<Config>(
+            ctx.program_id,
+            ctx.accounts,
+            Config::LEN,
+            &Config::LEGACY_LENS,
+            |config| {
+                require!(
+                    payer_key == config.authority,
+                    DarkPoolError::UnauthorizedCreator
+                );
+                config.outstanding_liabilities = outstanding_liabilities;
+                if config.pending_authority != Pubkey::default() {
+                    config.pending_authority_expires_at =
+                        config.proposal_expiry_from(config.pending_authority_set_at);
+                }
+                config.version = Config::VERSION;
+                Ok(())
+            },
+        )?;
+
+        emit!(AccountMigrated {
+            account: ctx.accounts.account.key(),
+            previous_len: previous_len as u32,
+            version: Config::VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: grows a `DropAccount` written under an older layout to the current one
+    /// and fills the new fields with their defaults. The payer covers the extra rent.
+    pub fn migrate_drop(ctx: Context<MigrateAccount>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let drop_key = ctx.accounts.account.key();
+        let previous_len = migrate_account_layout::<DropAccount>(
+            program_id,
+            ctx.accounts,
+            DropAccount::LEN,
+            &DropAccount::LEGACY_LENS,
+            |drop| {
+                let (drop_pda, drop_bump) =
+                    Pubkey::find_program_address(&[b"drop", drop.nullifier.as_ref()], program_id);
+                require_keys_eq!(drop_pda, drop_key, ErrorCode::ConstraintSeeds);
+                require_eq!(drop.bump, drop_bump, ErrorCode::ConstraintSeeds);
+                // Baseline drops were keyed by the value claimers now pass as the secret.
+                drop.legacy_seed = true;
+                drop.version = DropAccount::VERSION;
+                Ok(())
+            },
+        )?;
+
+        emit!(AccountMigrated {
+            account: ctx.accounts.account.key(),
+            previous_len: previous_len as u32,
+            version: DropAccount::VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: `migrate_drop` for a `NullifierAccount`.
+    pub fn migrate_nullifier(ctx: Context<MigrateAccount>) -> Result<()> {
+        let program_id = ctx.program_id;
+        let nullifier_key = ctx.accounts.account.key();
+        let previous_len = migrate_account_layout::<NullifierAccount>(
+            program_id,
+            ctx.accounts,
+            NullifierAccount::LEN,
+            &NullifierAccount::LEGACY_LENS,
+            |nullifier| {
+                let (nullifier_pda, nullifier_bump) = Pubkey::find_program_address(
+                    &[b"nullifier", nullifier.nullifier.as_ref()],
+                    program_id,
+                );
+                require_keys_eq!(nullifier_pda, nullifier_key, ErrorCode::ConstraintSeeds);
+                require_eq!(nullifier.bump, nullifier_bump, ErrorCode::ConstraintSeeds);
+                nullifier.version = NullifierAccount::VERSION;
+                Ok(())
+            },
+        )?;
+
+        emit!(AccountMigrated {
+            account: ctx.accounts.account.key(),
+            previous_len: previous_len as u32,
+            version: NullifierAccount::VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: `migrate_drop` for the `ShieldedConfig`.
+    pub fn migrate_shielded_config(ctx: Context<MigrateAccount>) -> Result<()> {
+        let (shielded_config_pda, _) =
+            Pubkey::find_program_address(&[b"shielded_config"], ctx.program_id);
+        require_keys_eq!(
+            shielded_config_pda,
+            ctx.accounts.account.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        let previous_len = migrate_account_layout::<ShieldedConfig>(
+            ctx.program_id,
+            ctx.accounts,
+            ShieldedConfig::LEN,
+            &ShieldedConfig::LEGACY_LENS,
+            |config| {
+                config.version = ShieldedConfig::VERSION;
+                Ok(())
+            },
+        )?;
+
+        emit!(AccountMigrated {
+            account: ctx.accounts.account.key(),
+            previous_len: previous_len as u32,
+            version: ShieldedConfig::VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Read-only: returns vault balance minus outstanding liabilities and accrued fees
+    /// (negative on deficit) as return data.
+    pub fn check_solvency(ctx: Context<CheckSolvency>) -> Result<i64> {
+        let program_id = ctx.program_id;
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+        let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+
+        require_keys_eq!(
+            config_pda,
+            ctx.accounts.config.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            sol_vault_pda,
+            *sol_vault_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+
+        let available = sol_vault_available(&sol_vault_info)? as i128;
+        let liabilities = ctx.accounts.config.outstanding_liabilities as i128
+            + ctx.accounts.config.accrued_fees as i128
+            + ctx.accounts.config.partner_accrued_fees as i128;
+        let surplus = (available - liabilities).clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+        msg!(
+            "Solvency: available={}, liabilities={}, surplus={}",
+            available,
+            liabilities,
+            surplus
+        );
+        Ok(surplus)
+    }
+
+    pub fn create_distributor(
+        ctx: Context<CreateDistributor>,
+        merkle_root: [u8; 32],
+        num_recipients: u32,
+        total_amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let program_id = ctx.program_id;
+        require_not_paused(&ctx.accounts.config, PAUSE_DEPOSIT)?;
+
+        let creator_key = ctx.accounts.creator.key();
+        let (distributor_pda, distributor_bump) = Pubkey::find_program_address(
+            &[b"distributor", creator_key.as_ref(), merkle_root.as_ref()],
+            program_id,
+        );
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+
+        let distributor_info = ctx.accounts.distributor.to_account_info();
+        let config_info = ctx.accounts.config.to_account_info();
+        let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+        let creator_info = ctx.accounts.creator.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(
+            distributor_pda,
+            *distributor_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            sol_vault_pda,
+            *sol_vault_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(distributor_info.is_writable, ErrorCode::ConstraintMut);
+        require!(sol_vault_info.is_writable, ErrorCode::ConstraintMut);
+        require!(creator_info.is_writable, ErrorCode::ConstraintMut);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        require!(
+            ctx.accounts.config.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(total_amount > 0, DarkPoolError::InvalidAmount);
+        require!(
+            num_recipients > 0 && num_recipients <= MAX_DISTRIBUTOR_RECIPIENTS,
+            DarkPoolError::InvalidRecipientCount
+        );
+        let policy = load_policy(program_id, ctx.accounts.policy.as_ref())?;
+        // Distributors always expire so close_distributor can return unclaimed funds.
+        require!(
+            expires_at > now
+                && expires_at - now >= policy.min_expiration_window
+                && expires_at - now <= policy.max_expiration_window,
+            DarkPoolError::InvalidExpiration
+        );
+
+        require!(
+            distributor_info.owner == &system_program::ID && distributor_info.lamports() == 0,
+            DarkPoolError::AccountAlreadyInitialized
+        );
+        let space = Distributor::space(num_recipients) as u64;
+        let lamports = Rent::get()?.minimum_balance(space as usize);
+        let seeds: &[&[u8]] = &[
+            b"distributor",
+            creator_key.as_ref(),
+            merkle_root.as_ref(),
+            &[distributor_bump],
+        ];
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program_info.clone(),
+                system_program::CreateAccount {
+                    from: creator_info.clone(),
+                    to: distributor_info.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space,
+            program_id,
+        )?;
+
+        // Fund every leaf in one transfer.
+        system_program::transfer(
+            CpiContext::new(
+                system_program_info,
+                system_program::Transfer {
+                    from: creator_info,
+                    to: sol_vault_info,
+                },
+            ),
+            total_amount,
+        )?;
+        add_sol_liability(
+            &mut ctx.accounts.config,
+            &ctx.accounts.sol_vault.to_account_info(),
+            total_amount,
+        )?;
+
+        let distributor_state = Distributor {
+            creator: creator_key,
+            merkle_root,
+            total_amount,
+            claimed_amount: 0,
+            num_recipients,
+            num_claimed: 0,
+            created_at: now,
+            expires_at,
+            bump: distributor_bump,
+            claimed_bitmap: vec![0u8; Distributor::bitmap_len(num_recipients)],
+        };
+        let mut distributor_data = distributor_info.try_borrow_mut_data()?;
+        let mut distributor_cursor: &mut [u8] = &mut distributor_data;
+        distributor_state.try_serialize(&mut distributor_cursor)?;
+
+        emit!(DistributorCreated {
+            distributor: distributor_pda,
+            creator: creator_key,
+            merkle_root,
+            num_recipients,
+            total_amount,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_distributor(
+        ctx: Context<ClaimDistributor>,
+        index: u32,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let program_id = ctx.program_id;
+        require_not_paused(&ctx.accounts.config, PAUSE_CLAIM)?;
+        require_outflow_open(&ctx.accounts.config)?;
+
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+
+        let distributor_info = ctx.accounts.distributor.to_account_info();
+        let config_info = ctx.accounts.config.to_account_info();
+        let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            sol_vault_pda,
+            *sol_vault_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            system_program::ID,
+            *system_program_info.key,
+            ErrorCode::ConstraintAddress
+        );
+        require!(sol_vault_info.is_writable, ErrorCode::ConstraintMut);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+        require!(
+            ctx.accounts.claimer.to_account_info().is_writable,
+            ErrorCode::ConstraintMut
+        );
+
+        let mut distributor_state = load_distributor(program_id, &distributor_info)?;
+
+        let config_state = &ctx.accounts.config;
+        require!(
+            config_state.is_initialized,
+            DarkPoolError::ConfigNotInitialized
+        );
+        require!(
+            distributor_state.expires_at == 0 || now <= distributor_state.expires_at,
+            DarkPoolError::DropExpired
+        );
+        require!(
+            index < distributor_state.num_recipients,
+            DarkPoolError::InvalidLeafIndex
+        );
+        require!(
+            !distributor_state.is_claimed(index),
+            DarkPoolError::LeafAlreadyClaimed
+        );
+        let leaf = distributor_leaf(index, ctx.accounts.claimer.key, amount);
+        require!(
+            verify_merkle_proof(&proof, &distributor_state.merkle_root, leaf),
+            DarkPoolError::InvalidMerkleProof
+        );
+        let claimed_amount = distributor_state
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        require!(
+            claimed_amount <= distributor_state.total_amount,
+            DarkPoolError::InsufficientVaultBalance
+        );
+
+        // Distributors are funded up front without a payer fee, so they always pay at claim.
+        let fee = config_state.fee_schedule(ASSET_TYPE_SOL).fee_for(amount);
+        let payout = amount
+            .checked_sub(fee)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        require!(
+            sol_vault_info.lamports() >= amount,
+            DarkPoolError::InsufficientVaultBalance
+        );
+        transfer_from_sol_vault(
+            &sol_vault_info,
+            &ctx.accounts.claimer.to_account_info(),
+            &system_program_info,
+            config_state.sol_vault_bump,
+            payout,
+        )?;
+        release_sol_liability(&mut ctx.accounts.config, amount)?;
+        accrue_sol_fee(&mut ctx.accounts.config, fee)?;
+        record_sol_outflow(&mut ctx.accounts.config, payout, now)?;
+
+        distributor_state.set_claimed(index);
+        distributor_state.claimed_amount = claimed_amount;
+        distributor_state.num_claimed = distributor_state
+            .num_claimed
+            .checked_add(1)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        let mut distributor_data = distributor_info.try_borrow_mut_data()?;
+        let mut distributor_cursor: &mut [u8] = &mut distributor_data;
+        distributor_state.try_serialize(&mut distributor_cursor)?;
+
+        emit!(DistributorClaimed {
+            distributor: *distributor_info.key,
+            index,
+            claimer: ctx.accounts.claimer.key(),
+            amount,
+            fee,
+        });
+
+        Ok(())
+    }
+
+    pub fn close_distributor(ctx: Context<CloseDistributor>) -> Result<()> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let program_id = ctx.program_id;
+
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+        let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+
+        let distributor_info = ctx.accounts.distributor.to_account_info();
+        let config_info = ctx.accounts.config.to_account_info();
+        let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+        let creator_info = ctx.accounts.creator.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            sol_vault_pda,
+            *sol_vault_info.key,
+            ErrorCode::ConstraintSeeds
+        );
+        require!(sol_vault_info.is_writable, ErrorCode::ConstraintMut);
+        require!(creator_info.is_writable, ErrorCode::ConstraintMut);
+        require!(config_info.is_writable, ErrorCode::ConstraintMut);
+
+        let distributor_state = load_distributor(program_id, &distributor_info)?;
+        require_keys_eq!(
+            distributor_state.creator,
+            *creator_info.key,
+            DarkPoolError::InvalidFunder
+        );
+        require!(
+            distributor_state.expires_at != 0 && now > distributor_state.expires_at,
+            DarkPoolError::DropNotExpired
+        );
+
+        // Permissionless: unclaimed funds and rent can only go back to the creator.
+        let refunded = distributor_state
+            .total_amount
+            .checked_sub(distributor_state.claimed_amount)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        if refunded > 0 {
+            require!(
+                sol_vault_info.lamports() >= refunded,
+                DarkPoolError::InsufficientVaultBalance
+            );
+            transfer_from_sol_vault(
+                &sol_vault_info,
+                &creator_info,
+                &system_program_info,
+                ctx.accounts.config.sol_vault_bump,
+                refunded,
+            )?;
+        }
+        release_sol_liability(&mut ctx.accounts.config, refunded)?;
+
+        emit!(DistributorClosed {
+            distributor: *distributor_info.key,
+            creator: distributor_state.creator,
+            refunded,
+        });
+
+        let mut creator_lamports = creator_info.try_borrow_mut_lamports()?;
+        let mut distributor_lamports = distributor_info.try_borrow_mut_lamports()?;
+        let new_creator_lamports = creator_lamports
+            .checked_add(**distributor_lamports)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        **creator_lamports = new_creator_lamports;
+        **distributor_lamports = 0;
+        distributor_info.try_borrow_mut_data()?.fill(0);
+
+        Ok(())
+    }
+}
+
+pub struct Initialize<'info> {
+    pub config: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    pub sol_vault: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub struct InitializeShielded<'info> {
+    pub shielded_config: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    pub shielded_vault: UncheckedAccount<'info>,
+    pub shielded_tree: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for InitializeShielded<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for InitializeShielded<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let shielded_config =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let shielded_vault =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let shielded_tree =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            shielded_config,
+            authority,
+            shielded_vault,
+            shielded_tree,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for InitializeShielded<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.shielded_config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.shielded_vault.to_account_metas(override_signer));
+        metas.extend(self.shielded_tree.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for InitializeShielded<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.shielded_config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.shielded_vault.to_account_infos());
+        infos.extend(self.shielded_tree.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for InitializeShielded<'info> {}
+
+pub(crate) mod __client_accounts_initialize_shielded {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct InitializeShielded {
+        pub shielded_config: Pubkey,
+        pub authority: Pubkey,
+        pub shielded_vault: Pubkey,
+        pub shielded_tree: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for InitializeShielded {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.shielded_config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.shielded_vault,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.shielded_tree,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
 
 pub struct DepositShielded<'info> {
     pub shielded_config: Account<'info, ShieldedConfig>,
     pub shielded_tree: Account<'info, ShieldedMerkleTree>,
     pub shielded_vault: SystemAccount<'info>,
-    pub depositor: Signer<'info>,
+    pub depositor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for DepositShielded<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for DepositShielded<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let shielded_config =
+            Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let shielded_tree =
+            Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let shielded_vault =
+            SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let depositor = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            shielded_config,
+            shielded_tree,
+            shielded_vault,
+            depositor,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for DepositShielded<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.shielded_config.to_account_metas(override_signer));
+        metas.extend(self.shielded_tree.to_account_metas(override_signer));
+        metas.extend(self.shielded_vault.to_account_metas(override_signer));
+        metas.extend(self.depositor.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for DepositShielded<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.shielded_config.to_account_infos());
+        infos.extend(self.shielded_tree.to_account_infos());
+        infos.extend(self.shielded_vault.to_account_infos());
+        infos.extend(self.depositor.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for DepositShielded<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.shielded_config.exit(program_id)?;
+        self.shielded_tree.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_deposit_shielded {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct DepositShielded {
+        pub shielded_config: Pubkey,
+        pub shielded_tree: Pubkey,
+        pub shielded_vault: Pubkey,
+        pub depositor: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for DepositShielded {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.shielded_config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.shielded_tree,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.shielded_vault,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.depositor, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier: [u8; 32])]
+pub struct SpendShielded<'info> {
+    #[account(mut)]
+    pub shielded_config: Box<Account<'info, ShieldedConfig>>,
+    #[account(mut)]
+    pub shielded_tree: Box<Account<'info, ShieldedMerkleTree>>,
+    #[account(mut)]
+    pub shielded_vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub nullifier_account: Signer<'info>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+    #[account(mut)]
+    pub spender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for Initialize<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for Initialize<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            config,
+            authority,
+            sol_vault,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for Initialize<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for Initialize<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for Initialize<'info> {}
+
+pub(crate) mod __client_accounts_initialize {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct Initialize {
+        pub config: Pubkey,
+        pub authority: Pubkey,
+        pub sol_vault: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for Initialize {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct CreateDrop<'info> {
+    pub drop: UncheckedAccount<'info>,
+    pub nullifier_account: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub rate_limit_account: UncheckedAccount<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub sol_vault: SystemAccount<'info>,
+    pub referrer_partner: Option<UncheckedAccount<'info>>,
+    pub creator_role: Option<UncheckedAccount<'info>>,
+    pub policy: UncheckedAccount<'info>,
+}
+
+impl<'info> Bumps for CreateDrop<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CreateDrop<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let nullifier_account =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let rate_limit_account =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let referrer_partner = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let creator_role = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let policy = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            drop,
+            nullifier_account,
+            config,
+            rate_limit_account,
+            payer,
+            system_program,
+            sol_vault,
+            referrer_partner,
+            creator_role,
+            policy,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for CreateDrop<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.drop.to_account_metas(override_signer));
+        metas.extend(self.nullifier_account.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.rate_limit_account.to_account_metas(override_signer));
+        metas.extend(self.payer.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        if let Some(referrer_partner) = &self.referrer_partner {
+            metas.extend(referrer_partner.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(creator_role) = &self.creator_role {
+            metas.extend(creator_role.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        metas.extend(self.policy.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CreateDrop<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.drop.to_account_infos());
+        infos.extend(self.nullifier_account.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.rate_limit_account.to_account_infos());
+        infos.extend(self.payer.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.referrer_partner.to_account_infos());
+        infos.extend(self.creator_role.to_account_infos());
+        infos.extend(self.policy.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CreateDrop<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_create_drop {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CreateDrop {
+        pub drop: Pubkey,
+        pub nullifier_account: Pubkey,
+        pub config: Pubkey,
+        pub rate_limit_account: Pubkey,
+        pub payer: Pubkey,
+        pub system_program: Pubkey,
+        pub sol_vault: Pubkey,
+        pub referrer_partner: Option<Pubkey>,
+        pub creator_role: Option<Pubkey>,
+        pub policy: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CreateDrop {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.drop, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.nullifier_account,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.rate_limit_account,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.sol_vault,
+                    false,
+                ),
+                optional_account_meta(self.referrer_partner, false),
+                optional_account_meta(self.creator_role, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.policy,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct DepositPool<'info> {
+    pub drop: UncheckedAccount<'info>,
+    pub nullifier_account: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub rate_limit_account: UncheckedAccount<'info>,
+    pub payer: Signer<'info>,
+    pub sol_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    pub token_vault: Option<UncheckedAccount<'info>>,
+    pub payer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub referrer_partner: Option<UncheckedAccount<'info>>,
+    pub policy: UncheckedAccount<'info>,
+    pub creator_role: Option<UncheckedAccount<'info>>,
+    pub token_ledger: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> Bumps for DepositPool<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for DepositPool<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let nullifier_account =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let rate_limit_account =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let mint = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_vault = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer_token_account =
+            Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_program = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let treasury_token_account =
+            Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let referrer_partner = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let policy = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let creator_role = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_ledger = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            drop,
+            nullifier_account,
+            config,
+            rate_limit_account,
+            payer,
+            sol_vault,
+            system_program,
+            mint,
+            token_vault,
+            payer_token_account,
+            token_program,
+            treasury_token_account,
+            referrer_partner,
+            policy,
+            creator_role,
+            token_ledger,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for DepositPool<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.drop.to_account_metas(override_signer));
+        metas.extend(self.nullifier_account.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.rate_limit_account.to_account_metas(override_signer));
+        metas.extend(self.payer.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        if let Some(mint) = &self.mint {
+            metas.extend(mint.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_vault) = &self.token_vault {
+            metas.extend(token_vault.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(payer_token_account) = &self.payer_token_account {
+            metas.extend(payer_token_account.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_program) = &self.token_program {
+            metas.extend(token_program.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(treasury_token_account) = &self.treasury_token_account {
+            metas.extend(treasury_token_account.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(referrer_partner) = &self.referrer_partner {
+            metas.extend(referrer_partner.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        metas.extend(self.policy.to_account_metas(override_signer));
+        if let Some(creator_role) = &self.creator_role {
+            metas.extend(creator_role.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_ledger) = &self.token_ledger {
+            metas.extend(token_ledger.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for DepositPool<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.drop.to_account_infos());
+        infos.extend(self.nullifier_account.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.rate_limit_account.to_account_infos());
+        infos.extend(self.payer.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos.extend(self.mint.to_account_infos());
+        infos.extend(self.token_vault.to_account_infos());
+        infos.extend(self.payer_token_account.to_account_infos());
+        infos.extend(self.token_program.to_account_infos());
+        infos.extend(self.treasury_token_account.to_account_infos());
+        infos.extend(self.referrer_partner.to_account_infos());
+        infos.extend(self.policy.to_account_infos());
+        infos.extend(self.creator_role.to_account_infos());
+        infos.extend(self.token_ledger.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for DepositPool<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_deposit_pool {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct DepositPool {
+        pub drop: Pubkey,
+        pub nullifier_account: Pubkey,
+        pub config: Pubkey,
+        pub rate_limit_account: Pubkey,
+        pub payer: Pubkey,
+        pub sol_vault: Pubkey,
+        pub system_program: Pubkey,
+        pub mint: Option<Pubkey>,
+        pub token_vault: Option<Pubkey>,
+        pub payer_token_account: Option<Pubkey>,
+        pub token_program: Option<Pubkey>,
+        pub treasury_token_account: Option<Pubkey>,
+        pub referrer_partner: Option<Pubkey>,
+        pub policy: Pubkey,
+        pub creator_role: Option<Pubkey>,
+        pub token_ledger: Option<Pubkey>,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for DepositPool {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.drop, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.nullifier_account,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.rate_limit_account,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+                optional_account_meta(self.mint, false),
+                optional_account_meta(self.token_vault, true),
+                optional_account_meta(self.payer_token_account, true),
+                optional_account_meta(self.token_program, false),
+                optional_account_meta(self.treasury_token_account, true),
+                optional_account_meta(self.referrer_partner, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.policy,
+                    false,
+                ),
+                optional_account_meta(self.creator_role, false),
+                optional_account_meta(self.token_ledger, true),
+            ]
+        }
+    }
+}
+
+pub struct ClaimDrop<'info> {
+    pub drop: UncheckedAccount<'info>,
+    pub nullifier_account: UncheckedAccount<'info>,
+    pub claimer: Signer<'info>,
+    pub config: Account<'info, Config>,
+    pub sol_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    pub token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub claimer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+    pub claim_commitment: Option<UncheckedAccount<'info>>,
+    pub claim_record: Option<UncheckedAccount<'info>>,
+    pub partner: Option<UncheckedAccount<'info>>,
+    pub partner_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_ledger: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> Bumps for ClaimDrop<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ClaimDrop<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let nullifier_account =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let claimer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let mint = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_vault = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let claimer_token_account =
+            Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let treasury_token_account =
+            Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_program = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let instructions_sysvar =
+            Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let claim_commitment = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let claim_record = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let partner = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let partner_token_account =
+            Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_ledger = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            drop,
+            nullifier_account,
+            claimer,
+            config,
+            sol_vault,
+            system_program,
+            mint,
+            token_vault,
+            claimer_token_account,
+            treasury_token_account,
+            token_program,
+            instructions_sysvar,
+            claim_commitment,
+            claim_record,
+            partner,
+            partner_token_account,
+            token_ledger,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for ClaimDrop<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.drop.to_account_metas(override_signer));
+        metas.extend(self.nullifier_account.to_account_metas(override_signer));
+        metas.extend(self.claimer.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        if let Some(mint) = &self.mint {
+            metas.extend(mint.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_vault) = &self.token_vault {
+            metas.extend(token_vault.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(claimer_token_account) = &self.claimer_token_account {
+            metas.extend(claimer_token_account.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(treasury_token_account) = &self.treasury_token_account {
+            metas.extend(treasury_token_account.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_program) = &self.token_program {
+            metas.extend(token_program.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(instructions_sysvar) = &self.instructions_sysvar {
+            metas.extend(instructions_sysvar.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(claim_commitment) = &self.claim_commitment {
+            metas.extend(claim_commitment.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(claim_record) = &self.claim_record {
+            metas.extend(claim_record.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(partner) = &self.partner {
+            metas.extend(partner.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(partner_token_account) = &self.partner_token_account {
+            metas.extend(partner_token_account.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_ledger) = &self.token_ledger {
+            metas.extend(token_ledger.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ClaimDrop<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.drop.to_account_infos());
+        infos.extend(self.nullifier_account.to_account_infos());
+        infos.extend(self.claimer.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos.extend(self.mint.to_account_infos());
+        infos.extend(self.token_vault.to_account_infos());
+        infos.extend(self.claimer_token_account.to_account_infos());
+        infos.extend(self.treasury_token_account.to_account_infos());
+        infos.extend(self.token_program.to_account_infos());
+        infos.extend(self.instructions_sysvar.to_account_infos());
+        infos.extend(self.claim_commitment.to_account_infos());
+        infos.extend(self.claim_record.to_account_infos());
+        infos.extend(self.partner.to_account_infos());
+        infos.extend(self.partner_token_account.to_account_infos());
+        infos.extend(self.token_ledger.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ClaimDrop<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_claim_drop {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ClaimDrop {
+        pub drop: Pubkey,
+        pub nullifier_account: Pubkey,
+        pub claimer: Pubkey,
+        pub config: Pubkey,
+        pub sol_vault: Pubkey,
+        pub system_program: Pubkey,
+        pub mint: Option<Pubkey>,
+        pub token_vault: Option<Pubkey>,
+        pub claimer_token_account: Option<Pubkey>,
+        pub treasury_token_account: Option<Pubkey>,
+        pub token_program: Option<Pubkey>,
+        pub instructions_sysvar: Option<Pubkey>,
+        pub claim_commitment: Option<Pubkey>,
+        pub claim_record: Option<Pubkey>,
+        pub partner: Option<Pubkey>,
+        pub partner_token_account: Option<Pubkey>,
+        pub token_ledger: Option<Pubkey>,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ClaimDrop {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.drop, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.nullifier_account,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.claimer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+                optional_account_meta(self.mint, false),
+                optional_account_meta(self.token_vault, true),
+                optional_account_meta(self.claimer_token_account, true),
+                optional_account_meta(self.treasury_token_account, true),
+                optional_account_meta(self.token_program, false),
+                optional_account_meta(self.instructions_sysvar, false),
+                optional_account_meta(self.claim_commitment, true),
+                optional_account_meta(self.claim_record, true),
+                optional_account_meta(self.partner, true),
+                optional_account_meta(self.partner_token_account, true),
+                optional_account_meta(self.token_ledger, true),
+            ]
+        }
+    }
+}
+
+pub struct CommitClaim<'info> {
+    pub drop: UncheckedAccount<'info>,
+    pub claim_commitment: UncheckedAccount<'info>,
+    pub claimer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for CommitClaim<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CommitClaim<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let claim_commitment =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let claimer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            drop,
+            claim_commitment,
+            claimer,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for CommitClaim<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.drop.to_account_metas(override_signer));
+        metas.extend(self.claim_commitment.to_account_metas(override_signer));
+        metas.extend(self.claimer.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CommitClaim<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.drop.to_account_infos());
+        infos.extend(self.claim_commitment.to_account_infos());
+        infos.extend(self.claimer.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CommitClaim<'info> {}
+
+pub(crate) mod __client_accounts_commit_claim {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CommitClaim {
+        pub drop: Pubkey,
+        pub claim_commitment: Pubkey,
+        pub claimer: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CommitClaim {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.drop,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.claim_commitment,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.claimer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ClaimDropToShielded<'info> {
+    pub drop: UncheckedAccount<'info>,
+    pub nullifier_account: UncheckedAccount<'info>,
+    pub claimer: Signer<'info>,
+    pub config: Account<'info, Config>,
+    pub sol_vault: SystemAccount<'info>,
+    pub shielded_config: Box<Account<'info, ShieldedConfig>>,
+    pub shielded_tree: Box<Account<'info, ShieldedMerkleTree>>,
+    pub shielded_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+    pub partner: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> Bumps for ClaimDropToShielded<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ClaimDropToShielded<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let nullifier_account =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let claimer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let shielded_config = Box::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let shielded_tree = Box::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let shielded_vault =
+            SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let instructions_sysvar =
+            Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let partner = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            drop,
+            nullifier_account,
+            claimer,
+            config,
+            sol_vault,
+            shielded_config,
+            shielded_tree,
+            shielded_vault,
+            system_program,
+            instructions_sysvar,
+            partner,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for ClaimDropToShielded<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.drop.to_account_metas(override_signer));
+        metas.extend(self.nullifier_account.to_account_metas(override_signer));
+        metas.extend(self.claimer.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.shielded_config.to_account_metas(override_signer));
+        metas.extend(self.shielded_tree.to_account_metas(override_signer));
+        metas.extend(self.shielded_vault.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        if let Some(instructions_sysvar) = &self.instructions_sysvar {
+            metas.extend(instructions_sysvar.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(partner) = &self.partner {
+            metas.extend(partner.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ClaimDropToShielded<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.drop.to_account_infos());
+        infos.extend(self.nullifier_account.to_account_infos());
+        infos.extend(self.claimer.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.shielded_config.to_account_infos());
+        infos.extend(self.shielded_tree.to_account_infos());
+        infos.extend(self.shielded_vault.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos.extend(self.instructions_sysvar.to_account_infos());
+        infos.extend(self.partner.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ClaimDropToShielded<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        self.shielded_config.exit(program_id)?;
+        self.shielded_tree.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_claim_drop_to_shielded {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ClaimDropToShielded {
+        pub drop: Pubkey,
+        pub nullifier_account: Pubkey,
+        pub claimer: Pubkey,
+        pub config: Pubkey,
+        pub sol_vault: Pubkey,
+        pub shielded_config: Pubkey,
+        pub shielded_tree: Pubkey,
+        pub shielded_vault: Pubkey,
+        pub system_program: Pubkey,
+        pub instructions_sysvar: Option<Pubkey>,
+        pub partner: Option<Pubkey>,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ClaimDropToShielded {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.drop, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.nullifier_account,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.claimer,
+                    true,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.shielded_config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.shielded_tree,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.shielded_vault,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+                optional_account_meta(self.instructions_sysvar, false),
+                optional_account_meta(self.partner, true),
+            ]
+        }
+    }
+}
+
+pub struct ProposeConfigChange<'info> {
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub policy: UncheckedAccount<'info>,
+}
+
+impl<'info> Bumps for ProposeConfigChange<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ProposeConfigChange<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let policy = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            config,
+            authority,
+            policy,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for ProposeConfigChange<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.policy.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ProposeConfigChange<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.policy.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ProposeConfigChange<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_propose_config_change {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ProposeConfigChange {
+        pub config: Pubkey,
+        pub authority: Pubkey,
+        pub policy: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ProposeConfigChange {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.policy,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ApplyConfigChange<'info> {
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> Bumps for ApplyConfigChange<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ApplyConfigChange<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { config })
+    }
+}
+
+impl<'info> ToAccountMetas for ApplyConfigChange<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ApplyConfigChange<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ApplyConfigChange<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_apply_config_change {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ApplyConfigChange {
+        pub config: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ApplyConfigChange {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+            ]
+        }
+    }
+}
+
+pub struct ApplyFeeChange<'info> {
+    pub config: Account<'info, Config>,
+    pub policy: UncheckedAccount<'info>,
+}
+
+impl<'info> Bumps for ApplyFeeChange<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ApplyFeeChange<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let policy =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { config, policy })
+    }
+}
+
+impl<'info> ToAccountMetas for ApplyFeeChange<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.policy.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ApplyFeeChange<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.policy.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ApplyFeeChange<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_apply_fee_change {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ApplyFeeChange {
+        pub config: Pubkey,
+        pub policy: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ApplyFeeChange {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.policy,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct SetPauseGuardian<'info> {
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
+
+impl<'info> Bumps for SetPauseGuardian<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for SetPauseGuardian<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { config, authority })
+    }
+}
+
+impl<'info> ToAccountMetas for SetPauseGuardian<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for SetPauseGuardian<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for SetPauseGuardian<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_set_pause_guardian {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct SetPauseGuardian {
+        pub config: Pubkey,
+        pub authority: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for SetPauseGuardian {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct SetPause<'info> {
+    pub config: Account<'info, Config>,
+    pub signer: Signer<'info>,
+}
+
+impl<'info> Bumps for SetPause<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for SetPause<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let signer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { config, signer })
+    }
+}
+
+impl<'info> ToAccountMetas for SetPause<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.signer.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for SetPause<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.signer.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for SetPause<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_set_pause {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct SetPause {
+        pub config: Pubkey,
+        pub signer: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for SetPause {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.signer,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ProposePolicyUpdate<'info> {
+    pub policy: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for ProposePolicyUpdate<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ProposePolicyUpdate<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let policy = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            policy,
+            config,
+            authority,
+            payer,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for ProposePolicyUpdate<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.policy.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.payer.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ProposePolicyUpdate<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.policy.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.payer.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ProposePolicyUpdate<'info> {}
+
+pub(crate) mod __client_accounts_propose_policy_update {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ProposePolicyUpdate {
+        pub policy: Pubkey,
+        pub config: Pubkey,
+        pub authority: Pubkey,
+        pub payer: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ProposePolicyUpdate {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.policy, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ApplyPolicyUpdate<'info> {
+    pub policy: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> Bumps for ApplyPolicyUpdate<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ApplyPolicyUpdate<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let policy = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { policy, config })
+    }
+}
+
+impl<'info> ToAccountMetas for ApplyPolicyUpdate<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.policy.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ApplyPolicyUpdate<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.policy.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ApplyPolicyUpdate<'info> {}
+
+pub(crate) mod __client_accounts_apply_policy_update {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ApplyPolicyUpdate {
+        pub policy: Pubkey,
+        pub config: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ApplyPolicyUpdate {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.policy, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.config,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ProposeAuthority<'info> {
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
+
+pub struct CancelAuthorityProposal<'info> {
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
+
+pub struct AcceptAuthority<'info> {
+    pub config: Account<'info, Config>,
+    pub pending_authority: Signer<'info>,
+}
+
+pub struct UpdateAuthorityDelay<'info> {
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
+
+impl<'info> Bumps for ProposeAuthority<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ProposeAuthority<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { config, authority })
+    }
+}
+
+impl<'info> ToAccountMetas for ProposeAuthority<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ProposeAuthority<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ProposeAuthority<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_propose_authority {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ProposeAuthority {
+        pub config: Pubkey,
+        pub authority: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ProposeAuthority {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
+            ]
+        }
+    }
+}
+
+impl<'info> Bumps for CancelAuthorityProposal<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CancelAuthorityProposal<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { config, authority })
+    }
+}
+
+impl<'info> ToAccountMetas for CancelAuthorityProposal<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CancelAuthorityProposal<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CancelAuthorityProposal<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_cancel_authority_proposal {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CancelAuthorityProposal {
+        pub config: Pubkey,
+        pub authority: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CancelAuthorityProposal {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
+            ]
+        }
+    }
+}
+
+impl<'info> Bumps for AcceptAuthority<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for AcceptAuthority<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let pending_authority =
+            Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            config,
+            pending_authority,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for AcceptAuthority<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.pending_authority.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for AcceptAuthority<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.pending_authority.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for AcceptAuthority<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_accept_authority {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct AcceptAuthority {
+        pub config: Pubkey,
+        pub pending_authority: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for AcceptAuthority {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.pending_authority,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+impl<'info> Bumps for UpdateAuthorityDelay<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for UpdateAuthorityDelay<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { config, authority })
+    }
+}
+
+impl<'info> ToAccountMetas for UpdateAuthorityDelay<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for UpdateAuthorityDelay<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for UpdateAuthorityDelay<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_update_authority_delay {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct UpdateAuthorityDelay {
+        pub config: Pubkey,
+        pub authority: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for UpdateAuthorityDelay {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
+            ]
+        }
+    }
+}
+
+pub struct ExpireDrop<'info> {
+    pub drop: UncheckedAccount<'info>,
+    pub nullifier_account: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub rent_collector: SystemAccount<'info>,
+    pub sol_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub funder: Option<SystemAccount<'info>>,
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    pub token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub funder_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub token_ledger: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> Bumps for ExpireDrop<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ExpireDrop<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let nullifier_account =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let rent_collector =
+            SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let funder = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let mint = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_vault = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let funder_token_account =
+            Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_program = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_ledger = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            drop,
+            nullifier_account,
+            config,
+            authority,
+            rent_collector,
+            sol_vault,
+            system_program,
+            funder,
+            mint,
+            token_vault,
+            funder_token_account,
+            token_program,
+            token_ledger,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for ExpireDrop<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.drop.to_account_metas(override_signer));
+        metas.extend(self.nullifier_account.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.rent_collector.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        if let Some(funder) = &self.funder {
+            metas.extend(funder.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(mint) = &self.mint {
+            metas.extend(mint.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_vault) = &self.token_vault {
+            metas.extend(token_vault.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(funder_token_account) = &self.funder_token_account {
+            metas.extend(funder_token_account.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_program) = &self.token_program {
+            metas.extend(token_program.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_ledger) = &self.token_ledger {
+            metas.extend(token_ledger.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ExpireDrop<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.drop.to_account_infos());
+        infos.extend(self.nullifier_account.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.rent_collector.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos.extend(self.funder.to_account_infos());
+        infos.extend(self.mint.to_account_infos());
+        infos.extend(self.token_vault.to_account_infos());
+        infos.extend(self.funder_token_account.to_account_infos());
+        infos.extend(self.token_program.to_account_infos());
+        infos.extend(self.token_ledger.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ExpireDrop<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_expire_drop {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ExpireDrop {
+        pub drop: Pubkey,
+        pub nullifier_account: Pubkey,
+        pub config: Pubkey,
+        pub authority: Pubkey,
+        pub rent_collector: Pubkey,
+        pub sol_vault: Pubkey,
+        pub system_program: Pubkey,
+        pub funder: Option<Pubkey>,
+        pub mint: Option<Pubkey>,
+        pub token_vault: Option<Pubkey>,
+        pub funder_token_account: Option<Pubkey>,
+        pub token_program: Option<Pubkey>,
+        pub token_ledger: Option<Pubkey>,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ExpireDrop {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.drop, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.nullifier_account,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.rent_collector,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+                optional_account_meta(self.funder, true),
+                optional_account_meta(self.mint, false),
+                optional_account_meta(self.token_vault, true),
+                optional_account_meta(self.funder_token_account, true),
+                optional_account_meta(self.token_program, false),
+                optional_account_meta(self.token_ledger, true),
+            ]
+        }
+    }
+}
+
+pub struct CancelDrop<'info> {
+    pub drop: UncheckedAccount<'info>,
+    pub nullifier_account: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub canceller: Signer<'info>,
+    pub sol_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub funder: Option<SystemAccount<'info>>,
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    pub token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub funder_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub token_ledger: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> Bumps for CancelDrop<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CancelDrop<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let nullifier_account =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let canceller = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let funder = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let mint = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_vault = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let funder_token_account =
+            Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_program = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let token_ledger = Option::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            drop,
+            nullifier_account,
+            config,
+            canceller,
+            sol_vault,
+            system_program,
+            funder,
+            mint,
+            token_vault,
+            funder_token_account,
+            token_program,
+            token_ledger,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for CancelDrop<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.drop.to_account_metas(override_signer));
+        metas.extend(self.nullifier_account.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.canceller.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        if let Some(funder) = &self.funder {
+            metas.extend(funder.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(mint) = &self.mint {
+            metas.extend(mint.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_vault) = &self.token_vault {
+            metas.extend(token_vault.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(funder_token_account) = &self.funder_token_account {
+            metas.extend(funder_token_account.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_program) = &self.token_program {
+            metas.extend(token_program.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        if let Some(token_ledger) = &self.token_ledger {
+            metas.extend(token_ledger.to_account_metas(override_signer));
+        } else {
+            metas.push(AccountMeta::new_readonly(crate::ID, false));
+        }
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CancelDrop<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.drop.to_account_infos());
+        infos.extend(self.nullifier_account.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.canceller.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos.extend(self.funder.to_account_infos());
+        infos.extend(self.mint.to_account_infos());
+        infos.extend(self.token_vault.to_account_infos());
+        infos.extend(self.funder_token_account.to_account_infos());
+        infos.extend(self.token_program.to_account_infos());
+        infos.extend(self.token_ledger.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CancelDrop<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_cancel_drop {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CancelDrop {
+        pub drop: Pubkey,
+        pub nullifier_account: Pubkey,
+        pub config: Pubkey,
+        pub canceller: Pubkey,
+        pub sol_vault: Pubkey,
+        pub system_program: Pubkey,
+        pub funder: Option<Pubkey>,
+        pub mint: Option<Pubkey>,
+        pub token_vault: Option<Pubkey>,
+        pub funder_token_account: Option<Pubkey>,
+        pub token_program: Option<Pubkey>,
+        pub token_ledger: Option<Pubkey>,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CancelDrop {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.drop, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.nullifier_account,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.canceller, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+                optional_account_meta(self.funder, true),
+                optional_account_meta(self.mint, false),
+                optional_account_meta(self.token_vault, true),
+                optional_account_meta(self.funder_token_account, true),
+                optional_account_meta(self.token_program, false),
+                optional_account_meta(self.token_ledger, true),
+            ]
+        }
+    }
+}
+
+pub struct SweepFees<'info> {
+    pub config: Account<'info, Config>,
+    pub sol_vault: SystemAccount<'info>,
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for SweepFees<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for SweepFees<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let treasury = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            config,
+            sol_vault,
+            treasury,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for SweepFees<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.treasury.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for SweepFees<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.treasury.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for SweepFees<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_sweep_fees {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct SweepFees {
+        pub config: Pubkey,
+        pub sol_vault: Pubkey,
+        pub treasury: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for SweepFees {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.treasury, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct RegisterPartner<'info> {
+    pub partner: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for RegisterPartner<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for RegisterPartner<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let partner =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            partner,
+            config,
+            authority,
+            payer,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for RegisterPartner<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.partner.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.payer.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for RegisterPartner<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.partner.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.payer.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for RegisterPartner<'info> {}
+
+pub(crate) mod __client_accounts_register_partner {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct RegisterPartner {
+        pub partner: Pubkey,
+        pub config: Pubkey,
+        pub authority: Pubkey,
+        pub payer: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for RegisterPartner {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.partner, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct RemovePartner<'info> {
+    pub partner: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub wallet: SystemAccount<'info>,
+    pub sol_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for RemovePartner<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for RemovePartner<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let partner =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let wallet = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            partner,
+            config,
+            authority,
+            wallet,
+            sol_vault,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for RemovePartner<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.partner.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.wallet.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for RemovePartner<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.partner.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.wallet.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for RemovePartner<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_remove_partner {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct RemovePartner {
+        pub partner: Pubkey,
+        pub config: Pubkey,
+        pub authority: Pubkey,
+        pub wallet: Pubkey,
+        pub sol_vault: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for RemovePartner {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.partner, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.wallet, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct WithdrawPartnerEarnings<'info> {
+    pub partner: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub sol_vault: SystemAccount<'info>,
+    pub wallet: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for WithdrawPartnerEarnings<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for WithdrawPartnerEarnings<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let partner =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let wallet = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            partner,
+            config,
+            sol_vault,
+            wallet,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for WithdrawPartnerEarnings<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.partner.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.wallet.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for WithdrawPartnerEarnings<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.partner.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.wallet.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for WithdrawPartnerEarnings<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_withdraw_partner_earnings {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct WithdrawPartnerEarnings {
+        pub partner: Pubkey,
+        pub config: Pubkey,
+        pub sol_vault: Pubkey,
+        pub wallet: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for WithdrawPartnerEarnings {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.partner, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.wallet, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct AddCreatorRole<'info> {
+    pub creator_role: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for AddCreatorRole<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for AddCreatorRole<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let creator_role =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            creator_role,
+            config,
+            authority,
+            payer,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for AddCreatorRole<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.creator_role.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.payer.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for AddCreatorRole<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.creator_role.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.payer.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for AddCreatorRole<'info> {}
+
+pub(crate) mod __client_accounts_add_creator_role {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct AddCreatorRole {
+        pub creator_role: Pubkey,
+        pub config: Pubkey,
+        pub authority: Pubkey,
+        pub payer: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for AddCreatorRole {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.creator_role,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct SetCreatorRoleEnabled<'info> {
+    pub creator_role: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
+
+impl<'info> Bumps for SetCreatorRoleEnabled<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for SetCreatorRoleEnabled<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let creator_role =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            creator_role,
+            config,
+            authority,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for SetCreatorRoleEnabled<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.creator_role.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for SetCreatorRoleEnabled<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.creator_role.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for SetCreatorRoleEnabled<'info> {}
+
+pub(crate) mod __client_accounts_set_creator_role_enabled {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct SetCreatorRoleEnabled {
+        pub creator_role: Pubkey,
+        pub config: Pubkey,
+        pub authority: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for SetCreatorRoleEnabled {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.creator_role,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct RemoveCreatorRole<'info> {
+    pub creator_role: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub rent_receiver: UncheckedAccount<'info>,
+}
+
+impl<'info> Bumps for RemoveCreatorRole<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for RemoveCreatorRole<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let creator_role =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let rent_receiver =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            creator_role,
+            config,
+            authority,
+            rent_receiver,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for RemoveCreatorRole<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.creator_role.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.rent_receiver.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for RemoveCreatorRole<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.creator_role.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.rent_receiver.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for RemoveCreatorRole<'info> {}
+
+pub(crate) mod __client_accounts_remove_creator_role {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct RemoveCreatorRole {
+        pub creator_role: Pubkey,
+        pub config: Pubkey,
+        pub authority: Pubkey,
+        pub rent_receiver: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for RemoveCreatorRole {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.creator_role,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.rent_receiver,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct CreateMultisig<'info> {
+    pub multisig: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for CreateMultisig<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CreateMultisig<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let multisig =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            multisig,
+            config,
+            authority,
+            payer,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for CreateMultisig<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.multisig.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.payer.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CreateMultisig<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.multisig.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.payer.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CreateMultisig<'info> {}
+
+pub(crate) mod __client_accounts_create_multisig {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CreateMultisig {
+        pub multisig: Pubkey,
+        pub config: Pubkey,
+        pub authority: Pubkey,
+        pub payer: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CreateMultisig {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.multisig, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ProposeMultisigTransaction<'info> {
+    pub multisig: Account<'info, Multisig>,
+    pub transaction: UncheckedAccount<'info>,
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for ProposeMultisigTransaction<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ProposeMultisigTransaction<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let multisig = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let transaction =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let proposer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            multisig,
+            transaction,
+            proposer,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for ProposeMultisigTransaction<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.multisig.to_account_metas(override_signer));
+        metas.extend(self.transaction.to_account_metas(override_signer));
+        metas.extend(self.proposer.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ProposeMultisigTransaction<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.multisig.to_account_infos());
+        infos.extend(self.transaction.to_account_infos());
+        infos.extend(self.proposer.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ProposeMultisigTransaction<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.multisig.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_propose_multisig_transaction {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ProposeMultisigTransaction {
+        pub multisig: Pubkey,
+        pub transaction: Pubkey,
+        pub proposer: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ProposeMultisigTransaction {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.multisig, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.transaction, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.proposer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ApproveMultisigTransaction<'info> {
+    pub multisig: Account<'info, Multisig>,
+    pub transaction: UncheckedAccount<'info>,
+    pub member: Signer<'info>,
+}
+
+impl<'info> Bumps for ApproveMultisigTransaction<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ApproveMultisigTransaction<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let multisig = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let transaction =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let member = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            multisig,
+            transaction,
+            member,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for ApproveMultisigTransaction<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.multisig.to_account_metas(override_signer));
+        metas.extend(self.transaction.to_account_metas(override_signer));
+        metas.extend(self.member.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ApproveMultisigTransaction<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.multisig.to_account_infos());
+        infos.extend(self.transaction.to_account_infos());
+        infos.extend(self.member.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ApproveMultisigTransaction<'info> {}
+
+pub(crate) mod __client_accounts_approve_multisig_transaction {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ApproveMultisigTransaction {
+        pub multisig: Pubkey,
+        pub transaction: Pubkey,
+        pub member: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ApproveMultisigTransaction {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.multisig,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.transaction, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.member,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ExecuteMultisigTransaction<'info> {
+    pub multisig: Account<'info, Multisig>,
+    pub transaction: UncheckedAccount<'info>,
+    pub multisig_signer: UncheckedAccount<'info>,
+    pub proposer: SystemAccount<'info>,
+}
+
+impl<'info> Bumps for ExecuteMultisigTransaction<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ExecuteMultisigTransaction<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let multisig = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let transaction =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let multisig_signer =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let proposer = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            multisig,
+            transaction,
+            multisig_signer,
+            proposer,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for ExecuteMultisigTransaction<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.multisig.to_account_metas(override_signer));
+        metas.extend(self.transaction.to_account_metas(override_signer));
+        metas.extend(self.multisig_signer.to_account_metas(override_signer));
+        metas.extend(self.proposer.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ExecuteMultisigTransaction<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.multisig.to_account_infos());
+        infos.extend(self.transaction.to_account_infos());
+        infos.extend(self.multisig_signer.to_account_infos());
+        infos.extend(self.proposer.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ExecuteMultisigTransaction<'info> {}
+
+pub(crate) mod __client_accounts_execute_multisig_transaction {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ExecuteMultisigTransaction {
+        pub multisig: Pubkey,
+        pub transaction: Pubkey,
+        pub multisig_signer: Pubkey,
+        pub proposer: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ExecuteMultisigTransaction {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.multisig,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.transaction, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.multisig_signer,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.proposer, false),
+            ]
+        }
+    }
+}
+
+pub struct CloseMultisigTransaction<'info> {
+    pub multisig: Account<'info, Multisig>,
+    pub transaction: UncheckedAccount<'info>,
+    pub proposer: SystemAccount<'info>,
+    pub closer: Signer<'info>,
+}
+
+impl<'info> Bumps for CloseMultisigTransaction<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CloseMultisigTransaction<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let multisig = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let transaction =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let proposer = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let closer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            multisig,
+            transaction,
+            proposer,
+            closer,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for CloseMultisigTransaction<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.multisig.to_account_metas(override_signer));
+        metas.extend(self.transaction.to_account_metas(override_signer));
+        metas.extend(self.proposer.to_account_metas(override_signer));
+        metas.extend(self.closer.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CloseMultisigTransaction<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.multisig.to_account_infos());
+        infos.extend(self.transaction.to_account_infos());
+        infos.extend(self.proposer.to_account_infos());
+        infos.extend(self.closer.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CloseMultisigTransaction<'info> {}
+
+pub(crate) mod __client_accounts_close_multisig_transaction {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CloseMultisigTransaction {
+        pub multisig: Pubkey,
+        pub transaction: Pubkey,
+        pub proposer: Pubkey,
+        pub closer: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CloseMultisigTransaction {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.multisig,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.transaction, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.proposer, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.closer,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct SetMultisigMembers<'info> {
+    pub multisig: Account<'info, Multisig>,
+    pub multisig_signer: Signer<'info>,
+}
+
+impl<'info> Bumps for SetMultisigMembers<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for SetMultisigMembers<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let multisig = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let multisig_signer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { multisig, multisig_signer })
+    }
+}
+
+impl<'info> ToAccountMetas for SetMultisigMembers<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.multisig.to_account_metas(override_signer));
+        metas.extend(self.multisig_signer.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for SetMultisigMembers<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.multisig.to_account_infos());
+        infos.extend(self.multisig_signer.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for SetMultisigMembers<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.multisig.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_set_multisig_members {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct SetMultisigMembers {
+        pub multisig: Pubkey,
+        pub multisig_signer: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for SetMultisigMembers {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.multisig, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.multisig_signer,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct SetCreatorRoleLimits<'info> {
+    pub creator_role: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
+
+impl<'info> Bumps for SetCreatorRoleLimits<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for SetCreatorRoleLimits<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let creator_role =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            creator_role,
+            config,
+            authority,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for SetCreatorRoleLimits<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.creator_role.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for SetCreatorRoleLimits<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.creator_role.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for SetCreatorRoleLimits<'info> {}
+
+pub(crate) mod __client_accounts_set_creator_role_limits {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct SetCreatorRoleLimits {
+        pub creator_role: Pubkey,
+        pub config: Pubkey,
+        pub authority: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for SetCreatorRoleLimits {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.creator_role,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ConfigureOutflowLimit<'info> {
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
+
+impl<'info> Bumps for ConfigureOutflowLimit<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ConfigureOutflowLimit<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { config, authority })
+    }
+}
+
+impl<'info> ToAccountMetas for ConfigureOutflowLimit<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ConfigureOutflowLimit<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ConfigureOutflowLimit<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_configure_outflow_limit {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ConfigureOutflowLimit {
+        pub config: Pubkey,
+        pub authority: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ConfigureOutflowLimit {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct MigrateAccount<'info> {
+    pub account: UncheckedAccount<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for MigrateAccount<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for MigrateAccount<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let account =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            account,
+            payer,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for MigrateAccount<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.account.to_account_metas(override_signer));
+        metas.extend(self.payer.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for MigrateAccount<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.account.to_account_infos());
+        infos.extend(self.payer.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for MigrateAccount<'info> {}
+
+pub(crate) mod __client_accounts_migrate_account {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct MigrateAccount {
+        pub account: Pubkey,
+        pub payer: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for MigrateAccount {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.account, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct EnqueueAdminAction<'info> {
+    pub config: Account<'info, Config>,
+    pub admin_action: UncheckedAccount<'info>,
+    pub policy: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for EnqueueAdminAction<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for EnqueueAdminAction<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let admin_action =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let policy = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            config,
+            admin_action,
+            policy,
+            authority,
+            payer,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for EnqueueAdminAction<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.admin_action.to_account_metas(override_signer));
+        metas.extend(self.policy.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas.extend(self.payer.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for EnqueueAdminAction<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.admin_action.to_account_infos());
+        infos.extend(self.policy.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos.extend(self.payer.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for EnqueueAdminAction<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_enqueue_admin_action {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct EnqueueAdminAction {
+        pub config: Pubkey,
+        pub admin_action: Pubkey,
+        pub policy: Pubkey,
+        pub authority: Pubkey,
+        pub payer: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for EnqueueAdminAction {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.admin_action,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.policy, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ExecuteAdminAction<'info> {
+    pub config: Account<'info, Config>,
+    pub admin_action: UncheckedAccount<'info>,
+    pub payer: UncheckedAccount<'info>,
+    pub policy: UncheckedAccount<'info>,
+    pub executor: Signer<'info>,
+}
+
+impl<'info> Bumps for ExecuteAdminAction<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ExecuteAdminAction<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let admin_action =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let policy = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let executor = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            config,
+            admin_action,
+            payer,
+            policy,
+            executor,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for ExecuteAdminAction<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.admin_action.to_account_metas(override_signer));
+        metas.extend(self.payer.to_account_metas(override_signer));
+        metas.extend(self.policy.to_account_metas(override_signer));
+        metas.extend(self.executor.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ExecuteAdminAction<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.admin_action.to_account_infos());
+        infos.extend(self.payer.to_account_infos());
+        infos.extend(self.policy.to_account_infos());
+        infos.extend(self.executor.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ExecuteAdminAction<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_execute_admin_action {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ExecuteAdminAction {
+        pub config: Pubkey,
+        pub admin_action: Pubkey,
+        pub payer: Pubkey,
+        pub policy: Pubkey,
+        pub executor: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ExecuteAdminAction {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.admin_action,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.policy, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.executor,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct CancelAdminAction<'info> {
+    pub config: Account<'info, Config>,
+    pub admin_action: UncheckedAccount<'info>,
+    pub payer: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+}
+
+impl<'info> Bumps for CancelAdminAction<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CancelAdminAction<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let admin_action =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let payer =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            config,
+            admin_action,
+            payer,
+            authority,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for CancelAdminAction<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.admin_action.to_account_metas(override_signer));
+        metas.extend(self.payer.to_account_metas(override_signer));
+        metas.extend(self.authority.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CancelAdminAction<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.admin_action.to_account_infos());
+        infos.extend(self.payer.to_account_infos());
+        infos.extend(self.authority.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CancelAdminAction<'info> {}
+
+pub(crate) mod __client_accounts_cancel_admin_action {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CancelAdminAction {
+        pub config: Pubkey,
+        pub admin_action: Pubkey,
+        pub payer: Pubkey,
+        pub authority: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CancelAdminAction {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.admin_action,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.authority,
+                    true,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct CloseClaimCommitment<'info> {
+    pub claim_commitment: UncheckedAccount<'info>,
+    pub claimer: Signer<'info>,
+}
+
+impl<'info> Bumps for CloseClaimCommitment<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CloseClaimCommitment<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let claim_commitment =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let claimer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { claim_commitment, claimer })
+    }
+}
+
+impl<'info> ToAccountMetas for CloseClaimCommitment<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.claim_commitment.to_account_metas(override_signer));
+        metas.extend(self.claimer.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CloseClaimCommitment<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.claim_commitment.to_account_infos());
+        infos.extend(self.claimer.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CloseClaimCommitment<'info> {}
+
+pub(crate) mod __client_accounts_close_claim_commitment {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CloseClaimCommitment {
+        pub claim_commitment: Pubkey,
+        pub claimer: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CloseClaimCommitment {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(
+                    self.claim_commitment,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.claimer, true),
+            ]
+        }
+    }
+}
+
+pub struct CheckSolvency<'info> {
+    pub config: Account<'info, Config>,
+    pub sol_vault: SystemAccount<'info>,
+}
+
+impl<'info> Bumps for CheckSolvency<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CheckSolvency<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self { config, sol_vault })
+    }
+}
+
+impl<'info> ToAccountMetas for CheckSolvency<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CheckSolvency<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CheckSolvency<'info> {}
+
+pub(crate) mod __client_accounts_check_solvency {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CheckSolvency {
+        pub config: Pubkey,
+        pub sol_vault: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CheckSolvency {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.config,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.sol_vault,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct CreateDistributor<'info> {
+    pub distributor: UncheckedAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub creator: Signer<'info>,
+    pub sol_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub policy: UncheckedAccount<'info>,
+}
+
+impl<'info> Bumps for CreateDistributor<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CreateDistributor<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let distributor =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let creator = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let policy = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            distributor,
+            config,
+            creator,
+            sol_vault,
+            system_program,
+            policy,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for CreateDistributor<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.distributor.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.creator.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas.extend(self.policy.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CreateDistributor<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.distributor.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.creator.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos.extend(self.policy.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CreateDistributor<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_create_distributor {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CreateDistributor {
+        pub distributor: Pubkey,
+        pub config: Pubkey,
+        pub creator: Pubkey,
+        pub sol_vault: Pubkey,
+        pub system_program: Pubkey,
+        pub policy: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CreateDistributor {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.distributor, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.creator, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.policy,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct ClaimDistributor<'info> {
+    pub distributor: UncheckedAccount<'info>,
+    pub claimer: Signer<'info>,
+    pub config: Account<'info, Config>,
+    pub sol_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Bumps for ClaimDistributor<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for ClaimDistributor<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let distributor =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let claimer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            distributor,
+            claimer,
+            config,
+            sol_vault,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for ClaimDistributor<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.distributor.to_account_metas(override_signer));
+        metas.extend(self.claimer.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for ClaimDistributor<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.distributor.to_account_infos());
+        infos.extend(self.claimer.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for ClaimDistributor<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_claim_distributor {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct ClaimDistributor {
+        pub distributor: Pubkey,
+        pub claimer: Pubkey,
+        pub config: Pubkey,
+        pub sol_vault: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for ClaimDistributor {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.distributor, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.claimer, true),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+pub struct CloseDistributor<'info> {
+    pub distributor: UncheckedAccount<'info>,
+    pub creator: SystemAccount<'info>,
+    pub config: Account<'info, Config>,
+    pub sol_vault: SystemAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
-impl<'info> Bumps for DepositShielded<'info> {
-    type Bumps = ();
+impl<'info> Bumps for CloseDistributor<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info, ()> for CloseDistributor<'info> {
+    fn try_accounts(
+        program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut (),
+        reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        let distributor =
+            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let creator = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        let system_program =
+            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
+        Ok(Self {
+            distributor,
+            creator,
+            config,
+            sol_vault,
+            system_program,
+        })
+    }
+}
+
+impl<'info> ToAccountMetas for CloseDistributor<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let mut metas = Vec::new();
+        let override_signer = is_signer;
+        metas.extend(self.distributor.to_account_metas(override_signer));
+        metas.extend(self.creator.to_account_metas(override_signer));
+        metas.extend(self.config.to_account_metas(override_signer));
+        metas.extend(self.sol_vault.to_account_metas(override_signer));
+        metas.extend(self.system_program.to_account_metas(override_signer));
+        metas
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for CloseDistributor<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = Vec::new();
+        infos.extend(self.distributor.to_account_infos());
+        infos.extend(self.creator.to_account_infos());
+        infos.extend(self.config.to_account_infos());
+        infos.extend(self.sol_vault.to_account_infos());
+        infos.extend(self.system_program.to_account_infos());
+        infos
+    }
+}
+
+impl<'info> AccountsExit<'info> for CloseDistributor<'info> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.config.exit(program_id)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod __client_accounts_close_distributor {
+    use super::*;
+    use anchor_lang::prelude::borsh;
+
+    #[derive(anchor_lang::AnchorSerialize)]
+    pub struct CloseDistributor {
+        pub distributor: Pubkey,
+        pub creator: Pubkey,
+        pub config: Pubkey,
+        pub sol_vault: Pubkey,
+        pub system_program: Pubkey,
+    }
+
+    #[automatically_derived]
+    impl anchor_lang::ToAccountMetas for CloseDistributor {
+        fn to_account_metas(
+            &self,
+            _is_signer: Option<bool>,
+        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+            vec![
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.distributor, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.creator, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
+                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                    self.system_program,
+                    false,
+                ),
+            ]
+        }
+    }
+}
+
+#[account]
+pub struct Config {
+    pub authority: Pubkey,
+    pub is_initialized: bool,
+    pub pending_authority: Pubkey,
+    pub pending_authority_set_at: i64,
+    pub authority_delay_seconds: i64,
+    pub treasury: Pubkey,
+    pub fee_bps: u16,
+    pub sol_vault_bump: u8,
+    /// SOL owed to active drops and distributors; `sol_vault` must always cover it.
+    pub outstanding_liabilities: u64,
+    /// Claim fees held in `sol_vault` until `sweep_fees` sends them to `treasury`.
+    pub accrued_fees: u64,
+    pub pending_fee_bps: u16,
+    pub pending_fee_effective_at: i64,
+    pub pending_treasury: Pubkey,
+    pub pending_treasury_effective_at: i64,
+    /// Fee schedules for SOL and token drops; a disabled schedule falls back to `fee_bps` at claim.
+    pub sol_fee_schedule: FeeSchedule,
+    pub token_fee_schedule: FeeSchedule,
+    pub pending_fee_schedule: FeeSchedule,
+    pub pending_fee_schedule_asset_type: u8,
+    pub pending_fee_schedule_effective_at: i64,
+    /// `PAUSE_*` bits for halted instruction groups.
+    pub paused: u8,
+    /// Key that may pause (but not unpause) alongside the authority; default when unset.
+    pub pause_guardian: Pubkey,
+    /// SOL allowed to leave `sol_vault` through claims and sweeps per window; 0 disables it.
+    pub outflow_window_seconds: i64,
+    pub outflow_limit: u64,
+    pub outflow_window_start: i64,
+    pub outflow_in_window: u64,
+    /// Set once `outflow_limit` is crossed; claims fail until the authority resets it.
+    pub outflow_tripped: bool,
+    /// Layout version; 0 for accounts written before versioning, see `migrate_config`.
+    pub version: u8,
+    /// How long a pending authority may accept once the delay has passed; 0 uses the default.
+    pub authority_acceptance_window_seconds: i64,
+    /// Index of the next `QueuedAdminAction`.
+    pub admin_action_count: u64,
+    /// Partner fee shares held in `sol_vault` until each partner withdraws them.
+    pub partner_accrued_fees: u64,
+    /// When `pending_authority` stops being able to accept, fixed at proposal time.
+    pub pending_authority_expires_at: i64,
+}
+
+fn max_leaves_for_depth(depth: u8) -> Result<u32> {
+    require!(
+        depth > 0 && depth <= SHIELDED_TREE_MAX_DEPTH,
+        DarkPoolError::InvalidTreeDepth
+    );
+    1u32
+        .checked_shl(depth as u32)
+        .ok_or(error!(DarkPoolError::NumericalOverflow))
+}
+
+#[cfg(feature = "poseidon")]
+fn poseidon_params() -> PoseidonParameters<Fr> {
+    let ark = poseidon_constants_fr::POSEIDON_ARK_FR.to_vec();
+    let mds = poseidon_constants_fr::POSEIDON_MDS_FR
+        .iter()
+        .map(|row| row.to_vec())
+        .collect::<Vec<_>>();
+    PoseidonParameters::new(ark, mds, 8, 57, 3, 5)
+}
+
+#[cfg(feature = "poseidon")]
+fn fr_from_bytes(value: &[u8; 32]) -> Fr {
+    <Fr as ark_ff::PrimeField>::from_be_bytes_mod_order(value)
+}
+
+#[cfg(feature = "poseidon")]
+fn fr_to_bytes(value: &Fr) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let bytes = <Fr as ark_ff::PrimeField>::into_bigint(*value).to_bytes_be();
+    let start = 32usize.saturating_sub(bytes.len());
+    out[start..].copy_from_slice(&bytes);
+    out
+}
+
+#[cfg(feature = "poseidon")]
+fn hash_pair_bytes(left: &[u8; 32], right: &[u8; 32]) -> Result<[u8; 32]> {
+    let mut poseidon = Poseidon::<Fr>::new(poseidon_params());
+    let left_fr = fr_from_bytes(left);
+    let right_fr = fr_from_bytes(right);
+    let hash = poseidon
+        .hash(&[left_fr, right_fr])
+        .map_err(|_| error!(DarkPoolError::PoseidonHashFailed))?;
+    Ok(fr_to_bytes(&hash))
+}
+
+#[cfg(not(feature = "poseidon"))]
+fn hash_pair_bytes(left: &[u8; 32], right: &[u8; 32]) -> Result<[u8; 32]> {
+    Ok(hashv(&[left, right]).to_bytes())
+}
+
+fn merkle_insert(
+    tree: &mut ShieldedMerkleTree,
+    commitment: [u8; 32],
+) -> Result<(u32, [u8; 32])> {
+    let depth = tree.depth as usize;
+    require!(
+        depth > 0 && depth <= SHIELDED_TREE_MAX_DEPTH_USIZE,
+        DarkPoolError::InvalidTreeDepth
+    );
+    let max_leaves = max_leaves_for_depth(tree.depth)?;
+    require!(
+        tree.next_leaf_index < max_leaves,
+        DarkPoolError::ShieldedTreeFull
+    );
+
+    let leaf_index = tree.next_leaf_index;
+    let mut index = leaf_index;
+    let mut current = commitment;
+    for level in 0..depth {
+        if index % 2 == 0 {
+            tree.filled_subtrees[level] = current;
+            current = hash_pair_bytes(&current, &tree.zeroes[level])?;
+        } else {
+            let left = tree.filled_subtrees[level];
+            current = hash_pair_bytes(&left, &current)?;
+        }
+        index /= 2;
+    }
+    tree.root = current;
+    tree.next_leaf_index = tree
+        .next_leaf_index
+        .checked_add(1)
+        .ok_or(DarkPoolError::NumericalOverflow)?;
+    Ok((leaf_index, current))
+}
+
+fn verify_shielded_proof(
+    proof: &[u8],
+    nullifier: &[u8; 32],
+    amount: u64,
+    root: &[u8; 32],
+) -> Result<()> {
+    // TODO: replace with actual verifier integration (e.g. Groth16/Plonk CPI).
+    // For wiring, require non-empty proof and bind to public inputs with a hash.
+    require!(!proof.is_empty(), DarkPoolError::InvalidShieldedProof);
+    let _ = hashv(&[proof, nullifier, &amount.to_le_bytes(), root]);
+    Ok(())
+}
+
+fn process_deposit_pool<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositPool<'info>>,
+    nullifier: [u8; 32],
+    amount: u64,
+    asset_type: u8,
+    expires_at: i64,
+    claim_policy: ClaimPolicy,
+    terms: DropTerms,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let program_id = ctx.program_id;
+    require_not_paused(&ctx.accounts.config, PAUSE_DEPOSIT)?;
+
+    let (drop_pda, drop_bump) =
+        Pubkey::find_program_address(&[b"drop", nullifier.as_ref()], program_id);
+    let (nullifier_pda, nullifier_bump) =
+        Pubkey::find_program_address(&[b"nullifier", nullifier.as_ref()], program_id);
+    let (rate_limit_pda, rate_limit_bump) = Pubkey::find_program_address(
+        &[b"rate_limit", ctx.accounts.payer.key().as_ref()],
+        program_id,
+    );
+    let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+    let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+
+    let drop_info = ctx.accounts.drop.to_account_info();
+    let nullifier_info = ctx.accounts.nullifier_account.to_account_info();
+    let rate_limit_info = ctx.accounts.rate_limit_account.to_account_info();
+    let config_info = ctx.accounts.config.to_account_info();
+    let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+    let system_program_info = ctx.accounts.system_program.to_account_info();
+
+    require_keys_eq!(drop_pda, *drop_info.key, ErrorCode::ConstraintSeeds);
+    require_keys_eq!(
+        nullifier_pda,
+        *nullifier_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require_keys_eq!(
+        rate_limit_pda,
+        *rate_limit_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+    require_keys_eq!(
+        sol_vault_pda,
+        *sol_vault_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require_keys_eq!(
+        system_program::ID,
+        *system_program_info.key,
+        ErrorCode::ConstraintAddress
+    );
+    require!(drop_info.is_writable, ErrorCode::ConstraintMut);
+    require!(nullifier_info.is_writable, ErrorCode::ConstraintMut);
+    require!(rate_limit_info.is_writable, ErrorCode::ConstraintMut);
+    require!(sol_vault_info.is_writable, ErrorCode::ConstraintMut);
+    require!(config_info.is_writable, ErrorCode::ConstraintMut);
+    require!(
+        ctx.accounts.payer.to_account_info().is_writable,
+        ErrorCode::ConstraintMut
+    );
+
+    require!(
+        ctx.accounts.config.is_initialized,
+        DarkPoolError::ConfigNotInitialized
+    );
+    require!(amount > 0, DarkPoolError::InvalidAmount);
+    terms.validate(amount, expires_at)?;
+    let mint = drop_mint(
+        asset_type,
+        ctx.accounts.mint.as_ref().map(|mint| mint.key()),
+    )?;
+    let referrer = drop_referrer(
+        program_id,
+        ctx.accounts
+            .referrer_partner
+            .as_ref()
+            .map(|partner| partner.as_ref()),
+    )?;
+    let policy = load_policy(program_id, ctx.accounts.policy.as_ref())?;
+    if expires_at != 0 {
+        require!(
+            expires_at > now
+                && expires_at - now >= policy.min_expiration_window
+                && expires_at - now <= policy.max_expiration_window,
+            DarkPoolError::InvalidExpiration
+        );
+    }
+
+    // A depositor holding a creator role is held to that role's quota instead of the default.
+    let mut limits = policy.default_quota;
+    if let Some(role) = ctx.accounts.creator_role.as_ref() {
+        let role_state = load_creator_role(program_id, role.as_ref())?;
+        require_keys_eq!(
+            role_state.creator,
+            ctx.accounts.payer.key(),
+            DarkPoolError::InvalidCreatorRole
+        );
+        if role_state.limits.is_set() {
+            limits = role_state.limits;
+        }
+    }
+    enforce_rate_limit(
+        program_id,
+        RateLimitAccounts {
+            rate_limit: &rate_limit_info,
+            payer: &ctx.accounts.payer.to_account_info(),
+            system_program: &system_program_info,
+        },
+        rate_limit_bump,
+        &policy,
+        &limits,
+        if asset_type == ASSET_TYPE_SOL { amount } else { 0 },
+        now,
+    )?;
+
+    let schedule = ctx.accounts.config.fee_schedule(asset_type);
+    let create_fee = if schedule.charge_on_create {
+        schedule.fee_for(amount)
+    } else {
+        0
+    };
+
+    let mut received = amount;
+    if asset_type == ASSET_TYPE_TOKEN {
+        let (mint_account, token_vault, payer_token_account, token_program, token_ledger) = match (
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault.as_ref(),
+            ctx.accounts.payer_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            ctx.accounts.token_ledger.as_ref(),
+        ) {
+            (Some(mint), Some(vault), Some(source), Some(program), Some(ledger)) => {
+                (mint, vault, source, program, ledger)
+            }
+            _ => return err!(DarkPoolError::MissingTokenAccounts),
+        };
+        require_keys_eq!(
+            payer_token_account.mint,
+            mint_account.key(),
+            DarkPoolError::InvalidMint
+        );
+        require_keys_eq!(
+            *mint_account.to_account_info().owner,
+            token_program.key(),
+            DarkPoolError::InvalidTokenProgram
+        );
+        validate_token_mint(&mint_account.to_account_info())?;
+        ensure_token_vault(
+            program_id,
+            &ctx.accounts.payer.to_account_info(),
+            &mint_account.to_account_info(),
+            &token_vault.to_account_info(),
+            &token_ledger.to_account_info(),
+            &token_program.to_account_info(),
+            &system_program_info,
+        )?;
+
+        // Transfer-fee mints withhold part of the transfer in the vault, so the drop
+        // escrows what the vault actually received rather than the requested amount.
+        let vault_before = token_account_amount(&token_vault.to_account_info())?;
+        spl_token_2022::onchain::invoke_transfer_checked(
+            token_program.key,
+            payer_token_account.to_account_info(),
+            mint_account.to_account_info(),
+            token_vault.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            mint_account.decimals,
+            &[],
+        )?;
+        let vault_after = token_account_amount(&token_vault.to_account_info())?;
+        received = vault_after
+            .checked_sub(vault_before)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        require!(received > 0, DarkPoolError::InvalidAmount);
+        add_token_liability(
+            program_id,
+            &token_ledger.to_account_info(),
+            &mint_account.key(),
+            vault_after,
+            received,
+        )?;
+
+        if create_fee > 0 {
+            let treasury_token_account = ctx
+                .accounts
+                .treasury_token_account
+                .as_ref()
+                .ok_or(DarkPoolError::MissingTokenAccounts)?;
+            require_keys_eq!(
+                treasury_token_account.mint,
+                mint_account.key(),
+                DarkPoolError::InvalidMint
+            );
+            require_keys_eq!(
+                treasury_token_account.owner,
+                ctx.accounts.config.treasury,
+                DarkPoolError::InvalidTreasury
+            );
+            spl_token_2022::onchain::invoke_transfer_checked(
+                token_program.key,
+                payer_token_account.to_account_info(),
+                mint_account.to_account_info(),
+                treasury_token_account.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.remaining_accounts,
+                create_fee,
+                mint_account.decimals,
+                &[],
+            )?;
+        }
+    } else {
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.payer.key(),
+            &ctx.accounts.sol_vault.key(),
+            amount
+                .checked_add(create_fee)
+                .ok_or(DarkPoolError::NumericalOverflow)?,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.sol_vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        accrue_sol_fee(&mut ctx.accounts.config, create_fee)?;
+        add_sol_liability(&mut ctx.accounts.config, &sol_vault_info, amount)?;
+    }
+
+    require!(
+        drop_info.owner == &system_program::ID && drop_info.lamports() == 0,
+        DarkPoolError::AccountAlreadyInitialized
+    );
+    let drop_space = (8 + DropAccount::LEN) as u64;
+    let drop_lamports = Rent::get()?.minimum_balance(drop_space as usize);
+    let drop_seeds: &[&[u8]] = &[b"drop", nullifier.as_ref(), &[drop_bump]];
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program_info.clone(),
+            system_program::CreateAccount {
+                from: ctx.accounts.payer.to_account_info(),
+                to: drop_info.clone(),
+            },
+            &[drop_seeds],
+        ),
+        drop_lamports,
+        drop_space,
+        program_id,
+    )?;
+
+    let drop_state = DropAccount {
+        nullifier,
+        recipient: terms.recipient,
+        amount: received,
+        asset_type,
+        created_at: now,
+        expires_at,
+        status: DropStatus::Active,
+        claimed_at: 0,
+        claimer: Pubkey::default(),
+        bump: drop_bump,
+        mint,
+        funder: ctx.accounts.payer.key(),
+        claim_policy,
+        max_claims: terms.max_claims,
+        claimed_count: 0,
+        per_claim_amount: terms.per_claim_amount,
+        claimed_amount: 0,
+        cliff_at: terms.vesting.cliff_at,
+        vest_start: terms.vesting.vest_start,
+        vest_end: terms.vesting.vest_end,
+        fee_prepaid: schedule.charge_on_create,
+        referrer,
+        version: DropAccount::VERSION,
+        escrowed: true,
+        legacy_seed: false,
+    };
+    let mut drop_data = drop_info.try_borrow_mut_data()?;
+    let mut drop_cursor: &mut [u8] = &mut drop_data;
+    drop_state.try_serialize(&mut drop_cursor)?;
+
+    let mut nullifier_state: NullifierAccount;
+    if nullifier_info.owner == program_id {
+        let mut data_slice: &[u8] = &nullifier_info.try_borrow_data()?;
+        nullifier_state = NullifierAccount::try_deserialize(&mut data_slice)?;
+        require_eq!(
+            nullifier_state.bump,
+            nullifier_bump,
+            ErrorCode::ConstraintSeeds
+        );
+    } else {
+        require!(
+            nullifier_info.owner == &system_program::ID && nullifier_info.lamports() == 0,
+            DarkPoolError::AccountAlreadyInitialized
+        );
+        let space = (8 + NullifierAccount::LEN) as u64;
+        let lamports = Rent::get()?.minimum_balance(space as usize);
+        let seeds: &[&[u8]] = &[b"nullifier", nullifier.as_ref(), &[nullifier_bump]];
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program_info,
+                system_program::CreateAccount {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: nullifier_info.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space,
+            program_id,
+        )?;
+        nullifier_state = NullifierAccount {
+            nullifier: [0u8; 32],
+            is_used: false,
+            claimer: Pubkey::default(),
+            used_at: 0,
+            bump: nullifier_bump,
+            version: NullifierAccount::VERSION,
+        };
+    }
+
+    if nullifier_state.nullifier == [0u8; 32] {
+        nullifier_state.nullifier = nullifier;
+        nullifier_state.is_used = false;
+        nullifier_state.claimer = Pubkey::default();
+        nullifier_state.used_at = 0;
+        nullifier_state.bump = nullifier_bump;
+    } else {
+        require!(
+            nullifier_state.nullifier == nullifier,
+            DarkPoolError::InvalidNullifier
+        );
+        require!(
+            !nullifier_state.is_used,
+            DarkPoolError::NullifierAlreadyUsed
+        );
+    }
+    let mut nullifier_data = nullifier_info.try_borrow_mut_data()?;
+    let mut nullifier_cursor: &mut [u8] = &mut nullifier_data;
+    nullifier_state.try_serialize(&mut nullifier_cursor)?;
+
+    emit!(DropCreated {
+        nullifier,
+        recipient: terms.recipient,
+        amount: received,
+        asset_type,
+        expires_at,
+        payer: ctx.accounts.payer.key(),
+        mint,
+    });
+
+    Ok(())
+}
+
+fn process_claim_drop<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimDrop<'info>>,
+    secret: [u8; 32],
+    committed: bool,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let program_id = ctx.program_id;
+    require_not_paused(&ctx.accounts.config, PAUSE_CLAIM)?;
+    require_outflow_open(&ctx.accounts.config)?;
+
+    let (config_pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+    let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+
+    let drop_info = ctx.accounts.drop.to_account_info();
+    let nullifier_info = ctx.accounts.nullifier_account.to_account_info();
+    let config_info = ctx.accounts.config.to_account_info();
+    let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+    let system_program_info = ctx.accounts.system_program.to_account_info();
+
+    require_keys_eq!(config_pda, *config_info.key, ErrorCode::ConstraintSeeds);
+    require_keys_eq!(
+        sol_vault_pda,
+        *sol_vault_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require_keys_eq!(
+        system_program::ID,
+        *system_program_info.key,
+        ErrorCode::ConstraintAddress
+    );
+    require!(sol_vault_info.is_writable, ErrorCode::ConstraintMut);
+    require!(config_info.is_writable, ErrorCode::ConstraintMut);
+    require!(
+        ctx.accounts.claimer.to_account_info().is_writable,
+        ErrorCode::ConstraintMut
+    );
+
+    let (mut drop_state, mut nullifier_state) =
+        load_claimable_drop(program_id, &drop_info, &nullifier_info, &secret, now)?;
+    let nullifier = drop_state.nullifier;
+    enforce_claim_policy(
+        drop_info.key,
+        &drop_state,
+        ctx.accounts.claimer.key,
+        ctx.accounts
+            .instructions_sysvar
+            .as_ref()
+            .map(|sysvar| sysvar.as_ref()),
+        committed,
+    )?;
+
+    let config_state = &ctx.accounts.config;
+    require!(
+        config_state.is_initialized,
+        DarkPoolError::ConfigNotInitialized
+    );
+    // Multi-claim drops pay one share per wallet and track it in a claim record.
+    let claim_amount = drop_state.next_claim_amount(clock.unix_timestamp);
+    if drop_state.is_vesting() {
+        require!(claim_amount > 0, DarkPoolError::NothingVested);
+    }
+    if drop_state.max_claims > 0 {
+        let claim_record = ctx
+            .accounts
+            .claim_record
+            .as_ref()
+            .ok_or(DarkPoolError::MissingClaimRecord)?;
+        create_claim_record(
+            program_id,
+            &drop_info,
+            &claim_record.to_account_info(),
+            &ctx.accounts.claimer.to_account_info(),
+            &system_program_info,
+            claim_amount,
+            now,
+        )?;
+    }
+    let fee = if drop_state.fee_prepaid {
+        0
+    } else {
+        config_state
+            .fee_schedule(drop_state.asset_type)
+            .fee_for_share(drop_state.amount, drop_state.claimed_amount, claim_amount)
+    };
+    let payout = claim_amount.saturating_sub(fee);
+    let partner_info = ctx
+        .accounts
+        .partner
+        .as_ref()
+        .map(|partner| partner.to_account_info());
+    let mut partner_state = load_referrer_partner(program_id, &drop_state, partner_info.as_ref())?;
+    let partner_share = partner_state
+        .as_ref()
+        .map_or(0, |partner| drop_fee(fee, partner.share_bps));
+    let treasury_fee = fee - partner_share;
+    // Token-2022 transfer fees are withheld at each destination; the vault has no spare
+    // balance to gross them up, so events report what the claimer and partner received.
+    let mut mint_fee = 0;
+    let mut partner_received = partner_share;
+
+    if drop_state.asset_type == ASSET_TYPE_TOKEN {
+        let (
+            mint_account,
+            token_vault,
+            claimer_token_account,
+            treasury_token_account,
+            token_program,
+            token_ledger,
+        ) = match (
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_vault.as_ref(),
+            ctx.accounts.claimer_token_account.as_ref(),
+            ctx.accounts.treasury_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            ctx.accounts.token_ledger.as_ref(),
+        ) {
+            (
+                Some(mint),
+                Some(vault),
+                Some(claimer),
+                Some(treasury),
+                Some(program),
+                Some(ledger),
+            ) => (mint, vault, claimer, treasury, program, ledger),
+            _ => return err!(DarkPoolError::MissingTokenAccounts),
+        };
+        require_keys_eq!(
+            mint_account.key(),
+            drop_state.mint,
+            DarkPoolError::InvalidMint
+        );
+        require_keys_eq!(
+            *mint_account.to_account_info().owner,
+            token_program.key(),
+            DarkPoolError::InvalidTokenProgram
+        );
+        let (token_vault_pda, token_vault_bump) = Pubkey::find_program_address(
+            &[b"token_vault", drop_state.mint.as_ref()],
+            program_id,
+        );
+        require_keys_eq!(
+            token_vault_pda,
+            token_vault.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            claimer_token_account.mint,
+            drop_state.mint,
+            DarkPoolError::InvalidMint
+        );
+        require_keys_eq!(
+            treasury_token_account.mint,
+            drop_state.mint,
+            DarkPoolError::InvalidMint
+        );
+        require_keys_eq!(
+            treasury_token_account.owner,
+            config_state.treasury,
+            DarkPoolError::InvalidTreasury
+        );
+        release_token_liability(
+            program_id,
+            &token_ledger.to_account_info(),
+            &drop_state.mint,
+            claim_amount,
+        )?;
+        mint_fee = mint_transfer_fee(&mint_account.to_account_info(), payout)?;
+        transfer_from_token_vault(
+            &token_vault.to_account_info(),
+            &claimer_token_account.to_account_info(),
+            mint_account,
+            &token_program.to_account_info(),
+            ctx.remaining_accounts,
+            token_vault_bump,
+            payout,
+        )?;
+        if treasury_fee > 0 {
+            transfer_from_token_vault(
+                &token_vault.to_account_info(),
+                &treasury_token_account.to_account_info(),
+                mint_account,
+                &token_program.to_account_info(),
+                ctx.remaining_accounts,
+                token_vault_bump,
+                treasury_fee,
+            )?;
+        }
+        if partner_share > 0 {
+            let partner_token_account = ctx
+                .accounts
+                .partner_token_account
+                .as_ref()
+                .ok_or(DarkPoolError::MissingPartnerAccount)?;
+            require_keys_eq!(
+                partner_token_account.mint,
+                drop_state.mint,
+                DarkPoolError::InvalidMint
+            );
+            require_keys_eq!(
+                partner_token_account.owner,
+                drop_state.referrer,
+                DarkPoolError::InvalidPartner
+            );
+            transfer_from_token_vault(
+                &token_vault.to_account_info(),
+                &partner_token_account.to_account_info(),
+                mint_account,
+                &token_program.to_account_info(),
+                ctx.remaining_accounts,
+                token_vault_bump,
+                partner_share,
+            )?;
+            partner_received = partner_share
+                - mint_transfer_fee(&mint_account.to_account_info(), partner_share)?;
+        }
+    } else {
+        require!(
+            sol_vault_info.lamports() >= claim_amount,
+            DarkPoolError::InsufficientVaultBalance
+        );
+        transfer_from_sol_vault(
+            &sol_vault_info,
+            &ctx.accounts.claimer.to_account_info(),
+            &system_program_info,
+            config_state.sol_vault_bump,
+            payout,
+        )?;
+        release_sol_liability(&mut ctx.accounts.config, claim_amount)?;
+        accrue_sol_fee(&mut ctx.accounts.config, treasury_fee)?;
+        record_sol_outflow(&mut ctx.accounts.config, payout, now)?;
+    }
+
+    if let (Some(partner_info), Some(partner_state)) =
+        (partner_info.as_ref(), partner_state.as_mut())
+    {
+        if partner_share > 0 {
+            record_partner_payment(
+                &mut ctx.accounts.config,
+                partner_info,
+                partner_state,
+                drop_state.asset_type,
+                partner_received,
+            )?;
+            emit!(PartnerPaid {
+                wallet: partner_state.wallet,
+                nullifier,
+                asset_type: drop_state.asset_type,
+                amount: partner_received,
+            });
+        }
+    }
+
+    let exhausted = if drop_state.max_claims > 0 || drop_state.is_vesting() {
+        if drop_state.max_claims > 0 {
+            drop_state.claimed_count = drop_state
+                .claimed_count
+                .checked_add(1)
+                .ok_or(DarkPoolError::NumericalOverflow)?;
+        }
+        drop_state.claimed_amount = drop_state
+            .claimed_amount
+            .checked_add(claim_amount)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        drop_state.remaining_amount() == 0
+            || (drop_state.max_claims > 0 && drop_state.claimed_count == drop_state.max_claims)
+    } else {
+        true
+    };
+    if exhausted {
+        mark_drop_claimed(
+            &drop_info,
+            &nullifier_info,
+            &mut drop_state,
+            &mut nullifier_state,
+            ctx.accounts.claimer.key(),
+            now,
+        )?;
+    } else {
+        let mut drop_data = drop_info.try_borrow_mut_data()?;
+        let mut drop_cursor: &mut [u8] = &mut drop_data;
+        drop_state.try_serialize(&mut drop_cursor)?;
+    }
+
+    emit!(DropClaimed {
+        nullifier,
+        claimer: ctx.accounts.claimer.key(),
+        claimed_at: now,
+        amount: claim_amount,
+        fee,
+        payout: payout - mint_fee,
+        mint_fee,
+    });
+
+    msg!(
+        "Drop claimed: nullifier={:?}, claimer={}",
+        nullifier,
+        ctx.accounts.claimer.key()
+    );
+    Ok(())
+}
+
+fn process_spend_shielded(
+    ctx: Context<SpendShielded>,
+    nullifier: [u8; 32],
+    amount: u64,
+    root: [u8; 32],
+) -> Result<()> {
+    let program_id = ctx.program_id;
+    let config = &ctx.accounts.shielded_config;
+    let tree = &ctx.accounts.shielded_tree;
+    let vault_info = ctx.accounts.shielded_vault.to_account_info();
+    let nullifier_info = ctx.accounts.nullifier_account.to_account_info();
+    let recipient_info = ctx.accounts.recipient.to_account_info();
+    let system_program_info = ctx.accounts.system_program.to_account_info();
+
+    require!(config.is_initialized, DarkPoolError::ShieldedConfigNotInitialized);
+    require!(amount > 0, DarkPoolError::InvalidAmount);
+    require!(root == config.current_root, DarkPoolError::ShieldedRootMismatch);
+    require!(root == tree.root, DarkPoolError::ShieldedStateMismatch);
+
+    if recipient_info.owner == &system_program::ID
+        && recipient_info.lamports() == 0
+        && recipient_info.data_len() == 0
+    {
+        let rent_min = Rent::get()?.minimum_balance(0);
+        require!(
+            amount >= rent_min,
+            DarkPoolError::ShieldedRecipientNotRentExempt
+        );
+    }
+
+    let vault_lamports = **vault_info.lamports.borrow();
+    require!(
+        vault_lamports >= amount,
+        DarkPoolError::InsufficientVaultBalance
+    );
+
+    let vault_seeds: &[&[u8]] = &[b"shielded_vault", &[config.vault_bump]];
+    let ix = anchor_lang::solana_program::system_instruction::transfer(
+        &vault_info.key(),
+        &recipient_info.key(),
+        amount,
+    );
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[vault_info, recipient_info, system_program_info.clone()],
+        &[vault_seeds],
+    )?;
+
+    let mut nullifier_state: ShieldedNullifier;
+    if nullifier_info.owner == program_id {
+        let mut data_slice: &[u8] = &nullifier_info.try_borrow_data()?;
+        nullifier_state = ShieldedNullifier::try_deserialize(&mut data_slice)?;
+    } else {
+        let space = (8 + ShieldedNullifier::LEN) as u64;
+        let rent_min = Rent::get()?.minimum_balance(space as usize);
+        msg!(
+            "shielded_nullifier pre-create key={} owner={} lamports={} data_len={} space={} rent_min={} payer={} payer_lamports={}",
+            nullifier_info.key(),
+            nullifier_info.owner,
+            nullifier_info.lamports(),
+            nullifier_info.data_len(),
+            space,
+            rent_min,
+            ctx.accounts.spender.key(),
+            ctx.accounts.spender.to_account_info().lamports()
+        );
+        require!(
+            nullifier_info.owner == &system_program::ID && nullifier_info.lamports() == 0,
+            DarkPoolError::AccountAlreadyInitialized
+        );
+        let lamports = rent_min + 1_000_000;
+        system_program::create_account(
+            CpiContext::new(
+                system_program_info.clone(),
+                system_program::CreateAccount {
+                    from: ctx.accounts.spender.to_account_info(),
+                    to: nullifier_info.clone(),
+                },
+            ),
+            lamports,
+            space,
+            program_id,
+        )?;
+        msg!(
+            "shielded_nullifier post-create key={} owner={} lamports={} data_len={} rent_min={} funded={}",
+            nullifier_info.key(),
+            nullifier_info.owner,
+            nullifier_info.lamports(),
+            nullifier_info.data_len(),
+            rent_min,
+            lamports
+        );
+        nullifier_state = ShieldedNullifier {
+            nullifier: [0u8; 32],
+            is_used: false,
+        };
+    }
+
+    require!(
+        !nullifier_state.is_used,
+        DarkPoolError::NullifierAlreadyUsed
+    );
+    nullifier_state.nullifier = nullifier;
+    nullifier_state.is_used = true;
+    let mut nullifier_data = nullifier_info.try_borrow_mut_data()?;
+    let pre_len = nullifier_data.len();
+    msg!(
+        "shielded_nullifier pre-write key={} lamports={} data_len={}",
+        nullifier_info.key(),
+        nullifier_info.lamports(),
+        pre_len
+    );
+    let mut nullifier_cursor: &mut [u8] = &mut nullifier_data;
+    nullifier_state.try_serialize(&mut nullifier_cursor)?;
+    let remaining = nullifier_cursor.len();
+    msg!(
+        "shielded_nullifier post-write key={} lamports={} data_len={} remaining={}",
+        nullifier_info.key(),
+        nullifier_info.lamports(),
+        pre_len,
+        remaining
+    );
+
+    emit!(ShieldedSpent {
+        nullifier,
+        recipient: ctx.accounts.recipient.key(),
+        amount,
+        root,
+    });
+
+    Ok(())
+}
+
+fn load_distributor(program_id: &Pubkey, distributor_info: &AccountInfo) -> Result<Distributor> {
+    require!(distributor_info.is_writable, ErrorCode::ConstraintMut);
+    require!(
+        distributor_info.owner == program_id,
+        ErrorCode::ConstraintOwner
+    );
+    let distributor_state = {
+        let distributor_data = distributor_info.try_borrow_data()?;
+        Distributor::try_deserialize(&mut &distributor_data[..])?
+    };
+    let (distributor_pda, distributor_bump) = Pubkey::find_program_address(
+        &[
+            b"distributor",
+            distributor_state.creator.as_ref(),
+            distributor_state.merkle_root.as_ref(),
+        ],
+        program_id,
+    );
+    require_keys_eq!(
+        distributor_pda,
+        *distributor_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require_eq!(
+        distributor_state.bump,
+        distributor_bump,
+        ErrorCode::ConstraintSeeds
+    );
+    Ok(distributor_state)
+}
+
+/// Leaf of a distributor tree. Leaves and nodes use distinct prefixes so an inner
+/// node can never be passed off as a leaf.
+fn distributor_leaf(index: u32, recipient: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[
+        &[0u8],
+        &index.to_le_bytes(),
+        recipient.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Verifies `proof` from `leaf` up to `root`, hashing each pair in sorted order.
+fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        hashv(&[&[1u8], &left, &right]).to_bytes()
+    });
+    computed == *root
+}
+
+/// Binds a secret to the claimer who will reveal it.
+fn claim_commitment_hash(secret: &[u8; 32], claimer: &Pubkey) -> [u8; 32] {
+    hashv(&[secret, claimer.as_ref()]).to_bytes()
+}
+
+/// Drops are keyed by `sha256(secret)`; the secret itself only appears on-chain at claim.
+fn drop_nullifier(secret: &[u8; 32]) -> [u8; 32] {
+    hashv(&[secret]).to_bytes()
+}
+
+/// Checks the claimer against the drop's `ClaimPolicy`.
+fn enforce_claim_policy(
+    drop_key: &Pubkey,
+    drop_state: &DropAccount,
+    claimer: &Pubkey,
+    instructions_sysvar: Option<&AccountInfo>,
+    committed: bool,
+) -> Result<()> {
+    // A secret revealed for a drop that also takes plain claims could be copied from the
+    // reveal and claimed first, so reveals are limited to `CommitReveal` drops.
+    if committed {
+        require!(
+            drop_state.claim_policy == ClaimPolicy::CommitReveal,
+            DarkPoolError::CommitRevealNotEnabled
+        );
+    }
+    match drop_state.claim_policy {
+        ClaimPolicy::Open => Ok(()),
+        ClaimPolicy::CommitReveal => {
+            require!(committed, DarkPoolError::CommitRevealRequired);
+            Ok(())
+        }
+        ClaimPolicy::RecipientSigner => {
+            require_keys_eq!(
+                *claimer,
+                drop_state.recipient,
+                DarkPoolError::RecipientMismatch
+            );
+            Ok(())
+        }
+        ClaimPolicy::RecipientSignature => {
+            let instructions_sysvar =
+                instructions_sysvar.ok_or(DarkPoolError::MissingRecipientSignature)?;
+            require_keys_eq!(
+                *instructions_sysvar.key,
+                sysvar_instructions::ID,
+                ErrorCode::ConstraintAddress
+            );
+            let current_index =
+                sysvar_instructions::load_current_index_checked(instructions_sysvar)?;
+            require!(current_index > 0, DarkPoolError::MissingRecipientSignature);
+            let ed25519_ix = sysvar_instructions::load_instruction_at_checked(
+                (current_index - 1) as usize,
+                instructions_sysvar,
+            )?;
+            require_keys_eq!(
+                ed25519_ix.program_id,
+                ed25519_program::ID,
+                DarkPoolError::MissingRecipientSignature
+            );
+            let message = [drop_key.as_ref(), claimer.as_ref()].concat();
+            require!(
+                ed25519_signature_matches(&ed25519_ix.data, &drop_state.recipient, &message),
+                DarkPoolError::MissingRecipientSignature
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Returns true if `ix_data` is a single-signature Ed25519 program instruction whose
+/// public key and message, both stored inline, equal `signer` and `message`.
+fn ed25519_signature_matches(ix_data: &[u8], signer: &Pubkey, message: &[u8]) -> bool {
+    const HEADER_LEN: usize = 16;
+    if ix_data.len() < HEADER_LEN || ix_data[0] != 1 {
+        return false;
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([ix_data[at], ix_data[at + 1]]);
+    // Offsets may point into other instructions; only data inside this one is trusted.
+    if read_u16(4) != u16::MAX || read_u16(8) != u16::MAX || read_u16(14) != u16::MAX {
+        return false;
+    }
+    let public_key_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_len = read_u16(12) as usize;
+    let signed_key = ix_data.get(public_key_offset..public_key_offset + 32);
+    let signed_message = ix_data.get(message_offset..message_offset + message_len);
+    signed_key == Some(signer.as_ref()) && signed_message == Some(message)
+}
+
+fn load_claimable_drop(
+    program_id: &Pubkey,
+    drop_info: &AccountInfo,
+    nullifier_info: &AccountInfo,
+    secret: &[u8; 32],
+    now: i64,
+) -> Result<(DropAccount, NullifierAccount)> {
+    // Drops are keyed by `sha256(secret)`. Migrated baseline drops were keyed by the secret
+    // itself; that fallback is only honoured for drops flagged `legacy_seed`.
+    let hashed = drop_nullifier(secret);
+    let (hashed_drop_pda, _) =
+        Pubkey::find_program_address(&[b"drop", hashed.as_ref()], program_id);
+    let legacy_seed = hashed_drop_pda != *drop_info.key;
+    let nullifier = if legacy_seed { *secret } else { hashed };
+    let (drop_pda, drop_bump) =
+        Pubkey::find_program_address(&[b"drop", nullifier.as_ref()], program_id);
+    let (nullifier_pda, nullifier_bump) =
+        Pubkey::find_program_address(&[b"nullifier", nullifier.as_ref()], program_id);
+
+    require_keys_eq!(drop_pda, *drop_info.key, ErrorCode::ConstraintSeeds);
+    require_keys_eq!(
+        nullifier_pda,
+        *nullifier_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require!(drop_info.is_writable, ErrorCode::ConstraintMut);
+    require!(nullifier_info.is_writable, ErrorCode::ConstraintMut);
+    require!(drop_info.owner == program_id, ErrorCode::ConstraintOwner);
+    require!(
+        nullifier_info.owner == program_id,
+        ErrorCode::ConstraintOwner
+    );
+
+    let mut drop_state = {
+        let drop_data = drop_info.try_borrow_data()?;
+        DropAccount::try_deserialize(&mut &drop_data[..])?
+    };
+    require_eq!(drop_state.bump, drop_bump, ErrorCode::ConstraintSeeds);
+    require!(
+        drop_state.legacy_seed == legacy_seed,
+        DarkPoolError::InvalidNullifier
+    );
+
+    let nullifier_state = {
+        let nullifier_data = nullifier_info.try_borrow_data()?;
+        NullifierAccount::try_deserialize(&mut &nullifier_data[..])?
+    };
+    require_eq!(
+        nullifier_state.bump,
+        nullifier_bump,
+        ErrorCode::ConstraintSeeds
+    );
+    require!(
+        nullifier_state.nullifier == nullifier,
+        DarkPoolError::InvalidNullifier
+    );
+
+    require!(
+        drop_state.status == DropStatus::Active,
+        DarkPoolError::DropNotActive
+    );
+
+    require!(
+        !nullifier_state.is_used,
+        DarkPoolError::NullifierAlreadyUsed
+    );
+
+    require!(
+        drop_state.nullifier == nullifier,
+        DarkPoolError::InvalidNullifier
+    );
+
+    if drop_state.expires_at != 0 && now > drop_state.expires_at {
+        drop_state.status = DropStatus::Expired;
+        let mut drop_data = drop_info.try_borrow_mut_data()?;
+        let mut drop_cursor: &mut [u8] = &mut drop_data;
+        drop_state.try_serialize(&mut drop_cursor)?;
+        return err!(DarkPoolError::DropExpired);
+    }
+
+    Ok((drop_state, nullifier_state))
+}
+
+/// Creates the `["claim", drop, claimer]` record, failing if this wallet already claimed.
+fn create_claim_record<'info>(
+    program_id: &Pubkey,
+    drop_info: &AccountInfo<'info>,
+    claim_record_info: &AccountInfo<'info>,
+    claimer_info: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    let (claim_record_pda, claim_record_bump) = Pubkey::find_program_address(
+        &[b"claim", drop_info.key.as_ref(), claimer_info.key.as_ref()],
+        program_id,
+    );
+    require_keys_eq!(
+        claim_record_pda,
+        *claim_record_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require!(claim_record_info.is_writable, ErrorCode::ConstraintMut);
+    require!(
+        claim_record_info.owner == &system_program::ID && claim_record_info.lamports() == 0,
+        DarkPoolError::AlreadyClaimedByWallet
+    );
+
+    let space = (8 + ClaimRecord::LEN) as u64;
+    let lamports = Rent::get()?.minimum_balance(space as usize);
+    let seeds: &[&[u8]] = &[
+        b"claim",
+        drop_info.key.as_ref(),
+        claimer_info.key.as_ref(),
+        &[claim_record_bump],
+    ];
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program_info.clone(),
+            system_program::CreateAccount {
+                from: claimer_info.clone(),
+                to: claim_record_info.clone(),
+            },
+            &[seeds],
+        ),
+        lamports,
+        space,
+        program_id,
+    )?;
+
+    let claim_record = ClaimRecord {
+        drop: *drop_info.key,
+        claimer: *claimer_info.key,
+        amount,
+        claimed_at: now,
+        bump: claim_record_bump,
+    };
+    let mut claim_record_data = claim_record_info.try_borrow_mut_data()?;
+    let mut claim_record_cursor: &mut [u8] = &mut claim_record_data;
+    claim_record.try_serialize(&mut claim_record_cursor)?;
+    Ok(())
+}
+
+fn mark_drop_claimed(
+    drop_info: &AccountInfo,
+    nullifier_info: &AccountInfo,
+    drop_state: &mut DropAccount,
+    nullifier_state: &mut NullifierAccount,
+    claimer: Pubkey,
+    now: i64,
+) -> Result<()> {
+    drop_state.status = DropStatus::Claimed;
+    drop_state.claimed_at = now;
+    drop_state.claimer = claimer;
+
+    nullifier_state.is_used = true;
+    nullifier_state.used_at = now;
+    nullifier_state.claimer = claimer;
+
+    let mut drop_data = drop_info.try_borrow_mut_data()?;
+    let mut drop_cursor: &mut [u8] = &mut drop_data;
+    drop_state.try_serialize(&mut drop_cursor)?;
+
+    let mut nullifier_data = nullifier_info.try_borrow_mut_data()?;
+    let mut nullifier_cursor: &mut [u8] = &mut nullifier_data;
+    nullifier_state.try_serialize(&mut nullifier_cursor)?;
+    Ok(())
+}
+
+fn drop_mint(asset_type: u8, mint: Option<Pubkey>) -> Result<Pubkey> {
+    match asset_type {
+        ASSET_TYPE_SOL => {
+            require!(mint.is_none(), DarkPoolError::InvalidMint);
+            Ok(Pubkey::default())
+        }
+        ASSET_TYPE_TOKEN => mint.ok_or(error!(DarkPoolError::MissingTokenAccounts)),
+        _ => err!(DarkPoolError::InvalidAssetType),
+    }
+}
+
+/// Validates the per-mint token vault PDA, creating and initializing it on first use.
+/// The vault is its own token authority, so payouts sign with `["token_vault", mint]`.
+/// The mint's `TokenLedger` is created alongside it.
+fn ensure_token_vault<'info>(
+    program_id: &Pubkey,
+    payer_info: &AccountInfo<'info>,
+    mint_info: &AccountInfo<'info>,
+    token_vault_info: &AccountInfo<'info>,
+    token_ledger_info: &AccountInfo<'info>,
+    token_program_info: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+) -> Result<u8> {
+    let (vault_pda, vault_bump) =
+        Pubkey::find_program_address(&[b"token_vault", mint_info.key.as_ref()], program_id);
+    require_keys_eq!(vault_pda, *token_vault_info.key, ErrorCode::ConstraintSeeds);
+    require!(token_vault_info.is_writable, ErrorCode::ConstraintMut);
+
+    if token_vault_info.owner == token_program_info.key {
+        let vault_state = {
+            let vault_data = token_vault_info.try_borrow_data()?;
+            TokenAccount::try_deserialize(&mut &vault_data[..])?
+        };
+        require_keys_eq!(vault_state.mint, *mint_info.key, DarkPoolError::InvalidMint);
+        require_keys_eq!(
+            vault_state.owner,
+            vault_pda,
+            DarkPoolError::InvalidTokenAccount
+        );
+        return Ok(vault_bump);
+    }
+
+    require!(
+        token_vault_info.owner == &system_program::ID && token_vault_info.lamports() == 0,
+        DarkPoolError::AccountAlreadyInitialized
+    );
+    let space = token_vault_space(mint_info)? as u64;
+    let lamports = Rent::get()?.minimum_balance(space as usize);
+    let seeds: &[&[u8]] = &[b"token_vault", mint_info.key.as_ref(), &[vault_bump]];
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program_info.clone(),
+            system_program::CreateAccount {
+                from: payer_info.clone(),
+                to: token_vault_info.clone(),
+            },
+            &[seeds],
+        ),
+        lamports,
+        space,
+        token_program_info.key,
+    )?;
+    token_interface::initialize_account3(CpiContext::new(
+        token_program_info.clone(),
+        token_interface::InitializeAccount3 {
+            account: token_vault_info.clone(),
+            mint: mint_info.clone(),
+            authority: token_vault_info.clone(),
+        },
+    ))?;
+
+    let (ledger_pda, ledger_bump) =
+        Pubkey::find_program_address(&[b"token_ledger", mint_info.key.as_ref()], program_id);
+    require_keys_eq!(
+        ledger_pda,
+        *token_ledger_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require!(token_ledger_info.is_writable, ErrorCode::ConstraintMut);
+    require!(
+        token_ledger_info.owner == &system_program::ID && token_ledger_info.lamports() == 0,
+        DarkPoolError::AccountAlreadyInitialized
+    );
+    let space = (8 + TokenLedger::LEN) as u64;
+    let lamports = Rent::get()?.minimum_balance(space as usize);
+    let seeds: &[&[u8]] = &[b"token_ledger", mint_info.key.as_ref(), &[ledger_bump]];
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program_info.clone(),
+            system_program::CreateAccount {
+                from: payer_info.clone(),
+                to: token_ledger_info.clone(),
+            },
+            &[seeds],
+        ),
+        lamports,
+        space,
+        program_id,
+    )?;
+    let ledger_state = TokenLedger {
+        mint: *mint_info.key,
+        outstanding_liabilities: 0,
+        bump: ledger_bump,
+    };
+    let mut ledger_data = token_ledger_info.try_borrow_mut_data()?;
+    let mut ledger_cursor: &mut [u8] = &mut ledger_data;
+    ledger_state.try_serialize(&mut ledger_cursor)?;
+    Ok(vault_bump)
+}
+
+fn load_token_ledger(
+    program_id: &Pubkey,
+    token_ledger_info: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<TokenLedger> {
+    let (ledger_pda, ledger_bump) =
+        Pubkey::find_program_address(&[b"token_ledger", mint.as_ref()], program_id);
+    require_keys_eq!(
+        ledger_pda,
+        *token_ledger_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require!(token_ledger_info.is_writable, ErrorCode::ConstraintMut);
+    require!(
+        token_ledger_info.owner == program_id,
+        ErrorCode::ConstraintOwner
+    );
+    let ledger_state = {
+        let ledger_data = token_ledger_info.try_borrow_data()?;
+        TokenLedger::try_deserialize(&mut &ledger_data[..])?
+    };
+    require_eq!(ledger_state.bump, ledger_bump, ErrorCode::ConstraintSeeds);
+    Ok(ledger_state)
+}
+
+fn store_token_ledger(token_ledger_info: &AccountInfo, ledger_state: &TokenLedger) -> Result<()> {
+    let mut ledger_data = token_ledger_info.try_borrow_mut_data()?;
+    let mut ledger_cursor: &mut [u8] = &mut ledger_data;
+    ledger_state.try_serialize(&mut ledger_cursor)?;
+    Ok(())
+}
+
+/// Records a new token obligation, rejecting it unless `vault_amount` covers every
+/// obligation of the mint.
+fn add_token_liability(
+    program_id: &Pubkey,
+    token_ledger_info: &AccountInfo,
+    mint: &Pubkey,
+    vault_amount: u64,
+    amount: u64,
+) -> Result<()> {
+    let mut ledger_state = load_token_ledger(program_id, token_ledger_info, mint)?;
+    ledger_state.outstanding_liabilities = ledger_state
+        .outstanding_liabilities
+        .checked_add(amount)
+        .ok_or(DarkPoolError::NumericalOverflow)?;
+    require!(
+        vault_amount >= ledger_state.outstanding_liabilities,
+        DarkPoolError::VaultUndercollateralized
+    );
+    store_token_ledger(token_ledger_info, &ledger_state)
+}
+
+/// Pays `amount` out of the mint's obligations, failing if the ledger does not hold it.
+fn release_token_liability(
+    program_id: &Pubkey,
+    token_ledger_info: &AccountInfo,
+    mint: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let mut ledger_state = load_token_ledger(program_id, token_ledger_info, mint)?;
+    ledger_state.outstanding_liabilities = ledger_state
+        .outstanding_liabilities
+        .checked_sub(amount)
+        .ok_or(DarkPoolError::InsufficientVaultBalance)?;
+    store_token_ledger(token_ledger_info, &ledger_state)
+}
+
+/// Reallocs `info` to `space` bytes, topping up rent from `payer`. New bytes are zeroed.
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    space: usize,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program_info.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.realloc(space, true)?;
+    Ok(())
+}
+
+/// Reads an account stored under an older, shorter layout. Every layout change so far has
+/// only appended fields, so the missing tail reads as zero, which is each new field's default.
+fn read_legacy_layout<T: AccountDeserialize>(data: &[u8], space: usize) -> Result<T> {
+    let mut padded = data.to_vec();
+    if padded.len() < space {
+        padded.resize(space, 0);
+    }
+    T::try_deserialize(&mut padded.as_slice())
+}
+
+/// Grows an account from one of `legacy_lens` to `8 + len`, fills defaults and lets
+/// `prepare` check the decoded state (e.g. its PDA seeds) and stamp the current version
+/// before anything is reallocated. Returns the previous data length.
+fn migrate_account_layout<'info, T: AccountSerialize + AccountDeserialize>(
+    program_id: &Pubkey,
+    accounts: &MigrateAccount<'info>,
+    len: usize,
+    legacy_lens: &[usize],
+    prepare: impl FnOnce(&mut T) -> Result<()>,
+) -> Result<usize> {
+    let info = accounts.account.to_account_info();
+    let system_program_info = accounts.system_program.to_account_info();
+    require_keys_eq!(*info.owner, *program_id, ErrorCode::ConstraintOwner);
+    require!(info.is_writable, ErrorCode::ConstraintMut);
+    require_keys_eq!(
+        system_program::ID,
+        *system_program_info.key,
+        ErrorCode::ConstraintAddress
+    );
+
+    let space = 8 + len;
+    let previous_len = info.data_len();
+    require!(previous_len != space, DarkPoolError::AccountAlreadyMigrated);
+    require!(
+        legacy_lens.iter().any(|legacy_len| 8 + legacy_len == previous_len),
+        DarkPoolError::UnknownAccountLayout
+    );
+
+    let mut state: T = read_legacy_layout(&info.try_borrow_data()?, space)?;
+    prepare(&mut state)?;
+    grow_account(
+        &info,
+        &accounts.payer.to_account_info(),
+        &system_program_info,
+        space,
+    )?;
+    let mut data = info.try_borrow_mut_data()?;
+    let mut cursor: &mut [u8] = &mut data;
+    state.try_serialize(&mut cursor)?;
+    Ok(previous_len)
+}
+
+/// Accounts needed to load, create or grow a payer's rate-limit account.
+struct RateLimitAccounts<'a, 'info> {
+    rate_limit: &'a AccountInfo<'info>,
+    payer: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Enforces the per-payer minimum gap and window quota, creating the rate-limit account on
+/// first use and growing accounts from the original layout in place.
+fn enforce_rate_limit(
+    program_id: &Pubkey,
+    accounts: RateLimitAccounts,
+    rate_limit_bump: u8,
+    policy: &PolicyValues,
+    limits: &QuotaLimits,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    let rate_limit_info = accounts.rate_limit;
+    let space = 8 + RateLimitAccount::LEN;
+    let mut rate_limit_state: RateLimitAccount;
+    if rate_limit_info.owner == program_id {
+        if rate_limit_info.data_len() < space {
+            grow_account(
+                rate_limit_info,
+                accounts.payer,
+                accounts.system_program,
+                space,
+            )?;
+        }
+        let mut data_slice: &[u8] = &rate_limit_info.try_borrow_data()?;
+        rate_limit_state = RateLimitAccount::try_deserialize(&mut data_slice)?;
+        require_eq!(
+            rate_limit_state.bump,
+            rate_limit_bump,
+            ErrorCode::ConstraintSeeds
+        );
+    } else {
+        require!(
+            rate_limit_info.owner == &system_program::ID && rate_limit_info.lamports() == 0,
+            DarkPoolError::AccountAlreadyInitialized
+        );
+        let lamports = Rent::get()?.minimum_balance(space);
+        let payer_key = accounts.payer.key();
+        let seeds: &[&[u8]] = &[b"rate_limit", payer_key.as_ref(), &[rate_limit_bump]];
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                accounts.system_program.clone(),
+                system_program::CreateAccount {
+                    from: accounts.payer.clone(),
+                    to: rate_limit_info.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space as u64,
+            program_id,
+        )?;
+        rate_limit_state = RateLimitAccount {
+            last_drop_at: 0,
+            bump: rate_limit_bump,
+            window_start: 0,
+            window_count: 0,
+            window_amount: 0,
+            prev_window_count: 0,
+            prev_window_amount: 0,
+        };
+    }
+
+    if rate_limit_state.last_drop_at != 0 {
+        require!(
+            now - rate_limit_state.last_drop_at >= policy.min_rate_limit_seconds,
+            DarkPoolError::RateLimitExceeded
+        );
+    }
+    rate_limit_state.record_drop(now, limits, amount)?;
+    rate_limit_state.last_drop_at = now;
+    rate_limit_state.bump = rate_limit_bump;
+    let mut rate_limit_data = rate_limit_info.try_borrow_mut_data()?;
+    let mut rate_limit_cursor: &mut [u8] = &mut rate_limit_data;
+    rate_limit_state.try_serialize(&mut rate_limit_cursor)?;
+    Ok(())
+}
+
+/// Optional accounts needed to return a drop's escrow to its funder.
+struct RefundAccounts<'a, 'info> {
+    funder: Option<&'a SystemAccount<'info>>,
+    mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    token_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    funder_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    token_program: Option<&'a Interface<'info, TokenInterface>>,
+    token_ledger: Option<&'a UncheckedAccount<'info>>,
+    additional_accounts: &'a [AccountInfo<'info>],
+}
+
+/// Moves the drop's remaining escrow from the SOL or token vault back to `drop_state.funder`.
+fn refund_drop_to_funder<'info>(
+    program_id: &Pubkey,
+    drop_state: &DropAccount,
+    config: &Config,
+    sol_vault_info: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    accounts: RefundAccounts<'_, 'info>,
+) -> Result<()> {
+    let (sol_vault_pda, _) = Pubkey::find_program_address(&[b"sol_vault"], program_id);
+    require_keys_eq!(sol_vault_pda, *sol_vault_info.key, ErrorCode::ConstraintSeeds);
+    let refund = drop_state.remaining_amount();
+    let funder = accounts.funder.ok_or(DarkPoolError::InvalidFunder)?;
+    require_keys_eq!(funder.key(), drop_state.funder, DarkPoolError::InvalidFunder);
+    require!(funder.to_account_info().is_writable, ErrorCode::ConstraintMut);
+
+    if drop_state.asset_type == ASSET_TYPE_TOKEN {
+        let (mint, token_vault, funder_token_account, token_program, token_ledger) = match (
+            accounts.mint,
+            accounts.token_vault,
+            accounts.funder_token_account,
+            accounts.token_program,
+            accounts.token_ledger,
+        ) {
+            (Some(mint), Some(vault), Some(destination), Some(program), Some(ledger)) => {
+                (mint, vault, destination, program, ledger)
+            }
+            _ => return err!(DarkPoolError::MissingTokenAccounts),
+        };
+        require_keys_eq!(mint.key(), drop_state.mint, DarkPoolError::InvalidMint);
+        require_keys_eq!(
+            *mint.to_account_info().owner,
+            token_program.key(),
+            DarkPoolError::InvalidTokenProgram
+        );
+        let (token_vault_pda, token_vault_bump) =
+            Pubkey::find_program_address(&[b"token_vault", drop_state.mint.as_ref()], program_id);
+        require_keys_eq!(
+            token_vault_pda,
+            token_vault.key(),
+            ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            funder_token_account.mint,
+            drop_state.mint,
+            DarkPoolError::InvalidMint
+        );
+        require_keys_eq!(
+            funder_token_account.owner,
+            drop_state.funder,
+            DarkPoolError::InvalidTokenAccount
+        );
+        release_token_liability(
+            program_id,
+            &token_ledger.to_account_info(),
+            &drop_state.mint,
+            refund,
+        )?;
+        transfer_from_token_vault(
+            &token_vault.to_account_info(),
+            &funder_token_account.to_account_info(),
+            mint,
+            &token_program.to_account_info(),
+            accounts.additional_accounts,
+            token_vault_bump,
+            refund,
+        )
+    } else {
+        require!(
+            sol_vault_info.lamports() >= refund,
+            DarkPoolError::InsufficientVaultBalance
+        );
+        transfer_from_sol_vault(
+            sol_vault_info,
+            &funder.to_account_info(),
+            system_program_info,
+            config.sol_vault_bump,
+            refund,
+        )
+    }
+}
+
+/// Transfers out of the token vault with `transfer_checked`, forwarding
+/// `additional_accounts` so Token-2022 transfer hooks can resolve their extra accounts.
+fn transfer_from_token_vault<'info>(
+    token_vault_info: &AccountInfo<'info>,
+    to_info: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program_info: &AccountInfo<'info>,
+    additional_accounts: &[AccountInfo<'info>],
+    token_vault_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let mint_key = mint.key();
+    let vault_seeds: &[&[u8]] = &[b"token_vault", mint_key.as_ref(), &[token_vault_bump]];
+    spl_token_2022::onchain::invoke_transfer_checked(
+        token_program_info.key,
+        token_vault_info.clone(),
+        mint.to_account_info(),
+        to_info.clone(),
+        token_vault_info.clone(),
+        additional_accounts,
+        amount,
+        mint.decimals,
+        &[vault_seeds],
+    )?;
+    Ok(())
+}
+
+/// Rejects Token-2022 mints carrying extensions outside `SUPPORTED_MINT_EXTENSIONS`,
+/// such as a permanent delegate that could drain the vault.
+fn validate_token_mint(mint_info: &AccountInfo) -> Result<()> {
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    for extension in mint.get_extension_types()? {
+        require!(
+            SUPPORTED_MINT_EXTENSIONS.contains(&extension),
+            DarkPoolError::UnsupportedMintExtension
+        );
+    }
+    Ok(())
+}
+
+fn token_vault_space(mint_info: &AccountInfo) -> Result<usize> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let account_extensions =
+        ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?);
+    Ok(ExtensionType::try_calculate_account_len::<
+        spl_token_2022::state::Account,
+    >(&account_extensions)?)
+}
+
+fn token_account_amount(token_account_info: &AccountInfo) -> Result<u64> {
+    let token_data = token_account_info.try_borrow_data()?;
+    let token_account = TokenAccount::try_deserialize(&mut &token_data[..])?;
+    Ok(token_account.amount)
+}
+
+/// Fee the mint's transfer-fee extension withholds on a transfer of `amount`.
+fn mint_transfer_fee(mint_info: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let fee = match mint.get_extension::<spl_token_2022::extension::transfer_fee::TransferFeeConfig>()
+    {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(DarkPoolError::NumericalOverflow)?,
+        Err(_) => 0,
+    };
+    Ok(fee)
+}
+
+fn optional_account_meta(key: Option<Pubkey>, is_writable: bool) -> AccountMeta {
+    match key {
+        Some(key) if is_writable => AccountMeta::new(key, false),
+        Some(key) => AccountMeta::new_readonly(key, false),
+        None => AccountMeta::new_readonly(crate::ID, false),
+    }
+}
+
+fn drop_fee(amount: u64, fee_bps: u16) -> u64 {
+    (amount as u128)
+        .checked_mul(fee_bps as u128)
+        .unwrap_or(0)
+        .checked_div(10_000)
+        .unwrap_or(0) as u64
+}
+
+/// Policy values in force: the stored ones, or the built-in defaults before the policy
+/// account has been created.
+fn load_policy(program_id: &Pubkey, policy_info: &AccountInfo) -> Result<PolicyValues> {
+    let (policy_pda, policy_bump) = Pubkey::find_program_address(&[b"policy"], program_id);
+    require_keys_eq!(policy_pda, *policy_info.key, ErrorCode::ConstraintSeeds);
+    if policy_info.owner != program_id {
+        return Ok(PolicyValues::default());
+    }
+    let policy_state = {
+        let policy_data = policy_info.try_borrow_data()?;
+        PolicyParams::try_deserialize(&mut &policy_data[..])?
+    };
+    require_eq!(policy_state.bump, policy_bump, ErrorCode::ConstraintSeeds);
+    Ok(policy_state.values)
+}
+
+/// Creates the policy PDA holding the built-in defaults; `payer` covers the rent.
+fn create_policy_account<'info>(
+    program_id: &Pubkey,
+    policy_info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    policy_bump: u8,
+) -> Result<PolicyParams> {
+    require!(
+        policy_info.owner == &system_program::ID && policy_info.lamports() == 0,
+        DarkPoolError::AccountAlreadyInitialized
+    );
+    let space = (8 + PolicyParams::LEN) as u64;
+    let lamports = Rent::get()?.minimum_balance(space as usize);
+    let seeds: &[&[u8]] = &[b"policy", &[policy_bump]];
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program_info.clone(),
+            system_program::CreateAccount {
+                from: payer.clone(),
+                to: policy_info.clone(),
+            },
+            &[seeds],
+        ),
+        lamports,
+        space,
+        program_id,
+    )?;
+    let policy_state = PolicyParams {
+        values: PolicyValues::default(),
+        pending: PolicyValues::default(),
+        pending_effective_at: 0,
+        bump: policy_bump,
+    };
+    let mut policy_data = policy_info.try_borrow_mut_data()?;
+    let mut policy_cursor: &mut [u8] = &mut policy_data;
+    policy_state.try_serialize(&mut policy_cursor)?;
+    Ok(policy_state)
+}
+
+/// Fails if `fee_bps` or either fee schedule exceeds `max_fee_bps`, so a policy update
+/// cannot leave fees above its own cap.
+fn require_fees_within_cap(config: &Config, max_fee_bps: u16) -> Result<()> {
+    require!(config.fee_bps <= max_fee_bps, DarkPoolError::InvalidFeeBps);
+    config.sol_fee_schedule.validate(max_fee_bps)?;
+    config.token_fee_schedule.validate(max_fee_bps)
+}
+
+/// Checks an admin action against the policy in force.
+fn validate_admin_action(action: &AdminAction, policy: &PolicyValues) -> Result<()> {
+    match action {
+        AdminAction::SetFee { fee_bps } => {
+            require!(*fee_bps <= policy.max_fee_bps, DarkPoolError::InvalidFeeBps)
+        }
+        AdminAction::SetTreasury { treasury } => {
+            require!(
+                *treasury != Pubkey::default(),
+                DarkPoolError::InvalidTreasury
+            )
+        }
+        AdminAction::SetPolicy { values } => values.validate()?,
+        AdminAction::RotateKey { new_authority } => {
+            require!(
+                *new_authority != Pubkey::default(),
+                DarkPoolError::InvalidAuthority
+            )
+        }
+        AdminAction::Unpause { flags } => {
+            require!(
+                *flags != 0 && *flags & !PAUSE_ALL == 0,
+                DarkPoolError::InvalidPauseFlags
+            )
+        }
+    }
+    Ok(())
+}
+
+fn load_admin_action(
+    program_id: &Pubkey,
+    admin_action_info: &AccountInfo,
+) -> Result<QueuedAdminAction> {
+    require!(admin_action_info.is_writable, ErrorCode::ConstraintMut);
+    require!(
+        admin_action_info.owner == program_id,
+        ErrorCode::ConstraintOwner
+    );
+    let admin_action_state = {
+        let admin_action_data = admin_action_info.try_borrow_data()?;
+        QueuedAdminAction::try_deserialize(&mut &admin_action_data[..])?
+    };
+    let (admin_action_pda, admin_action_bump) = Pubkey::find_program_address(
+        &[b"admin_action", &admin_action_state.index.to_le_bytes()],
+        program_id,
+    );
+    require_keys_eq!(
+        admin_action_pda,
+        *admin_action_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require_eq!(
+        admin_action_state.bump,
+        admin_action_bump,
+        ErrorCode::ConstraintSeeds
+    );
+    Ok(admin_action_state)
+}
+
+/// Closes a queued action, returning its rent to whoever queued it.
+fn close_admin_action(
+    admin_action_info: &AccountInfo,
+    payer_info: &AccountInfo,
+    admin_action_state: &QueuedAdminAction,
+) -> Result<()> {
+    require_keys_eq!(
+        *payer_info.key,
+        admin_action_state.payer,
+        DarkPoolError::InvalidAdminActionPayer
+    );
+    require!(payer_info.is_writable, ErrorCode::ConstraintMut);
+    {
+        let mut payer_lamports = payer_info.try_borrow_mut_lamports()?;
+        let mut admin_action_lamports = admin_action_info.try_borrow_mut_lamports()?;
+        let new_payer_lamports = payer_lamports
+            .checked_add(**admin_action_lamports)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        **payer_lamports = new_payer_lamports;
+        **admin_action_lamports = 0;
+    }
+    admin_action_info.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
+
+fn require_not_paused(config: &Config, flag: u8) -> Result<()> {
+    require!(config.paused & flag == 0, DarkPoolError::ProgramPaused);
+    Ok(())
+}
+
+fn require_outflow_open(config: &Config) -> Result<()> {
+    require!(!config.outflow_tripped, DarkPoolError::OutflowLimitTripped);
+    Ok(())
+}
+
+/// Counts SOL leaving `sol_vault` against the current outflow window. The outflow that
+/// crosses the limit still completes; it trips the breaker so later claims fail.
+fn record_sol_outflow(config: &mut Config, amount: u64, now: i64) -> Result<()> {
+    if config.outflow_limit == 0 || amount == 0 {
+        return Ok(());
+    }
+    if now.saturating_sub(config.outflow_window_start) >= config.outflow_window_seconds {
+        config.outflow_window_start = now;
+        config.outflow_in_window = 0;
+    }
+    config.outflow_in_window = config
+        .outflow_in_window
+        .checked_add(amount)
+        .ok_or(DarkPoolError::NumericalOverflow)?;
+    if config.outflow_in_window > config.outflow_limit && !config.outflow_tripped {
+        config.outflow_tripped = true;
+        emit!(OutflowLimitTripped {
+            window_start: config.outflow_window_start,
+            outflow: config.outflow_in_window,
+            limit: config.outflow_limit,
+            tripped_at: now,
+        });
+        msg!(
+            "Outflow limit tripped: outflow={}, limit={}",
+            config.outflow_in_window,
+            config.outflow_limit
+        );
+    }
+    Ok(())
+}
+
+fn validate_multisig_members(members: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !members.is_empty() && members.len() <= MAX_MULTISIG_MEMBERS,
+        DarkPoolError::InvalidMultisigMembers
+    );
+    for (i, member) in members.iter().enumerate() {
+        require!(
+            *member != Pubkey::default() && !members[..i].contains(member),
+            DarkPoolError::InvalidMultisigMembers
+        );
+    }
+    require!(
+        threshold > 0 && threshold as usize <= members.len(),
+        DarkPoolError::InvalidMultisigThreshold
+    );
+    Ok(())
+}
+
+/// Loads a pending multisig transaction, checking its PDA, multisig and member-set version.
+fn load_multisig_transaction(
+    program_id: &Pubkey,
+    multisig: &Account<Multisig>,
+    transaction_info: &AccountInfo,
+) -> Result<MultisigTransaction> {
+    let transaction_state = read_multisig_transaction(program_id, multisig, transaction_info)?;
+    require!(
+        transaction_state.members_version == multisig.members_version,
+        DarkPoolError::MultisigMembersChanged
+    );
+    Ok(transaction_state)
+}
+
+/// Loads an unexecuted transaction of `multisig`, whatever member set it was proposed under.
+fn read_multisig_transaction(
+    program_id: &Pubkey,
+    multisig: &Account<Multisig>,
+    transaction_info: &AccountInfo,
+) -> Result<MultisigTransaction> {
+    require!(transaction_info.is_writable, ErrorCode::ConstraintMut);
+    require!(
+        transaction_info.owner == program_id,
+        ErrorCode::ConstraintOwner
+    );
+    let transaction_state = {
+        let transaction_data = transaction_info.try_borrow_data()?;
+        MultisigTransaction::try_deserialize(&mut &transaction_data[..])?
+    };
+    let (transaction_pda, transaction_bump) = Pubkey::find_program_address(
+        &[
+            b"multisig_tx",
+            multisig.key().as_ref(),
+            &transaction_state.index.to_le_bytes(),
+        ],
+        program_id,
+    );
+    require_keys_eq!(
+        transaction_pda,
+        *transaction_info.key,
+        ErrorCode::ConstraintSeeds
+    );
+    require_eq!(
+        transaction_state.bump,
+        transaction_bump,
+        ErrorCode::ConstraintSeeds
+    );
+    require!(
+        !transaction_state.executed,
+        DarkPoolError::MultisigTransactionExecuted
+    );
+    Ok(transaction_state)
+}
+
+/// Moves a multisig transaction's rent to its proposer and clears its data.
+fn close_multisig_transaction_account(
+    transaction_info: &AccountInfo,
+    proposer_info: &AccountInfo,
+    transaction_state: &MultisigTransaction,
+) -> Result<()> {
+    require_keys_eq!(
+        *proposer_info.key,
+        transaction_state.proposer,
+        DarkPoolError::InvalidMultisigProposer
+    );
+    require!(proposer_info.is_writable, ErrorCode::ConstraintMut);
+    {
+        let mut proposer_lamports = proposer_info.try_borrow_mut_lamports()?;
+        let mut transaction_lamports = transaction_info.try_borrow_mut_lamports()?;
+        let new_proposer_lamports = proposer_lamports
+            .checked_add(**transaction_lamports)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        **proposer_lamports = new_proposer_lamports;
+        **transaction_lamports = 0;
+    }
+    transaction_info.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
+
+/// Loads a creator role and checks it sits at `["creator_role", creator]`.
+fn load_creator_role(program_id: &Pubkey, role_info: &AccountInfo) -> Result<CreatorRole> {
+    require!(role_info.owner == program_id, ErrorCode::ConstraintOwner);
+    let role_state = {
+        let role_data = role_info.try_borrow_data()?;
+        CreatorRole::try_deserialize(&mut &role_data[..])?
+    };
+    let (role_pda, role_bump) = Pubkey::find_program_address(
+        &[b"creator_role", role_state.creator.as_ref()],
+        program_id,
+    );
+    require_keys_eq!(role_pda, *role_info.key, ErrorCode::ConstraintSeeds);
+    require_eq!(role_state.bump, role_bump, ErrorCode::ConstraintSeeds);
+    Ok(role_state)
+}
+
+/// Loads a partner account and checks it sits at `["partner", wallet]`.
+fn load_partner(program_id: &Pubkey, partner_info: &AccountInfo) -> Result<Partner> {
+    require!(partner_info.owner == program_id, ErrorCode::ConstraintOwner);
+    let partner_state = {
+        let partner_data = partner_info.try_borrow_data()?;
+        Partner::try_deserialize(&mut &partner_data[..])?
+    };
+    let (partner_pda, partner_bump) =
+        Pubkey::find_program_address(&[b"partner", partner_state.wallet.as_ref()], program_id);
+    require_keys_eq!(partner_pda, *partner_info.key, ErrorCode::ConstraintSeeds);
+    require_eq!(partner_state.bump, partner_bump, ErrorCode::ConstraintSeeds);
+    Ok(partner_state)
+}
+
+/// Referrer recorded on a new drop: the wallet of the supplied partner, if any.
+fn drop_referrer(program_id: &Pubkey, partner_info: Option<&AccountInfo>) -> Result<Pubkey> {
+    match partner_info {
+        Some(partner_info) => Ok(load_partner(program_id, partner_info)?.wallet),
+        None => Ok(Pubkey::default()),
+    }
+}
+
+/// Partner credited for a claim on a referred drop. Returns `None` when the drop has no
+/// referrer or its partner has been removed, in which case the treasury keeps the whole fee.
+fn load_referrer_partner(
+    program_id: &Pubkey,
+    drop_state: &DropAccount,
+    partner_info: Option<&AccountInfo>,
+) -> Result<Option<Partner>> {
+    if drop_state.referrer == Pubkey::default() {
+        return Ok(None);
+    }
+    let partner_info = partner_info.ok_or(DarkPoolError::MissingPartnerAccount)?;
+    let (partner_pda, _) =
+        Pubkey::find_program_address(&[b"partner", drop_state.referrer.as_ref()], program_id);
+    require_keys_eq!(partner_pda, *partner_info.key, ErrorCode::ConstraintSeeds);
+    if partner_info.owner != program_id {
+        return Ok(None);
+    }
+    require!(partner_info.is_writable, ErrorCode::ConstraintMut);
+    load_partner(program_id, partner_info).map(Some)
+}
+
+/// Credits a partner's fee share. SOL shares stay in `sol_vault` and are counted as owed
+/// until the partner withdraws them; token shares have already been transferred.
+fn record_partner_payment(
+    config: &mut Config,
+    partner_info: &AccountInfo,
+    partner_state: &mut Partner,
+    asset_type: u8,
+    share: u64,
+) -> Result<()> {
+    if asset_type == ASSET_TYPE_TOKEN {
+        partner_state.token_earned = partner_state
+            .token_earned
+            .checked_add(share)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+    } else {
+        partner_state.sol_earned = partner_state
+            .sol_earned
+            .checked_add(share)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        partner_state.sol_accrued = partner_state
+            .sol_accrued
+            .checked_add(share)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+        config.partner_accrued_fees = config
+            .partner_accrued_fees
+            .checked_add(share)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
+    }
+    let mut partner_data = partner_info.try_borrow_mut_data()?;
+    let mut partner_cursor: &mut [u8] = &mut partner_data;
+    partner_state.try_serialize(&mut partner_cursor)?;
+    Ok(())
+}
+
+/// SOL the vault can pay out, excluding its own rent-exempt reserve.
+fn sol_vault_available(sol_vault_info: &AccountInfo) -> Result<u64> {
+    Ok(sol_vault_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0)))
+}
+
+/// Records a new SOL obligation, rejecting it unless the vault covers every obligation
+/// (drop liabilities, fees not yet swept and partner shares not yet withdrawn).
+fn add_sol_liability(config: &mut Config, sol_vault_info: &AccountInfo, amount: u64) -> Result<()> {
+    config.outstanding_liabilities = config
+        .outstanding_liabilities
+        .checked_add(amount)
+        .ok_or(DarkPoolError::NumericalOverflow)?;
+    let obligations = config
+        .outstanding_liabilities
+        .checked_add(config.accrued_fees)
+        .and_then(|total| total.checked_add(config.partner_accrued_fees))
+        .ok_or(DarkPoolError::NumericalOverflow)?;
+    require!(
+        sol_vault_available(sol_vault_info)? >= obligations,
+        DarkPoolError::VaultUndercollateralized
+    );
+    Ok(())
+}
+
+fn release_sol_liability(config: &mut Config, amount: u64) -> Result<()> {
+    config.outstanding_liabilities = config
+        .outstanding_liabilities
+        .checked_sub(amount)
+        .ok_or(DarkPoolError::InsufficientVaultBalance)?;
+    Ok(())
+}
+
+/// Leaves a claim fee in the vault until `sweep_fees` moves it to the treasury.
+fn accrue_sol_fee(config: &mut Config, fee: u64) -> Result<()> {
+    config.accrued_fees = config
+        .accrued_fees
+        .checked_add(fee)
+        .ok_or(DarkPoolError::NumericalOverflow)?;
+    Ok(())
+}
+
+fn transfer_from_sol_vault<'info>(
+    sol_vault_info: &AccountInfo<'info>,
+    to_info: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    sol_vault_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let vault_seeds: &[&[u8]] = &[b"sol_vault", &[sol_vault_bump]];
+    let ix = anchor_lang::solana_program::system_instruction::transfer(
+        sol_vault_info.key,
+        to_info.key,
+        amount,
+    );
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[
+            sol_vault_info.clone(),
+            to_info.clone(),
+            system_program_info.clone(),
+        ],
+        &[vault_seeds],
+    )?;
+    Ok(())
+}
+
+impl Config {
+    pub const LEN: usize = 32 + 1 + 32 + 8 + 8 + 32 + 2 + 1 + 8 + 8 + 2 + 8 + 32 + 8
+        + FeeSchedule::LEN * 3
+        + 1
+        + 8
+        + 1
+        + 32
+        + 8
+        + 8
+        + 8
+        + 8
+        + 1
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8;
+    pub const VERSION: u8 = 1;
+    /// Data lengths of every deployed earlier layout, oldest first.
+    pub const LEGACY_LENS: [usize; 1] = [116];
+
+    /// Acceptance window in force; configs migrated from older layouts use the default.
+    pub fn acceptance_window(&self) -> i64 {
+        if self.authority_acceptance_window_seconds > 0 {
+            self.authority_acceptance_window_seconds
+        } else {
+            DEFAULT_ACCEPTANCE_WINDOW_SECONDS
+        }
+    }
+
+    /// Time after which the pending authority can no longer accept; proposals made before
+    /// the expiry was stored fall back to the delay and window in force.
+    pub fn authority_proposal_expires_at(&self) -> i64 {
+        if self.pending_authority_expires_at > 0 {
+            self.pending_authority_expires_at
+        } else {
+            self.proposal_expiry_from(self.pending_authority_set_at)
+        }
+    }
+
+    /// Expiry for a proposal made at `set_at` under the current delay and window.
+    pub fn proposal_expiry_from(&self, set_at: i64) -> i64 {
+        set_at
+            .saturating_add(self.authority_delay_seconds)
+            .saturating_add(self.acceptance_window())
+    }
+
+    /// Schedule in force for `asset_type`.
+    pub fn fee_schedule(&self, asset_type: u8) -> FeeSchedule {
+        let schedule = if asset_type == ASSET_TYPE_TOKEN {
+            self.token_fee_schedule
+        } else {
+            self.sol_fee_schedule
+        };
+        if schedule.enabled {
+            schedule
+        } else {
+            FeeSchedule {
+                bps: self.fee_bps,
+                ..FeeSchedule::default()
+            }
+        }
+    }
+}
+
+/// Fee charged per drop: `flat + amount * bps / 10_000`, clamped to `[min, max]`
+/// (`max == 0` means uncapped) and never more than the amount itself.
+#[derive(AnchorSerialize, AnchorDeserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FeeSchedule {
+    pub flat: u64,
+    pub bps: u16,
+    pub min: u64,
+    pub max: u64,
+    /// Charge the payer on deposit instead of taking the fee from the claim payout.
+    pub charge_on_create: bool,
+    pub enabled: bool,
+}
+
+impl FeeSchedule {
+    pub const LEN: usize = 8 + 2 + 8 + 8 + 1 + 1;
+
+    pub fn validate(&self, max_fee_bps: u16) -> Result<()> {
+        require!(self.bps <= max_fee_bps, DarkPoolError::InvalidFeeBps);
+        require!(
+            self.max == 0 || self.min <= self.max,
+            DarkPoolError::InvalidFeeSchedule
+        );
+        Ok(())
+    }
+
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        let mut fee = self
+            .flat
+            .saturating_add(drop_fee(amount, self.bps))
+            .max(self.min);
+        if self.max > 0 {
+            fee = fee.min(self.max);
+        }
+        fee.min(amount)
+    }
+
+    /// Fee for a `share` of a `total` drop of which `claimed` is already paid out. The
+    /// whole-drop fee is prorated by cumulative amount, so flat and min parts are charged
+    /// once per drop and the shares' fees always sum to `fee_for(total)`.
+    pub fn fee_for_share(&self, total: u64, claimed: u64, share: u64) -> u64 {
+        if total == 0 {
+            return 0;
+        }
+        let full = self.fee_for(total) as u128;
+        let owed = |paid: u64| full * paid.min(total) as u128 / total as u128;
+        (owed(claimed.saturating_add(share)) - owed(claimed)) as u64
+    }
+}
+
+#[account]
+pub struct DropAccount {
+    pub nullifier: [u8; 32],
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub asset_type: u8,
+    pub status: DropStatus,
+    pub expires_at: i64,
+    pub created_at: i64,
+    pub claimed_at: i64,
+    pub claimer: Pubkey,
+    pub bump: u8,
+    /// SPL mint for `asset_type` 1 drops; `Pubkey::default()` for SOL drops.
+    pub mint: Pubkey,
+    /// Creator of the drop: the `deposit_pool` depositor or the `create_drop` payer. It may
+    /// cancel the drop and gets back the drop's rent.
+    pub funder: Pubkey,
+    pub claim_policy: ClaimPolicy,
+    /// Number of wallets that may claim; 0 for single-claim drops.
+    pub max_claims: u32,
+    pub claimed_count: u32,
+    /// Fixed share per multi-claim; 0 for single-claim and vesting drops.
+    pub per_claim_amount: u64,
+    /// Total paid out so far for multi-claim and vesting drops.
+    pub claimed_amount: u64,
+    pub cliff_at: i64,
+    pub vest_start: i64,
+    /// End of the linear unlock; 0 for drops that do not vest.
+    pub vest_end: i64,
+    /// The fee was charged to the funder on deposit, so claims pay no further fee.
+    pub fee_prepaid: bool,
+    /// Partner wallet credited with a share of the claim fee; default when unreferred.
+    pub referrer: Pubkey,
+    /// Layout version; 0 for accounts written before versioning, see `migrate_drop`.
+    pub version: u8,
+    /// `funder` deposited `amount` and is refunded the remainder on expiry or cancellation.
+    /// False for `create_drop` drops, which draw on vault surplus and refund nothing.
+    pub escrowed: bool,
+    /// Baseline drop keyed by the raw secret rather than `sha256(secret)`; set by `migrate_drop`.
+    pub legacy_seed: bool,
+}
+
+#[account]
+pub struct Distributor {
+    pub creator: Pubkey,
+    /// Root over `distributor_leaf(index, recipient, amount)` leaves.
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub num_recipients: u32,
+    pub num_claimed: u32,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+    /// One bit per leaf index, set once that leaf is claimed.
+    pub claimed_bitmap: Vec<u8>,
+}
+
+impl Distributor {
+    pub const BASE_LEN: usize = 32 + 32 + 8 + 8 + 4 + 4 + 8 + 8 + 1 + 4;
+
+    pub fn bitmap_len(num_recipients: u32) -> usize {
+        (num_recipients as usize).div_ceil(8)
+    }
+
+    pub fn space(num_recipients: u32) -> usize {
+        8 + Self::BASE_LEN + Self::bitmap_len(num_recipients)
+    }
+
+    pub fn is_claimed(&self, index: u32) -> bool {
+        self.claimed_bitmap[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+
+    pub fn set_claimed(&mut self, index: u32) {
+        self.claimed_bitmap[(index / 8) as usize] |= 1 << (index % 8);
+    }
+}
+
+/// Per-wallet marker for multi-claim drops, seeded `["claim", drop, claimer]`.
+#[account]
+pub struct ClaimRecord {
+    pub drop: Pubkey,
+    pub claimer: Pubkey,
+    pub amount: u64,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+impl ClaimRecord {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1;
+}
+
+/// What the `["token_vault", mint]` vault owes across every live drop of one mint,
+/// seeded `["token_ledger", mint]`. The vault is shared, so claims and refunds check this
+/// rather than the vault's raw balance.
+#[account]
+pub struct TokenLedger {
+    pub mint: Pubkey,
+    pub outstanding_liabilities: u64,
+    pub bump: u8,
+}
+
+impl TokenLedger {
+    pub const LEN: usize = 32 + 8 + 1;
+}
+
+/// Runtime-tunable limits. `Default` returns the built-in values.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PolicyValues {
+    pub min_expiration_window: i64,
+    pub max_expiration_window: i64,
+    pub min_rate_limit_seconds: i64,
+    pub max_fee_bps: u16,
+    /// Window quota for payers without a role-specific one.
+    pub default_quota: QuotaLimits,
+}
+
+impl Default for PolicyValues {
+    fn default() -> Self {
+        Self {
+            min_expiration_window: MIN_EXPIRATION_WINDOW,
+            max_expiration_window: MAX_EXPIRATION_WINDOW,
+            min_rate_limit_seconds: MIN_RATE_LIMIT_SECONDS,
+            max_fee_bps: MAX_FEE_BPS,
+            default_quota: QuotaLimits::default(),
+        }
+    }
+}
+
+impl PolicyValues {
+    pub const LEN: usize = 8 + 8 + 8 + 2 + QuotaLimits::LEN;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.min_expiration_window > 0
+                && self.min_expiration_window <= self.max_expiration_window
+                && self.max_expiration_window <= POLICY_MAX_EXPIRATION_WINDOW,
+            DarkPoolError::InvalidPolicy
+        );
+        require!(
+            (0..=POLICY_MAX_RATE_LIMIT_SECONDS).contains(&self.min_rate_limit_seconds),
+            DarkPoolError::InvalidPolicy
+        );
+        require!(
+            self.max_fee_bps <= POLICY_MAX_FEE_BPS,
+            DarkPoolError::InvalidPolicy
+        );
+        self.default_quota.validate()
+    }
+}
+
+#[account]
+pub struct PolicyParams {
+    pub values: PolicyValues,
+    pub pending: PolicyValues,
+    pub pending_effective_at: i64,
+    pub bump: u8,
+}
+
+impl PolicyParams {
+    pub const LEN: usize = PolicyValues::LEN * 2 + 8 + 1;
+}
+
+#[account]
+pub struct Multisig {
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    /// Bumped on every member change; transactions from an older member set are void.
+    pub members_version: u64,
+    pub transaction_count: u64,
+    /// Bump of `["multisig_signer", multisig]`, the key that holds the Config authority.
+    pub signer_bump: u8,
+    pub bump: u8,
+}
+
+impl Multisig {
+    pub const LEN: usize = 4 + 32 * MAX_MULTISIG_MEMBERS + 1 + 8 + 8 + 1 + 1;
+
+    pub fn member_index(&self, key: &Pubkey) -> Option<usize> {
+        self.members.iter().position(|member| member == key)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MultisigAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[account]
+pub struct MultisigTransaction {
+    pub multisig: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub accounts: Vec<MultisigAccountMeta>,
+    pub data: Vec<u8>,
+    /// Bit `i` is set once `members[i]` has approved.
+    pub approvals: u16,
+    pub members_version: u64,
+    pub executed: bool,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl MultisigTransaction {
+    pub fn space(num_accounts: usize, data_len: usize) -> usize {
+        32 + 8 + 32 + 4 + num_accounts * (32 + 1 + 1) + 4 + data_len + 2 + 8 + 1 + 8 + 1
+    }
+}
+
+/// A change the authority can queue through `enqueue_admin_action`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AdminAction {
+    SetFee { fee_bps: u16 },
+    SetTreasury { treasury: Pubkey },
+    SetPolicy { values: PolicyValues },
+    /// Makes `new_authority` the pending authority, cancelling any pending proposal; it still
+    /// has to call `accept_authority` within the acceptance window.
+    RotateKey { new_authority: Pubkey },
+    Unpause { flags: u8 },
+}
+
+impl AdminAction {
+    /// Borsh size of the largest variant.
+    pub const MAX_LEN: usize = 1 + PolicyValues::LEN;
+}
+
+#[account]
+pub struct QueuedAdminAction {
+    pub index: u64,
+    pub action: AdminAction,
+    /// Account that paid the rent; receives it back when the action is closed.
+    pub payer: Pubkey,
+    pub queued_at: i64,
+    /// Earliest time `execute_admin_action` may run it.
+    pub eta: i64,
+    pub bump: u8,
+    /// Authority that queued it; the action lapses if the authority changes.
+    pub authority: Pubkey,
+    /// Time after which it can no longer be executed, only cancelled.
+    pub expires_at: i64,
+}
+
+impl QueuedAdminAction {
+    pub const LEN: usize = 8 + AdminAction::MAX_LEN + 32 + 8 + 8 + 1 + 32 + 8;
+}
+
+#[account]
+pub struct CreatorRole {
+    pub creator: Pubkey,
+    pub enabled: bool,
+    /// Maximum drops this creator may create; 0 means unlimited, allowed only with a window
+    /// `max_amount` in `limits`.
+    pub quota: u64,
+    pub drops_created: u64,
+    pub granted_at: i64,
+    pub bump: u8,
+    /// Role-specific window quota; unset falls back to the policy default.
+    pub limits: QuotaLimits,
+}
+
+impl CreatorRole {
+    pub const LEN: usize = 32 + 1 + 8 + 8 + 8 + 1 + QuotaLimits::LEN;
+
+    /// Role drops are backed by vault surplus rather than escrow, so every role must cap
+    /// either its drop count or the lamports it can commit per window.
+    pub fn is_bounded(&self) -> bool {
+        self.quota > 0 || (self.limits.is_set() && self.limits.max_amount > 0)
+    }
+}
+
+#[account]
+pub struct Partner {
+    pub wallet: Pubkey,
+    /// Share of each referred claim fee, in bps of the fee.
+    pub share_bps: u16,
+    /// Lifetime SOL earnings in lamports.
+    pub sol_earned: u64,
+    /// SOL earnings still held in `sol_vault`, paid out by `withdraw_partner_earnings`.
+    pub sol_accrued: u64,
+    /// Lifetime token earnings in raw units, summed across mints.
+    pub token_earned: u64,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl Partner {
+    pub const LEN: usize = 32 + 2 + 8 + 8 + 8 + 8 + 1;
+}
+
+#[account]
+pub struct ClaimCommitment {
+    pub drop: Pubkey,
+    pub claimer: Pubkey,
+    /// `sha256(secret || claimer)`.
+    pub commitment: [u8; 32],
+    pub committed_slot: u64,
+    pub bump: u8,
+}
+
+impl ClaimCommitment {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 1;
+}
+
+#[account]
+pub struct ShieldedConfig {
+    pub authority: Pubkey,
+    pub is_initialized: bool,
+    pub tree_depth: u8,
+    pub vault_bump: u8,
+    pub current_root: [u8; 32],
+    pub next_leaf_index: u32,
+    /// Layout version; 0 for accounts written before versioning.
+    pub version: u8,
+}
+
+impl ShieldedConfig {
+    pub const LEN: usize = 32 + 1 + 1 + 1 + 32 + 4 + 1;
+    pub const VERSION: u8 = 1;
+    pub const LEGACY_LENS: [usize; 1] = [71];
+}
+
+#[account]
+pub struct ShieldedMerkleTree {
+    pub depth: u8,
+    pub filled_subtrees: [[u8; 32]; 20],
+    pub zeroes: [[u8; 32]; 20],
+    pub root: [u8; 32],
+    pub next_leaf_index: u32,
+}
+
+impl ShieldedMerkleTree {
+    pub const LEN: usize = 1 + (32 * 20 * 2) + 32 + 4;
+}
+
+#[account]
+pub struct ShieldedNullifier {
+    pub nullifier: [u8; 32],
+    pub is_used: bool,
+}
+
+impl ShieldedNullifier {
+    pub const LEN: usize = 32 + 1;
+}
+
+impl DropAccount {
+    pub const LEN: usize = 32 + 32 + 8 + 1 + 1 + 8 + 8 + 8 + 32 + 1 + 32 + 32 + 1 + 4 + 4 + 8
+        + 8 + 8 + 8 + 8 + 1 + 32 + 1 + 1 + 1;
+    pub const VERSION: u8 = 1;
+    /// Data lengths of every deployed earlier layout, oldest first.
+    pub const LEGACY_LENS: [usize; 1] = [131];
+
+    /// Escrow still held for this drop.
+    pub fn remaining_amount(&self) -> u64 {
+        self.amount.saturating_sub(self.claimed_amount)
+    }
+
+    pub fn is_vesting(&self) -> bool {
+        self.vest_end != 0
+    }
+
+    /// Portion of `amount` unlocked at `now`: nothing before the cliff, then linear
+    /// from `vest_start` to `vest_end`.
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        if !self.is_vesting() || now >= self.vest_end {
+            return self.amount;
+        }
+        if now < self.cliff_at || now <= self.vest_start {
+            return 0;
+        }
+        let elapsed = (now - self.vest_start) as u128;
+        let duration = (self.vest_end - self.vest_start) as u128;
+        (self.amount as u128 * elapsed / duration) as u64
+    }
+
+    /// Amount the next claim pays at `now`.
+    pub fn next_claim_amount(&self, now: i64) -> u64 {
+        if self.is_vesting() {
+            return self.vested_amount(now).saturating_sub(self.claimed_amount);
+        }
+        if self.max_claims == 0 {
+            return self.amount;
+        }
+        self.per_claim_amount.min(self.remaining_amount())
+    }
+}
+
+/// Linear unlock schedule for vesting drops.
+#[derive(AnchorSerialize, AnchorDeserialize, Default, Clone, Copy)]
+pub struct VestingSchedule {
+    /// Nothing can be claimed before this time; must lie within `[vest_start, vest_end]`.
+    pub cliff_at: i64,
+    pub vest_start: i64,
+    pub vest_end: i64,
+}
+
+/// Recipient, split and unlock parameters for pool drops.
+#[derive(Default, Clone, Copy)]
+struct DropTerms {
+    /// Stored as the drop's `recipient`; the grantee of a vesting drop.
+    recipient: Pubkey,
+    max_claims: u32,
+    per_claim_amount: u64,
+    vesting: VestingSchedule,
+}
+
+impl DropTerms {
+    fn validate(&self, amount: u64, expires_at: i64) -> Result<()> {
+        let vesting = &self.vesting;
+        if vesting.vest_end != 0 {
+            require!(self.max_claims == 0, DarkPoolError::InvalidVestingSchedule);
+            require!(
+                vesting.vest_start < vesting.vest_end
+                    && vesting.cliff_at >= vesting.vest_start
+                    && vesting.cliff_at <= vesting.vest_end,
+                DarkPoolError::InvalidVestingSchedule
+            );
+            // An expiry before full unlock would let the funder reclaim vested funds.
+            require!(
+                expires_at == 0 || expires_at > vesting.vest_end,
+                DarkPoolError::InvalidVestingSchedule
+            );
+        }
+        if self.max_claims == 0 {
+            return Ok(());
+        }
+        // Shares are fixed: a split derived from on-chain state could be ground by claimers.
+        require!(
+            self.per_claim_amount > 0
+                && self.per_claim_amount.checked_mul(self.max_claims as u64) == Some(amount),
+            DarkPoolError::InvalidClaimSplit
+        );
+        Ok(())
+    }
+}
+
+#[account]
+pub struct NullifierAccount {
+    pub nullifier: [u8; 32],
+    pub is_used: bool,
+    pub claimer: Pubkey,
+    pub used_at: i64,
+    pub bump: u8,
+    /// Layout version; 0 for accounts written before versioning.
+    pub version: u8,
 }
 
-impl<'info> Accounts<'info, ()> for DepositShielded<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let shielded_config =
-            Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let shielded_tree =
-            Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let shielded_vault =
-            SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let depositor = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let system_program =
-            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self {
-            shielded_config,
-            shielded_tree,
-            shielded_vault,
-            depositor,
-            system_program,
-        })
-    }
+impl NullifierAccount {
+    pub const LEN: usize = 32 + 1 + 32 + 8 + 1 + 1;
+    pub const VERSION: u8 = 1;
+    pub const LEGACY_LENS: [usize; 1] = [74];
 }
 
-impl<'info> ToAccountMetas for DepositShielded<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.shielded_config.to_account_metas(override_signer));
-        metas.extend(self.shielded_tree.to_account_metas(override_signer));
-        metas.extend(self.shielded_vault.to_account_metas(override_signer));
-        metas.extend(self.depositor.to_account_metas(override_signer));
-        metas.extend(self.system_program.to_account_metas(override_signer));
-        metas
-    }
+#[account]
+pub struct RateLimitAccount {
+    pub last_drop_at: i64,
+    pub bump: u8,
+    /// Start of the current quota window; the previous window's totals are kept so usage can
+    /// be weighted across the window boundary.
+    pub window_start: i64,
+    pub window_count: u32,
+    pub window_amount: u64,
+    pub prev_window_count: u32,
+    pub prev_window_amount: u64,
 }
 
-impl<'info> ToAccountInfos<'info> for DepositShielded<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.shielded_config.to_account_infos());
-        infos.extend(self.shielded_tree.to_account_infos());
-        infos.extend(self.shielded_vault.to_account_infos());
-        infos.extend(self.depositor.to_account_infos());
-        infos.extend(self.system_program.to_account_infos());
-        infos
+impl RateLimitAccount {
+    pub const LEN: usize = 8 + 1 + 8 + 4 + 8 + 4 + 8;
+    /// Size of accounts created before window quotas existed.
+    pub const LEGACY_LEN: usize = 8 + 1;
+
+    /// Usage over the last `window_seconds`: the current window plus the overlapping share of
+    /// the previous one.
+    pub fn window_usage(&self, now: i64, window_seconds: i64) -> (u64, u64) {
+        let overlap = window_seconds
+            .saturating_sub(now.saturating_sub(self.window_start))
+            .clamp(0, window_seconds) as u128;
+        let weigh = |previous: u64| {
+            ((previous as u128) * overlap / (window_seconds as u128).max(1)) as u64
+        };
+        (
+            (self.window_count as u64).saturating_add(weigh(self.prev_window_count as u64)),
+            self.window_amount
+                .saturating_add(weigh(self.prev_window_amount)),
+        )
     }
-}
 
-impl<'info> AccountsExit<'info> for DepositShielded<'info> {
-    fn exit(&self, program_id: &Pubkey) -> Result<()> {
-        self.shielded_config.exit(program_id)?;
-        self.shielded_tree.exit(program_id)?;
+    /// Moves the window forward so that `now` falls inside the current one.
+    pub fn roll_window(&mut self, now: i64, window_seconds: i64) {
+        let elapsed = now.saturating_sub(self.window_start);
+        if self.window_start == 0 || elapsed >= window_seconds.saturating_mul(2) {
+            self.prev_window_count = 0;
+            self.prev_window_amount = 0;
+            self.window_count = 0;
+            self.window_amount = 0;
+            self.window_start = now;
+        } else if elapsed >= window_seconds {
+            self.prev_window_count = self.window_count;
+            self.prev_window_amount = self.window_amount;
+            self.window_count = 0;
+            self.window_amount = 0;
+            self.window_start = self.window_start.saturating_add(window_seconds);
+        }
+    }
+
+    /// Records one drop of `amount`, failing with `QuotaExceeded` (and logging the headroom
+    /// left) if it would exceed `limits`.
+    pub fn record_drop(&mut self, now: i64, limits: &QuotaLimits, amount: u64) -> Result<()> {
+        if !limits.is_set() {
+            return Ok(());
+        }
+        self.roll_window(now, limits.window_seconds);
+        let (used_count, used_amount) = self.window_usage(now, limits.window_seconds);
+        let count_left = if limits.max_drops == 0 {
+            u64::MAX
+        } else {
+            (limits.max_drops as u64).saturating_sub(used_count)
+        };
+        let amount_left = if limits.max_amount == 0 {
+            u64::MAX
+        } else {
+            limits.max_amount.saturating_sub(used_amount)
+        };
+        if count_left == 0 || amount > amount_left {
+            msg!(
+                "Quota exceeded: drops_left={}, lamports_left={}, window_seconds={}",
+                count_left,
+                amount_left,
+                limits.window_seconds
+            );
+            return err!(DarkPoolError::QuotaExceeded);
+        }
+        self.window_count = self.window_count.saturating_add(1);
+        self.window_amount = self
+            .window_amount
+            .checked_add(amount)
+            .ok_or(DarkPoolError::NumericalOverflow)?;
         Ok(())
     }
 }
 
-pub(crate) mod __client_accounts_deposit_shielded {
-    use super::*;
-    use anchor_lang::prelude::borsh;
+/// Rolling-window quota; `window_seconds == 0` disables it and a zero max means unlimited.
+/// `max_amount` counts lamports of SOL drops only.
+#[derive(AnchorSerialize, AnchorDeserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct QuotaLimits {
+    pub window_seconds: i64,
+    pub max_drops: u32,
+    pub max_amount: u64,
+}
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct DepositShielded {
-        pub shielded_config: Pubkey,
-        pub shielded_tree: Pubkey,
-        pub shielded_vault: Pubkey,
-        pub depositor: Pubkey,
-        pub system_program: Pubkey,
+impl QuotaLimits {
+    pub const LEN: usize = 8 + 4 + 8;
+
+    pub fn is_set(&self) -> bool {
+        self.window_seconds > 0
     }
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for DepositShielded {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.shielded_config,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.shielded_tree,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.shielded_vault,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.depositor, true),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    self.system_program,
-                    false,
-                ),
-            ]
-        }
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            (0..=POLICY_MAX_QUOTA_WINDOW).contains(&self.window_seconds),
+            DarkPoolError::InvalidPolicy
+        );
+        Ok(())
     }
 }
 
-#[derive(Accounts)]
-#[instruction(nullifier: [u8; 32])]
-pub struct SpendShielded<'info> {
-    #[account(mut)]
-    pub shielded_config: Box<Account<'info, ShieldedConfig>>,
-    #[account(mut)]
-    pub shielded_tree: Box<Account<'info, ShieldedMerkleTree>>,
-    #[account(mut)]
-    pub shielded_vault: SystemAccount<'info>,
-    #[account(mut)]
-    pub nullifier_account: Signer<'info>,
-    #[account(mut)]
-    pub recipient: SystemAccount<'info>,
-    #[account(mut)]
-    pub spender: Signer<'info>,
-    pub system_program: Program<'info, System>,
+/// Who may claim a drop.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimPolicy {
+    /// Any signer holding the nullifier.
+    Open,
+    /// Only `recipient` itself, as the claim signer.
+    RecipientSigner,
+    /// Any claimer carrying `recipient`'s Ed25519 signature over `drop || claimer`,
+    /// verified by an Ed25519 program instruction placed right before the claim.
+    RecipientSignature,
+    /// Only through `commit_claim` followed by `reveal_claim` in a later slot.
+    CommitReveal,
 }
 
-impl<'info> Bumps for Initialize<'info> {
-    type Bumps = ();
+impl ClaimPolicy {
+    /// True for the policies that check the claim against the drop's `recipient`.
+    pub fn is_recipient_bound(&self) -> bool {
+        matches!(
+            self,
+            ClaimPolicy::RecipientSigner | ClaimPolicy::RecipientSignature
+        )
+    }
 }
 
-impl<'info> Accounts<'info, ()> for Initialize<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let config = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let sol_vault =
-            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let system_program =
-            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self {
-            config,
-            authority,
-            sol_vault,
-            system_program,
-        })
-    }
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DropStatus {
+    Active,
+    Claimed,
+    Expired,
+}
+
+#[event]
+pub struct DropCreated {
+    pub nullifier: [u8; 32],
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub asset_type: u8,
+    pub expires_at: i64,
+    pub payer: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct DropClaimed {
+    pub nullifier: [u8; 32],
+    pub claimer: Pubkey,
+    pub claimed_at: i64,
+    pub amount: u64,
+    pub fee: u64,
+    /// What the claimer received, net of any mint transfer fee.
+    pub payout: u64,
+    /// Token-2022 transfer fee withheld from the claimer's transfer.
+    pub mint_fee: u64,
+}
+
+#[event]
+pub struct DropExpired {
+    pub nullifier: [u8; 32],
+    pub recipient: Pubkey,
+    pub expires_at: i64,
+    pub funder: Pubkey,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct DropCancelled {
+    pub nullifier: [u8; 32],
+    pub cancelled_by: Pubkey,
+    pub funder: Pubkey,
+    pub refunded: u64,
+    pub cancelled_at: i64,
+}
+
+#[event]
+pub struct DistributorCreated {
+    pub distributor: Pubkey,
+    pub creator: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub num_recipients: u32,
+    pub total_amount: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct DistributorClaimed {
+    pub distributor: Pubkey,
+    pub index: u32,
+    pub claimer: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct DistributorClosed {
+    pub distributor: Pubkey,
+    pub creator: Pubkey,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct PolicyUpdateProposed {
+    pub values: PolicyValues,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct PolicyUpdateCancelled {
+    pub values: PolicyValues,
+}
+
+#[event]
+pub struct PolicyUpdateApplied {
+    pub previous: PolicyValues,
+    pub values: PolicyValues,
+}
+
+#[event]
+pub struct PauseGuardianUpdated {
+    pub previous_guardian: Pubkey,
+    pub new_guardian: Pubkey,
+}
+
+#[event]
+pub struct ProgramPaused {
+    pub paused_by: Pubkey,
+    pub flags: u8,
+    pub paused: u8,
+}
+
+#[event]
+pub struct ProgramUnpaused {
+    pub unpaused_by: Pubkey,
+    pub flags: u8,
+    pub paused: u8,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub previous_len: u32,
+    pub version: u8,
+}
+
+#[event]
+pub struct OutflowLimitUpdated {
+    pub window_seconds: i64,
+    pub limit: u64,
+}
+
+#[event]
+pub struct OutflowLimitTripped {
+    pub window_start: i64,
+    pub outflow: u64,
+    pub limit: u64,
+    pub tripped_at: i64,
+}
+
+#[event]
+pub struct OutflowBreakerReset {
+    pub reset_by: Pubkey,
+    pub outflow: u64,
+    pub reset_at: i64,
+}
+
+#[event]
+pub struct MultisigCreated {
+    pub multisig: Pubkey,
+    pub signer: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct MultisigTransactionProposed {
+    pub transaction: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+}
+
+#[event]
+pub struct MultisigTransactionApproved {
+    pub transaction: Pubkey,
+    pub member: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct MultisigTransactionExecuted {
+    pub transaction: Pubkey,
+    pub index: u64,
+}
+
+#[event]
+pub struct MultisigTransactionClosed {
+    pub transaction: Pubkey,
+    pub index: u64,
+    pub closed_by: Pubkey,
+}
+
+#[event]
+pub struct MultisigMembersUpdated {
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub members_version: u64,
 }
 
-impl<'info> ToAccountMetas for Initialize<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.config.to_account_metas(override_signer));
-        metas.extend(self.authority.to_account_metas(override_signer));
-        metas.extend(self.sol_vault.to_account_metas(override_signer));
-        metas.extend(self.system_program.to_account_metas(override_signer));
-        metas
-    }
+#[event]
+pub struct CreatorRoleAdded {
+    pub creator: Pubkey,
+    pub quota: u64,
 }
 
-impl<'info> ToAccountInfos<'info> for Initialize<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.config.to_account_infos());
-        infos.extend(self.authority.to_account_infos());
-        infos.extend(self.sol_vault.to_account_infos());
-        infos.extend(self.system_program.to_account_infos());
-        infos
-    }
+#[event]
+pub struct CreatorRoleUpdated {
+    pub creator: Pubkey,
+    pub enabled: bool,
 }
 
-impl<'info> AccountsExit<'info> for Initialize<'info> {}
+#[event]
+pub struct CreatorRoleLimitsUpdated {
+    pub creator: Pubkey,
+    pub limits: QuotaLimits,
+}
 
-pub(crate) mod __client_accounts_initialize {
-    use super::*;
-    use anchor_lang::prelude::borsh;
+#[event]
+pub struct CreatorRoleRemoved {
+    pub creator: Pubkey,
+}
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct Initialize {
-        pub config: Pubkey,
-        pub authority: Pubkey,
-        pub sol_vault: Pubkey,
-        pub system_program: Pubkey,
-    }
+#[event]
+pub struct PartnerRegistered {
+    pub wallet: Pubkey,
+    pub share_bps: u16,
+}
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for Initialize {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    self.system_program,
-                    false,
-                ),
-            ]
-        }
-    }
+#[event]
+pub struct PartnerRemoved {
+    pub wallet: Pubkey,
+    pub refunded: u64,
 }
 
-pub struct CreateDrop<'info> {
-    pub drop: UncheckedAccount<'info>,
-    pub nullifier_account: UncheckedAccount<'info>,
-    pub config: Account<'info, Config>,
-    pub rate_limit_account: UncheckedAccount<'info>,
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct PartnerPaid {
+    pub wallet: Pubkey,
+    pub nullifier: [u8; 32],
+    pub asset_type: u8,
+    pub amount: u64,
 }
 
-impl<'info> Bumps for CreateDrop<'info> {
-    type Bumps = ();
+#[event]
+pub struct PartnerEarningsWithdrawn {
+    pub wallet: Pubkey,
+    pub amount: u64,
 }
 
-impl<'info> Accounts<'info, ()> for CreateDrop<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let nullifier_account =
-            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let rate_limit_account =
-            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let payer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let system_program =
-            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self {
-            drop,
-            nullifier_account,
-            config,
-            rate_limit_account,
-            payer,
-            system_program,
-        })
-    }
+#[event]
+pub struct FeesSwept {
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub swept_at: i64,
 }
 
-impl<'info> ToAccountMetas for CreateDrop<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.drop.to_account_metas(override_signer));
-        metas.extend(self.nullifier_account.to_account_metas(override_signer));
-        metas.extend(self.config.to_account_metas(override_signer));
-        metas.extend(self.rate_limit_account.to_account_metas(override_signer));
-        metas.extend(self.payer.to_account_metas(override_signer));
-        metas.extend(self.system_program.to_account_metas(override_signer));
-        metas
-    }
+#[event]
+pub struct ClaimCommitted {
+    pub drop: Pubkey,
+    pub claimer: Pubkey,
+    pub slot: u64,
 }
 
-impl<'info> ToAccountInfos<'info> for CreateDrop<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.drop.to_account_infos());
-        infos.extend(self.nullifier_account.to_account_infos());
-        infos.extend(self.config.to_account_infos());
-        infos.extend(self.rate_limit_account.to_account_infos());
-        infos.extend(self.payer.to_account_infos());
-        infos.extend(self.system_program.to_account_infos());
-        infos
-    }
+#[event]
+pub struct ClaimCommitmentClosed {
+    pub drop: Pubkey,
+    pub claimer: Pubkey,
 }
 
-impl<'info> AccountsExit<'info> for CreateDrop<'info> {}
+#[event]
+pub struct ShieldedInitialized {
+    pub authority: Pubkey,
+    pub tree_depth: u8,
+}
 
-pub(crate) mod __client_accounts_create_drop {
-    use super::*;
-    use anchor_lang::prelude::borsh;
+#[event]
+pub struct ShieldedDeposit {
+    pub commitment: [u8; 32],
+    pub amount: u64,
+    pub leaf_index: u32,
+    pub new_root: [u8; 32],
+}
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct CreateDrop {
-        pub drop: Pubkey,
-        pub nullifier_account: Pubkey,
-        pub config: Pubkey,
-        pub rate_limit_account: Pubkey,
-        pub payer: Pubkey,
-        pub system_program: Pubkey,
-    }
+#[event]
+pub struct ShieldedSpent {
+    pub nullifier: [u8; 32],
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub root: [u8; 32],
+}
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for CreateDrop {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.drop, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.nullifier_account,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    self.config,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.rate_limit_account,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, true),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    self.system_program,
-                    false,
-                ),
-            ]
-        }
-    }
+#[event]
+pub struct AuthorityProposed {
+    pub current_authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub delay_seconds: i64,
+    pub expires_at: i64,
 }
 
-pub struct DepositPool<'info> {
-    pub drop: UncheckedAccount<'info>,
-    pub nullifier_account: UncheckedAccount<'info>,
-    pub config: Account<'info, Config>,
-    pub rate_limit_account: UncheckedAccount<'info>,
-    pub payer: Signer<'info>,
-    pub sol_vault: SystemAccount<'info>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct AuthorityProposalCancelled {
+    pub authority: Pubkey,
+    pub cancelled_authority: Pubkey,
 }
 
-impl<'info> Bumps for DepositPool<'info> {
-    type Bumps = ();
+#[event]
+pub struct AuthorityAccepted {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
 }
 
-impl<'info> Accounts<'info, ()> for DepositPool<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let nullifier_account =
-            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let rate_limit_account =
-            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let payer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let system_program =
-            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self {
-            drop,
-            nullifier_account,
-            config,
-            rate_limit_account,
-            payer,
-            sol_vault,
-            system_program,
-        })
-    }
+#[event]
+pub struct AuthorityProposalExpired {
+    pub expired_authority: Pubkey,
+    pub expired_at: i64,
 }
 
-impl<'info> ToAccountMetas for DepositPool<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.drop.to_account_metas(override_signer));
-        metas.extend(self.nullifier_account.to_account_metas(override_signer));
-        metas.extend(self.config.to_account_metas(override_signer));
-        metas.extend(self.rate_limit_account.to_account_metas(override_signer));
-        metas.extend(self.payer.to_account_metas(override_signer));
-        metas.extend(self.sol_vault.to_account_metas(override_signer));
-        metas.extend(self.system_program.to_account_metas(override_signer));
-        metas
-    }
+#[event]
+pub struct FeeChangeProposed {
+    pub current_fee_bps: u16,
+    pub new_fee_bps: u16,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct FeeChangeCancelled {
+    pub cancelled_fee_bps: u16,
 }
 
-impl<'info> ToAccountInfos<'info> for DepositPool<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.drop.to_account_infos());
-        infos.extend(self.nullifier_account.to_account_infos());
-        infos.extend(self.config.to_account_infos());
-        infos.extend(self.rate_limit_account.to_account_infos());
-        infos.extend(self.payer.to_account_infos());
-        infos.extend(self.sol_vault.to_account_infos());
-        infos.extend(self.system_program.to_account_infos());
-        infos
-    }
+#[event]
+pub struct FeeChangeApplied {
+    pub previous_fee_bps: u16,
+    pub new_fee_bps: u16,
 }
 
-impl<'info> AccountsExit<'info> for DepositPool<'info> {}
+#[event]
+pub struct TreasuryChangeProposed {
+    pub current_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+    pub effective_at: i64,
+}
 
-pub(crate) mod __client_accounts_deposit_pool {
-    use super::*;
-    use anchor_lang::prelude::borsh;
+#[event]
+pub struct TreasuryChangeCancelled {
+    pub cancelled_treasury: Pubkey,
+}
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct DepositPool {
-        pub drop: Pubkey,
-        pub nullifier_account: Pubkey,
-        pub config: Pubkey,
-        pub rate_limit_account: Pubkey,
-        pub payer: Pubkey,
-        pub sol_vault: Pubkey,
-        pub system_program: Pubkey,
-    }
+#[event]
+pub struct TreasuryChangeApplied {
+    pub previous_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+}
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for DepositPool {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.drop, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.nullifier_account,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    self.config,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.rate_limit_account,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.payer, true),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    self.system_program,
-                    false,
-                ),
-            ]
-        }
-    }
+#[event]
+pub struct FeeScheduleProposed {
+    pub asset_type: u8,
+    pub schedule: FeeSchedule,
+    pub effective_at: i64,
 }
 
-pub struct ClaimDrop<'info> {
-    pub drop: UncheckedAccount<'info>,
-    pub nullifier_account: UncheckedAccount<'info>,
-    pub claimer: Signer<'info>,
-    pub config: Account<'info, Config>,
-    pub sol_vault: SystemAccount<'info>,
-    pub treasury: SystemAccount<'info>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct FeeScheduleCancelled {
+    pub asset_type: u8,
+    pub schedule: FeeSchedule,
 }
 
-impl<'info> Bumps for ClaimDrop<'info> {
-    type Bumps = ();
+#[event]
+pub struct FeeScheduleApplied {
+    pub asset_type: u8,
+    pub schedule: FeeSchedule,
 }
 
-impl<'info> Accounts<'info, ()> for ClaimDrop<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let nullifier_account =
-            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let claimer = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let sol_vault = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let treasury = SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let system_program =
-            Program::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self {
-            drop,
-            nullifier_account,
-            claimer,
-            config,
-            sol_vault,
-            treasury,
-            system_program,
-        })
-    }
+#[event]
+pub struct AdminActionQueued {
+    pub admin_action: Pubkey,
+    pub index: u64,
+    pub action: AdminAction,
+    pub proposer: Pubkey,
+    pub eta: i64,
 }
 
-impl<'info> ToAccountMetas for ClaimDrop<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.drop.to_account_metas(override_signer));
-        metas.extend(self.nullifier_account.to_account_metas(override_signer));
-        metas.extend(self.claimer.to_account_metas(override_signer));
-        metas.extend(self.config.to_account_metas(override_signer));
-        metas.extend(self.sol_vault.to_account_metas(override_signer));
-        metas.extend(self.treasury.to_account_metas(override_signer));
-        metas.extend(self.system_program.to_account_metas(override_signer));
-        metas
-    }
+#[event]
+pub struct AdminActionExecuted {
+    pub admin_action: Pubkey,
+    pub index: u64,
+    pub action: AdminAction,
+    pub executed_at: i64,
 }
 
-impl<'info> ToAccountInfos<'info> for ClaimDrop<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.drop.to_account_infos());
-        infos.extend(self.nullifier_account.to_account_infos());
-        infos.extend(self.claimer.to_account_infos());
-        infos.extend(self.config.to_account_infos());
-        infos.extend(self.sol_vault.to_account_infos());
-        infos.extend(self.treasury.to_account_infos());
-        infos.extend(self.system_program.to_account_infos());
-        infos
-    }
+#[event]
+pub struct AdminActionCancelled {
+    pub admin_action: Pubkey,
+    pub index: u64,
+    pub action: AdminAction,
+    pub cancelled_by: Pubkey,
 }
 
-impl<'info> AccountsExit<'info> for ClaimDrop<'info> {}
+#[event]
+pub struct AuthorityDelayUpdated {
+    pub authority: Pubkey,
+    pub new_delay_seconds: i64,
+}
 
-pub(crate) mod __client_accounts_claim_drop {
-    use super::*;
-    use anchor_lang::prelude::borsh;
+#[event]
+pub struct AcceptanceWindowUpdated {
+    pub authority: Pubkey,
+    pub new_window_seconds: i64,
+}
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct ClaimDrop {
-        pub drop: Pubkey,
-        pub nullifier_account: Pubkey,
-        pub claimer: Pubkey,
-        pub config: Pubkey,
-        pub sol_vault: Pubkey,
-        pub treasury: Pubkey,
-        pub system_program: Pubkey,
-    }
+#[error_code]
+pub enum DarkPoolError {
+    #[msg("This nullifier has already been used")]
+    NullifierAlreadyUsed,
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for ClaimDrop {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.drop, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.nullifier_account,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.claimer, true),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    self.config,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.sol_vault, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.treasury, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    self.system_program,
-                    false,
-                ),
-            ]
-        }
-    }
-}
+    #[msg("Drop is not active")]
+    DropNotActive,
+
+    #[msg("Invalid nullifier")]
+    InvalidNullifier,
+
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[msg("Asset type is not supported")]
+    InvalidAssetType,
+
+    #[msg("Recipient must differ from payer")]
+    InvalidRecipient,
+
+    #[msg("Expiration timestamp is invalid")]
+    InvalidExpiration,
+
+    #[msg("Program config is not initialized")]
+    ConfigNotInitialized,
+
+    #[msg("Caller is not authorized to create drops")]
+    UnauthorizedCreator,
+
+    #[msg("Create drop rate limit exceeded")]
+    RateLimitExceeded,
+
+    #[msg("Drop has expired")]
+    DropExpired,
+
+    #[msg("Authority cannot be the default public key")]
+    InvalidAuthority,
+
+    #[msg("A pending authority already exists")]
+    PendingAuthorityExists,
+
+    #[msg("No pending authority to process")]
+    NoPendingAuthority,
+
+    #[msg("Authority delay has not elapsed")]
+    AuthorityDelayNotElapsed,
+
+    #[msg("Authority delay is out of bounds")]
+    InvalidAuthorityDelay,
+
+    #[msg("Drop has not yet expired")]
+    DropNotExpired,
+
+    #[msg("Invalid fee bps")]
+    InvalidFeeBps,
+
+    #[msg("Invalid treasury address")]
+    InvalidTreasury,
+
+    #[msg("Insufficient vault balance")]
+    InsufficientVaultBalance,
+
+    #[msg("Account is already initialized")]
+    AccountAlreadyInitialized,
+
+    #[msg("Numerical overflow")]
+    NumericalOverflow,
+
+    #[msg("Shielded config is not initialized")]
+    ShieldedConfigNotInitialized,
+
+    #[msg("Invalid shielded tree depth")]
+    InvalidTreeDepth,
+
+    #[msg("Shielded tree is full")]
+    ShieldedTreeFull,
 
-pub struct ProposeAuthority<'info> {
-    pub config: Account<'info, Config>,
-    pub authority: Signer<'info>,
-}
+    #[msg("Invalid shielded commitment")]
+    InvalidCommitment,
 
-pub struct CancelAuthorityProposal<'info> {
-    pub config: Account<'info, Config>,
-    pub authority: Signer<'info>,
-}
+    #[msg("Shielded state mismatch")]
+    ShieldedStateMismatch,
 
-pub struct AcceptAuthority<'info> {
-    pub config: Account<'info, Config>,
-    pub pending_authority: Signer<'info>,
-}
+    #[msg("Shielded root mismatch")]
+    ShieldedRootMismatch,
 
-pub struct UpdateAuthorityDelay<'info> {
-    pub config: Account<'info, Config>,
-    pub authority: Signer<'info>,
-}
+    #[msg("Recipient amount must cover rent exemption when creating a new account")]
+    ShieldedRecipientNotRentExempt,
 
-impl<'info> Bumps for ProposeAuthority<'info> {
-    type Bumps = ();
-}
+    #[msg("Invalid shielded proof")]
+    InvalidShieldedProof,
 
-impl<'info> Accounts<'info, ()> for ProposeAuthority<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self { config, authority })
-    }
+    #[msg("Poseidon hash failed")]
+    PoseidonHashFailed,
+
+    #[msg("Token accounts are required for token drops")]
+    MissingTokenAccounts,
+
+    #[msg("Mint does not match the drop")]
+    InvalidMint,
+
+    #[msg("Invalid token account")]
+    InvalidTokenAccount,
+
+    #[msg("Mint is not owned by the supplied token program")]
+    InvalidTokenProgram,
+
+    #[msg("Mint has an unsupported Token-2022 extension")]
+    UnsupportedMintExtension,
+
+    #[msg("Funder account does not match the drop")]
+    InvalidFunder,
+
+    #[msg("Only the drop's payer or the authority can cancel it")]
+    UnauthorizedCanceller,
+
+    #[msg("Claimer is not the drop recipient")]
+    RecipientMismatch,
+
+    #[msg("Missing or invalid recipient Ed25519 authorization")]
+    MissingRecipientSignature,
+
+    #[msg("Drop must be claimed with commit_claim and reveal_claim")]
+    CommitRevealRequired,
+
+    #[msg("Claim commitment account is required")]
+    MissingClaimCommitment,
+
+    #[msg("Claim commitment does not match")]
+    InvalidClaimCommitment,
+
+    #[msg("Reveal must land at least one slot after commit")]
+    RevealTooEarly,
+
+    #[msg("Distributor recipient count is out of range")]
+    InvalidRecipientCount,
+
+    #[msg("Leaf index is out of range")]
+    InvalidLeafIndex,
+
+    #[msg("Distributor leaf already claimed")]
+    LeafAlreadyClaimed,
+
+    #[msg("Invalid Merkle proof")]
+    InvalidMerkleProof,
+
+    #[msg("Invalid multi-claim split")]
+    InvalidClaimSplit,
+
+    #[msg("Claim record account is required for multi-claim drops")]
+    MissingClaimRecord,
+
+    #[msg("This wallet already claimed from the drop")]
+    AlreadyClaimedByWallet,
+
+    #[msg("Multi-claim and vesting drops must be claimed with claim_drop")]
+    PartialClaimNotSupported,
+
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+
+    #[msg("Nothing has vested since the last claim")]
+    NothingVested,
+
+    #[msg("Vault balance does not cover outstanding liabilities")]
+    VaultUndercollateralized,
+    #[msg("No fees have accrued since the last sweep")]
+    NoFeesAccrued,
+    #[msg("No pending config change")]
+    NoPendingConfigChange,
+    #[msg("Fee schedule minimum exceeds its maximum")]
+    InvalidFeeSchedule,
+    #[msg("Invalid partner account")]
+    InvalidPartner,
+    #[msg("Partner share must not exceed 10000 bps")]
+    InvalidPartnerShare,
+    #[msg("Partner account required for a referred drop")]
+    MissingPartnerAccount,
+    #[msg("Invalid creator role")]
+    InvalidCreatorRole,
+    #[msg("Creator role is disabled")]
+    CreatorRoleDisabled,
+    #[msg("Creator has used its drop quota")]
+    CreatorQuotaExceeded,
+    #[msg("Multisig needs 1 to 16 distinct members")]
+    InvalidMultisigMembers,
+    #[msg("Multisig threshold must be between 1 and the member count")]
+    InvalidMultisigThreshold,
+    #[msg("Signer is not a multisig member")]
+    NotMultisigMember,
+    #[msg("Multisig transaction does not have enough approvals")]
+    MultisigThresholdNotMet,
+    #[msg("Multisig transaction already executed")]
+    MultisigTransactionExecuted,
+    #[msg("Multisig members changed since the transaction was proposed")]
+    MultisigMembersChanged,
+    #[msg("This instruction is paused")]
+    ProgramPaused,
+    #[msg("Signer may not pause the program")]
+    UnauthorizedPauser,
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+    #[msg("Policy value out of bounds")]
+    InvalidPolicy,
+    #[msg("Window quota exceeded; see the log for the remaining headroom")]
+    QuotaExceeded,
+    #[msg("Vault outflow limit tripped; claims are halted until the authority resets it")]
+    OutflowLimitTripped,
+    #[msg("Invalid outflow limit")]
+    InvalidOutflowLimit,
+    #[msg("Account already uses the current layout")]
+    AccountAlreadyMigrated,
+    #[msg("Account data does not match any known layout")]
+    UnknownAccountLayout,
+    #[msg("The pending authority's acceptance window has closed")]
+    ProposalExpired,
+    #[msg("The pending authority's acceptance window is still open")]
+    ProposalNotExpired,
+    #[msg("Acceptance window is out of bounds")]
+    InvalidAcceptanceWindow,
+    #[msg("Admin action's execution window has closed")]
+    AdminActionExpired,
+    #[msg("Payer account does not match the queued admin action")]
+    InvalidAdminActionPayer,
+    #[msg("Drop does not use the CommitReveal claim policy")]
+    CommitRevealNotEnabled,
+    #[msg("Vesting drops need a grantee")]
+    MissingGrantee,
+    #[msg("Vesting drops cannot be cancelled once the cliff has passed")]
+    VestingStarted,
+    #[msg("Claim policy is not supported for this kind of drop")]
+    UnsupportedClaimPolicy,
+    #[msg("Creator roles need a drop quota or a window max_amount")]
+    UnboundedCreatorRole,
+    #[msg("Proposer does not match the multisig transaction")]
+    InvalidMultisigProposer,
+    #[msg("Authority has changed since the admin action was queued")]
+    AdminActionAuthorityChanged,
+    #[msg("Only the proposer may close a transaction from the current member set")]
+    MultisigTransactionActive,
 }
 
-impl<'info> ToAccountMetas for ProposeAuthority<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.config.to_account_metas(override_signer));
-        metas.extend(self.authority.to_account_metas(override_signer));
-        metas
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn ed25519_ix_data(signer: &Pubkey, message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = public_key_offset + 32;
+        let message_offset: u16 = signature_offset + 64;
+        let mut data = vec![1u8, 0];
+        for field in [
+            signature_offset,
+            instruction_index,
+            public_key_offset,
+            instruction_index,
+            message_offset,
+            message.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[7u8; 64]);
+        data.extend_from_slice(message);
+        data
     }
-}
 
-impl<'info> ToAccountInfos<'info> for ProposeAuthority<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.config.to_account_infos());
-        infos.extend(self.authority.to_account_infos());
-        infos
+    #[test]
+    fn drop_nullifier_is_sha256_of_secret() {
+        let expected: [u8; 32] = [
+            0x66, 0x68, 0x7a, 0xad, 0xf8, 0x62, 0xbd, 0x77, 0x6c, 0x8f, 0xc1, 0x8b, 0x8e, 0x9f,
+            0x8e, 0x20, 0x08, 0x97, 0x14, 0x85, 0x6e, 0xe2, 0x33, 0xb3, 0x90, 0x2a, 0x59, 0x1d,
+            0x0d, 0x5f, 0x29, 0x25,
+        ];
+        assert_eq!(drop_nullifier(&[0u8; 32]), expected);
+        assert_ne!(drop_nullifier(&[1u8; 32]), expected);
     }
-}
 
-impl<'info> AccountsExit<'info> for ProposeAuthority<'info> {}
+    fn merkle_parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let (left, right) = if a <= b { (a, b) } else { (b, a) };
+        hashv(&[&[1u8], &left, &right]).to_bytes()
+    }
 
-pub(crate) mod __client_accounts_propose_authority {
-    use super::*;
-    use anchor_lang::prelude::borsh;
+    #[test]
+    fn merkle_proof_verifies_every_leaf() {
+        let recipients: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = recipients
+            .iter()
+            .enumerate()
+            .map(|(i, recipient)| distributor_leaf(i as u32, recipient, 100 * (i as u64 + 1)))
+            .collect();
+        let left = merkle_parent(leaves[0], leaves[1]);
+        let root = merkle_parent(left, leaves[2]);
+
+        assert!(verify_merkle_proof(&[leaves[1], leaves[2]], &root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[0], leaves[2]], &root, leaves[1]));
+        assert!(verify_merkle_proof(&[left], &root, leaves[2]));
+
+        let wrong_amount = distributor_leaf(0, &recipients[0], 101);
+        assert!(!verify_merkle_proof(&[leaves[1], leaves[2]], &root, wrong_amount));
+        let wrong_recipient = distributor_leaf(2, &recipients[0], 300);
+        assert!(!verify_merkle_proof(&[left], &root, wrong_recipient));
+        assert!(!verify_merkle_proof(&[leaves[2]], &root, leaves[0]));
+    }
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct ProposeAuthority {
-        pub config: Pubkey,
-        pub authority: Pubkey,
+    #[test]
+    fn distributor_bitmap_tracks_claims() {
+        let mut distributor = Distributor {
+            creator: Pubkey::new_unique(),
+            merkle_root: [0u8; 32],
+            total_amount: 0,
+            claimed_amount: 0,
+            num_recipients: 10,
+            num_claimed: 0,
+            created_at: 0,
+            expires_at: 0,
+            bump: 0,
+            claimed_bitmap: vec![0u8; Distributor::bitmap_len(10)],
+        };
+        assert_eq!(distributor.claimed_bitmap.len(), 2);
+        assert!(!distributor.is_claimed(9));
+        distributor.set_claimed(9);
+        assert!(distributor.is_claimed(9));
+        assert!(!distributor.is_claimed(8));
+        assert!(!distributor.is_claimed(1));
+        assert_eq!(
+            Distributor::space(MAX_DISTRIBUTOR_RECIPIENTS),
+            8 + Distributor::BASE_LEN + 8192
+        );
     }
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for ProposeAuthority {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
-            ]
+    fn multi_claim_drop(amount: u64, max_claims: u32, per_claim_amount: u64) -> DropAccount {
+        DropAccount {
+            nullifier: [0u8; 32],
+            recipient: Pubkey::new_unique(),
+            amount,
+            asset_type: ASSET_TYPE_SOL,
+            status: DropStatus::Active,
+            expires_at: 0,
+            created_at: 0,
+            claimed_at: 0,
+            claimer: Pubkey::default(),
+            bump: 0,
+            mint: Pubkey::default(),
+            funder: Pubkey::new_unique(),
+            claim_policy: ClaimPolicy::Open,
+            max_claims,
+            claimed_count: 0,
+            per_claim_amount,
+            claimed_amount: 0,
+            cliff_at: 0,
+            vest_start: 0,
+            vest_end: 0,
+            fee_prepaid: false,
+            referrer: Pubkey::default(),
+            version: DropAccount::VERSION,
+            escrowed: true,
+            legacy_seed: false,
         }
     }
-}
-
-impl<'info> Bumps for CancelAuthorityProposal<'info> {
-    type Bumps = ();
-}
 
-impl<'info> Accounts<'info, ()> for CancelAuthorityProposal<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self { config, authority })
+    #[test]
+    fn multi_claim_terms_validate_split() {
+        let fixed = DropTerms {
+            max_claims: 4,
+            per_claim_amount: 250,
+            ..DropTerms::default()
+        };
+        assert!(fixed.validate(1_000, 0).is_ok());
+        assert!(fixed.validate(999, 0).is_err());
+        let unsplit = DropTerms {
+            max_claims: 4,
+            per_claim_amount: 0,
+            ..DropTerms::default()
+        };
+        assert!(unsplit.validate(4, 0).is_err());
+        assert!(DropTerms::default().validate(1, 0).is_ok());
     }
-}
 
-impl<'info> ToAccountMetas for CancelAuthorityProposal<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.config.to_account_metas(override_signer));
-        metas.extend(self.authority.to_account_metas(override_signer));
-        metas
+    #[test]
+    fn fixed_split_and_single_claim_amounts() {
+        let mut fixed = multi_claim_drop(900, 3, 300);
+        assert_eq!(fixed.next_claim_amount(7), 300);
+        fixed.claimed_amount = 800;
+        assert_eq!(fixed.next_claim_amount(7), 100);
+        let single = multi_claim_drop(900, 0, 0);
+        assert_eq!(single.next_claim_amount(7), 900);
     }
-}
 
-impl<'info> ToAccountInfos<'info> for CancelAuthorityProposal<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.config.to_account_infos());
-        infos.extend(self.authority.to_account_infos());
-        infos
+    #[test]
+    fn vesting_unlocks_linearly_after_cliff() {
+        let mut drop = multi_claim_drop(1_000, 0, 0);
+        drop.vest_start = 100;
+        drop.cliff_at = 150;
+        drop.vest_end = 200;
+        assert_eq!(drop.vested_amount(99), 0);
+        assert_eq!(drop.vested_amount(149), 0);
+        assert_eq!(drop.vested_amount(150), 500);
+        assert_eq!(drop.vested_amount(175), 750);
+        assert_eq!(drop.vested_amount(200), 1_000);
+
+        drop.claimed_amount = 500;
+        assert_eq!(drop.next_claim_amount(175), 250);
+        assert_eq!(drop.next_claim_amount(300), 500);
     }
-}
 
-impl<'info> AccountsExit<'info> for CancelAuthorityProposal<'info> {}
+    #[test]
+    fn vesting_terms_reject_bad_schedules() {
+        let terms = |cliff_at, vest_start, vest_end| DropTerms {
+            vesting: VestingSchedule {
+                cliff_at,
+                vest_start,
+                vest_end,
+            },
+            ..DropTerms::default()
+        };
+        assert!(terms(100, 100, 200).validate(1, 0).is_ok());
+        assert!(terms(100, 100, 200).validate(1, 201).is_ok());
+        assert!(terms(100, 100, 200).validate(1, 200).is_err());
+        assert!(terms(50, 100, 200).validate(1, 0).is_err());
+        assert!(terms(200, 200, 200).validate(1, 0).is_err());
+        let mut multi = terms(100, 100, 200);
+        multi.max_claims = 2;
+        assert!(multi.validate(2, 0).is_err());
+    }
 
-pub(crate) mod __client_accounts_cancel_authority_proposal {
-    use super::*;
-    use anchor_lang::prelude::borsh;
+    #[test]
+    fn claim_commitment_binds_secret_and_claimer() {
+        let secret = [9u8; 32];
+        let claimer = Pubkey::new_unique();
+        let commitment = claim_commitment_hash(&secret, &claimer);
+        assert_eq!(commitment, claim_commitment_hash(&secret, &claimer));
+        assert_ne!(commitment, claim_commitment_hash(&[8u8; 32], &claimer));
+        assert_ne!(
+            commitment,
+            claim_commitment_hash(&secret, &Pubkey::new_unique())
+        );
+    }
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct CancelAuthorityProposal {
-        pub config: Pubkey,
-        pub authority: Pubkey,
+    #[test]
+    fn fee_schedule_clamps_flat_and_bps_components() {
+        let schedule = FeeSchedule {
+            flat: 1_000,
+            bps: 100,
+            min: 2_000,
+            max: 50_000,
+            enabled: true,
+            ..FeeSchedule::default()
+        };
+        assert_eq!(schedule.fee_for(10_000), 2_000);
+        assert_eq!(schedule.fee_for(1_000_000), 11_000);
+        assert_eq!(schedule.fee_for(100_000_000), 50_000);
+        assert_eq!(schedule.fee_for(500), 500);
+        assert_eq!(schedule.fee_for_share(1_000_000, 0, 1_000_000), 11_000);
+        let shares: u64 = (0..3)
+            .map(|i| schedule.fee_for_share(1_000_000, i * 333_333, 333_333))
+            .sum::<u64>()
+            + schedule.fee_for_share(1_000_000, 999_999, 1);
+        assert_eq!(shares, 11_000);
+        assert!(schedule.validate(MAX_FEE_BPS).is_ok());
+        assert!(FeeSchedule { min: 60_000, ..schedule }
+            .validate(MAX_FEE_BPS)
+            .is_err());
+        assert!(FeeSchedule { bps: MAX_FEE_BPS + 1, ..schedule }
+            .validate(MAX_FEE_BPS)
+            .is_err());
     }
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for CancelAuthorityProposal {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
-            ]
+    #[test]
+    fn window_quota_weighs_previous_window_and_reports_exhaustion() {
+        let limits = QuotaLimits {
+            window_seconds: 100,
+            max_drops: 4,
+            max_amount: 1_000,
+        };
+        let mut state = RateLimitAccount {
+            last_drop_at: 0,
+            bump: 255,
+            window_start: 0,
+            window_count: 0,
+            window_amount: 0,
+            prev_window_count: 0,
+            prev_window_amount: 0,
+        };
+        for i in 0..4 {
+            assert!(state.record_drop(1_000 + i, &limits, 200).is_ok());
         }
+        assert!(state.record_drop(1_010, &limits, 1).is_err());
+
+        // Halfway through the next window half of the previous usage still counts.
+        assert_eq!(state.window_usage(1_150, 100), (4, 800));
+        state.roll_window(1_150, 100);
+        assert_eq!(state.window_usage(1_150, 100), (2, 400));
+        assert!(state.record_drop(1_150, &limits, 700).is_err());
+        assert!(state.record_drop(1_150, &limits, 600).is_ok());
+        assert!(state.record_drop(1_150, &limits, 1).is_err());
+
+        // After two idle windows everything is forgotten.
+        assert!(state.record_drop(1_400, &limits, 1_000).is_ok());
+        assert_eq!(state.window_start, 1_400);
+        assert!(state.record_drop(1_401, &QuotaLimits::default(), u64::MAX).is_ok());
     }
-}
-
-impl<'info> Bumps for AcceptAuthority<'info> {
-    type Bumps = ();
-}
 
-impl<'info> Accounts<'info, ()> for AcceptAuthority<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let pending_authority =
-            Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self {
-            config,
-            pending_authority,
-        })
+    #[test]
+    fn policy_defaults_are_valid_and_bounds_are_enforced() {
+        let defaults = PolicyValues::default();
+        assert_eq!(defaults.min_expiration_window, MIN_EXPIRATION_WINDOW);
+        assert_eq!(defaults.max_fee_bps, MAX_FEE_BPS);
+        assert!(defaults.validate().is_ok());
+        assert!(PolicyValues {
+            min_expiration_window: defaults.max_expiration_window + 1,
+            ..defaults
+        }
+        .validate()
+        .is_err());
+        assert!(PolicyValues {
+            max_expiration_window: POLICY_MAX_EXPIRATION_WINDOW + 1,
+            ..defaults
+        }
+        .validate()
+        .is_err());
+        assert!(PolicyValues {
+            min_rate_limit_seconds: -1,
+            ..defaults
+        }
+        .validate()
+        .is_err());
+        assert!(PolicyValues {
+            max_fee_bps: POLICY_MAX_FEE_BPS + 1,
+            ..defaults
+        }
+        .validate()
+        .is_err());
+        assert!(PolicyValues {
+            default_quota: QuotaLimits {
+                window_seconds: POLICY_MAX_QUOTA_WINDOW + 1,
+                ..QuotaLimits::default()
+            },
+            ..defaults
+        }
+        .validate()
+        .is_err());
     }
-}
 
-impl<'info> ToAccountMetas for AcceptAuthority<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.config.to_account_metas(override_signer));
-        metas.extend(self.pending_authority.to_account_metas(override_signer));
-        metas
+    #[test]
+    fn disabled_fee_schedule_falls_back_to_fee_bps() {
+        let config = Config {
+            authority: Pubkey::new_unique(),
+            is_initialized: true,
+            pending_authority: Pubkey::default(),
+            pending_authority_set_at: 0,
+            authority_delay_seconds: DEFAULT_AUTHORITY_DELAY_SECONDS,
+            treasury: Pubkey::new_unique(),
+            fee_bps: 250,
+            sol_vault_bump: 255,
+            outstanding_liabilities: 0,
+            accrued_fees: 0,
+            pending_fee_bps: 0,
+            pending_fee_effective_at: 0,
+            pending_treasury: Pubkey::default(),
+            pending_treasury_effective_at: 0,
+            sol_fee_schedule: FeeSchedule::default(),
+            token_fee_schedule: FeeSchedule {
+                flat: 7,
+                enabled: true,
+                ..FeeSchedule::default()
+            },
+            pending_fee_schedule: FeeSchedule::default(),
+            pending_fee_schedule_asset_type: 0,
+            pending_fee_schedule_effective_at: 0,
+            paused: 0,
+            pause_guardian: Pubkey::default(),
+            outflow_window_seconds: 0,
+            outflow_limit: 0,
+            outflow_window_start: 0,
+            outflow_in_window: 0,
+            outflow_tripped: false,
+            version: Config::VERSION,
+            authority_acceptance_window_seconds: DEFAULT_ACCEPTANCE_WINDOW_SECONDS,
+            admin_action_count: 0,
+            partner_accrued_fees: 0,
+            pending_authority_expires_at: 0,
+        };
+        assert_eq!(config.fee_schedule(ASSET_TYPE_SOL).fee_for(10_000), 250);
+        assert_eq!(config.fee_schedule(ASSET_TYPE_TOKEN).fee_for(10_000), 7);
     }
-}
 
-impl<'info> ToAccountInfos<'info> for AcceptAuthority<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.config.to_account_infos());
-        infos.extend(self.pending_authority.to_account_infos());
-        infos
+    #[test]
+    fn multisig_members_must_be_distinct_and_cover_threshold() {
+        let members: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        assert!(validate_multisig_members(&members, 2).is_ok());
+        assert!(validate_multisig_members(&members, 0).is_err());
+        assert!(validate_multisig_members(&members, 4).is_err());
+        assert!(validate_multisig_members(&[], 1).is_err());
+        assert!(validate_multisig_members(&[members[0], members[0]], 1).is_err());
+        assert!(validate_multisig_members(&[Pubkey::default()], 1).is_err());
+        let too_many: Vec<Pubkey> = (0..=MAX_MULTISIG_MEMBERS)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        assert!(validate_multisig_members(&too_many, 1).is_err());
     }
-}
-
-impl<'info> AccountsExit<'info> for AcceptAuthority<'info> {}
-
-pub(crate) mod __client_accounts_accept_authority {
-    use super::*;
-    use anchor_lang::prelude::borsh;
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct AcceptAuthority {
-        pub config: Pubkey,
-        pub pending_authority: Pubkey,
+    #[test]
+    fn multisig_transaction_space_matches_serialized_size() {
+        let transaction = MultisigTransaction {
+            multisig: Pubkey::new_unique(),
+            index: 3,
+            proposer: Pubkey::new_unique(),
+            accounts: vec![
+                MultisigAccountMeta {
+                    pubkey: Pubkey::new_unique(),
+                    is_signer: false,
+                    is_writable: true,
+                };
+                2
+            ],
+            data: vec![7u8; 40],
+            approvals: 0b101,
+            members_version: 1,
+            executed: false,
+            created_at: 0,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        transaction.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + MultisigTransaction::space(2, 40));
     }
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for AcceptAuthority {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.pending_authority,
-                    true,
-                ),
-            ]
-        }
+    #[test]
+    fn ed25519_signature_matches_inline_key_and_message() {
+        let signer = Pubkey::new_unique();
+        let message = [Pubkey::new_unique().to_bytes(), Pubkey::new_unique().to_bytes()].concat();
+        let data = ed25519_ix_data(&signer, &message, u16::MAX);
+        assert!(ed25519_signature_matches(&data, &signer, &message));
+        assert!(!ed25519_signature_matches(&data, &Pubkey::new_unique(), &message));
+        assert!(!ed25519_signature_matches(&data, &signer, &message[..32]));
     }
-}
-
-impl<'info> Bumps for UpdateAuthorityDelay<'info> {
-    type Bumps = ();
-}
 
-impl<'info> Accounts<'info, ()> for UpdateAuthorityDelay<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self { config, authority })
+    #[test]
+    fn ed25519_signature_rejects_external_offsets_and_bad_data() {
+        let signer = Pubkey::new_unique();
+        let message = [1u8; 64];
+        let data = ed25519_ix_data(&signer, &message, 0);
+        assert!(!ed25519_signature_matches(&data, &signer, &message));
+        assert!(!ed25519_signature_matches(&[], &signer, &message));
+        let truncated = ed25519_ix_data(&signer, &message, u16::MAX);
+        assert!(!ed25519_signature_matches(&truncated[..100], &signer, &message));
     }
-}
 
-impl<'info> ToAccountMetas for UpdateAuthorityDelay<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.config.to_account_metas(override_signer));
-        metas.extend(self.authority.to_account_metas(override_signer));
-        metas
+    /// Fills every current field with ones, truncates to each earlier layout and checks the
+    /// legacy reader keeps the stored prefix and zero-fills the rest.
+    fn assert_reads_legacy_layouts<T>(len: usize, legacy_lens: &[usize])
+    where
+        T: AccountSerialize + AccountDeserialize + Discriminator,
+    {
+        assert!(*legacy_lens.last().unwrap() < len);
+        assert!(legacy_lens.windows(2).all(|pair| pair[0] < pair[1]));
+        let mut current = T::DISCRIMINATOR.to_vec();
+        current.resize(8 + len, 1);
+        for legacy_len in legacy_lens {
+            let stored = &current[..8 + legacy_len];
+            assert!(T::try_deserialize(&mut &stored[..]).is_err());
+            let state: T = read_legacy_layout(stored, 8 + len).unwrap();
+            let mut written = Vec::new();
+            state.try_serialize(&mut written).unwrap();
+            let mut expected = stored.to_vec();
+            expected.resize(8 + len, 0);
+            assert_eq!(written, expected, "layout of {} bytes", legacy_len);
+        }
+        let state: T = read_legacy_layout(&current, 8 + len).unwrap();
+        let mut written = Vec::new();
+        state.try_serialize(&mut written).unwrap();
+        assert_eq!(written, current);
     }
-}
 
-impl<'info> ToAccountInfos<'info> for UpdateAuthorityDelay<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.config.to_account_infos());
-        infos.extend(self.authority.to_account_infos());
-        infos
-    }
-}
+    #[test]
+    fn every_historical_layout_deserializes_with_defaults() {
+        assert_reads_legacy_layouts::<Config>(Config::LEN, &Config::LEGACY_LENS);
+        assert_reads_legacy_layouts::<DropAccount>(DropAccount::LEN, &DropAccount::LEGACY_LENS);
+        assert_reads_legacy_layouts::<NullifierAccount>(
+            NullifierAccount::LEN,
+            &NullifierAccount::LEGACY_LENS,
+        );
+        assert_reads_legacy_layouts::<ShieldedConfig>(
+            ShieldedConfig::LEN,
+            &ShieldedConfig::LEGACY_LENS,
+        );
 
-impl<'info> AccountsExit<'info> for UpdateAuthorityDelay<'info> {}
+    }
 
-pub(crate) mod __client_accounts_update_authority_delay {
-    use super::*;
-    use anchor_lang::prelude::borsh;
+    /// `Config` as the baseline build wrote it.
+    #[derive(AnchorSerialize)]
+    struct BaselineConfig {
+        authority: Pubkey,
+        is_initialized: bool,
+        pending_authority: Pubkey,
+        pending_authority_set_at: i64,
+        authority_delay_seconds: i64,
+        treasury: Pubkey,
+        fee_bps: u16,
+        sol_vault_bump: u8,
+    }
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct UpdateAuthorityDelay {
-        pub config: Pubkey,
-        pub authority: Pubkey,
+    #[derive(AnchorSerialize)]
+    struct BaselineDropAccount {
+        nullifier: [u8; 32],
+        recipient: Pubkey,
+        amount: u64,
+        asset_type: u8,
+        status: DropStatus,
+        expires_at: i64,
+        created_at: i64,
+        claimed_at: i64,
+        claimer: Pubkey,
+        bump: u8,
     }
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for UpdateAuthorityDelay {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.config, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
-            ]
-        }
+    #[derive(AnchorSerialize)]
+    struct BaselineNullifierAccount {
+        nullifier: [u8; 32],
+        is_used: bool,
+        claimer: Pubkey,
+        used_at: i64,
+        bump: u8,
     }
-}
 
-pub struct ExpireDrop<'info> {
-    pub drop: UncheckedAccount<'info>,
-    pub nullifier_account: UncheckedAccount<'info>,
-    pub config: Account<'info, Config>,
-    pub authority: Signer<'info>,
-    pub rent_collector: SystemAccount<'info>,
-}
+    #[derive(AnchorSerialize)]
+    struct BaselineShieldedConfig {
+        authority: Pubkey,
+        is_initialized: bool,
+        tree_depth: u8,
+        vault_bump: u8,
+        current_root: [u8; 32],
+        next_leaf_index: u32,
+    }
 
-impl<'info> Bumps for ExpireDrop<'info> {
-    type Bumps = ();
-}
+    /// Account data for `fields` under `T`'s discriminator, checked against `T`'s baseline length.
+    fn baseline_account<T: Discriminator>(
+        fields: impl AnchorSerialize,
+        legacy_len: usize,
+    ) -> Vec<u8> {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        data.extend(fields.try_to_vec().unwrap());
+        assert_eq!(data.len(), 8 + legacy_len);
+        data
+    }
 
-impl<'info> Accounts<'info, ()> for ExpireDrop<'info> {
-    fn try_accounts(
-        program_id: &Pubkey,
-        accounts: &mut &'info [AccountInfo<'info>],
-        ix_data: &[u8],
-        bumps: &mut (),
-        reallocs: &mut BTreeSet<Pubkey>,
-    ) -> Result<Self> {
-        let drop = UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let nullifier_account =
-            UncheckedAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let config = Account::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let authority = Signer::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        let rent_collector =
-            SystemAccount::try_accounts(program_id, accounts, ix_data, bumps, reallocs)?;
-        Ok(Self {
-            drop,
-            nullifier_account,
-            config,
+    fn baseline_config(authority: Pubkey) -> BaselineConfig {
+        BaselineConfig {
             authority,
-            rent_collector,
-        })
+            is_initialized: true,
+            pending_authority: Pubkey::default(),
+            pending_authority_set_at: 0,
+            authority_delay_seconds: DEFAULT_AUTHORITY_DELAY_SECONDS,
+            treasury: Pubkey::new_unique(),
+            fee_bps: 250,
+            sol_vault_bump: 254,
+        }
     }
-}
 
-impl<'info> ToAccountMetas for ExpireDrop<'info> {
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
-        let mut metas = Vec::new();
-        let override_signer = is_signer;
-        metas.extend(self.drop.to_account_metas(override_signer));
-        metas.extend(self.nullifier_account.to_account_metas(override_signer));
-        metas.extend(self.config.to_account_metas(override_signer));
-        metas.extend(self.authority.to_account_metas(override_signer));
-        metas.extend(self.rent_collector.to_account_metas(override_signer));
-        metas
+    #[test]
+    fn baseline_accounts_read_with_their_fields_and_defaults() {
+        let authority = Pubkey::new_unique();
+        let data = baseline_account::<Config>(baseline_config(authority), Config::LEGACY_LENS[0]);
+        let config: Config = read_legacy_layout(&data, 8 + Config::LEN).unwrap();
+        assert_eq!(config.authority, authority);
+        assert!(config.is_initialized);
+        assert_eq!(
+            config.authority_delay_seconds,
+            DEFAULT_AUTHORITY_DELAY_SECONDS
+        );
+        assert_eq!((config.fee_bps, config.sol_vault_bump), (250, 254));
+        assert!(!config.sol_fee_schedule.enabled);
+        assert_eq!(config.paused, 0);
+        assert_eq!(config.outflow_limit, 0);
+        assert_eq!(config.outstanding_liabilities, 0);
+        assert_eq!(config.version, 0);
+        assert_eq!(config.acceptance_window(), DEFAULT_ACCEPTANCE_WINDOW_SECONDS);
+
+        let claimer = Pubkey::new_unique();
+        let data = baseline_account::<DropAccount>(
+            BaselineDropAccount {
+                nullifier: [7u8; 32],
+                recipient: Pubkey::new_unique(),
+                amount: LAMPORTS,
+                asset_type: ASSET_TYPE_SOL,
+                status: DropStatus::Claimed,
+                expires_at: 1_000,
+                created_at: 10,
+                claimed_at: 20,
+                claimer,
+                bump: 253,
+            },
+            DropAccount::LEGACY_LENS[0],
+        );
+        let drop: DropAccount = read_legacy_layout(&data, 8 + DropAccount::LEN).unwrap();
+        assert_eq!(drop.nullifier, [7u8; 32]);
+        assert_eq!(drop.amount, LAMPORTS);
+        assert!(matches!(drop.status, DropStatus::Claimed));
+        assert_eq!((drop.expires_at, drop.claimed_at), (1_000, 20));
+        assert_eq!((drop.claimer, drop.bump), (claimer, 253));
+        assert_eq!(drop.funder, Pubkey::default());
+        assert!(matches!(drop.claim_policy, ClaimPolicy::Open));
+        assert_eq!((drop.max_claims, drop.vest_end), (0, 0));
+        assert!(!drop.escrowed);
+        assert_eq!(drop.version, 0);
+
+        let data = baseline_account::<NullifierAccount>(
+            BaselineNullifierAccount {
+                nullifier: [7u8; 32],
+                is_used: true,
+                claimer,
+                used_at: 20,
+                bump: 252,
+            },
+            NullifierAccount::LEGACY_LENS[0],
+        );
+        let nullifier: NullifierAccount =
+            read_legacy_layout(&data, 8 + NullifierAccount::LEN).unwrap();
+        assert!(nullifier.is_used);
+        assert_eq!((nullifier.claimer, nullifier.used_at), (claimer, 20));
+        assert_eq!((nullifier.bump, nullifier.version), (252, 0));
+
+        let data = baseline_account::<ShieldedConfig>(
+            BaselineShieldedConfig {
+                authority,
+                is_initialized: true,
+                tree_depth: SHIELDED_TREE_MAX_DEPTH,
+                vault_bump: 251,
+                current_root: [9u8; 32],
+                next_leaf_index: 5,
+            },
+            ShieldedConfig::LEGACY_LENS[0],
+        );
+        let shielded: ShieldedConfig = read_legacy_layout(&data, 8 + ShieldedConfig::LEN).unwrap();
+        assert_eq!(shielded.authority, authority);
+        assert_eq!(shielded.tree_depth, SHIELDED_TREE_MAX_DEPTH);
+        assert_eq!(
+            (shielded.current_root, shielded.next_leaf_index),
+            ([9u8; 32], 5)
+        );
+        assert_eq!(shielded.version, 0);
     }
-}
 
-impl<'info> ToAccountInfos<'info> for ExpireDrop<'info> {
-    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
-        let mut infos = Vec::new();
-        infos.extend(self.drop.to_account_infos());
-        infos.extend(self.nullifier_account.to_account_infos());
-        infos.extend(self.config.to_account_infos());
-        infos.extend(self.authority.to_account_infos());
-        infos.extend(self.rent_collector.to_account_infos());
-        infos
+    #[test]
+    fn admin_action_space_fits_every_variant() {
+        let actions = [
+            AdminAction::SetFee { fee_bps: 0 },
+            AdminAction::SetTreasury {
+                treasury: Pubkey::new_unique(),
+            },
+            AdminAction::SetPolicy {
+                values: PolicyValues::default(),
+            },
+            AdminAction::RotateKey {
+                new_authority: Pubkey::new_unique(),
+            },
+            AdminAction::Unpause { flags: PAUSE_ALL },
+        ];
+        let sizes: Vec<usize> = actions
+            .iter()
+            .map(|action| action.try_to_vec().unwrap().len())
+            .collect();
+        assert_eq!(sizes.iter().copied().max(), Some(AdminAction::MAX_LEN));
+
+        let policy = PolicyValues::default();
+        assert!(actions
+            .iter()
+            .all(|action| validate_admin_action(action, &policy).is_ok()));
+        assert!(validate_admin_action(
+            &AdminAction::SetFee {
+                fee_bps: policy.max_fee_bps + 1
+            },
+            &policy
+        )
+        .is_err());
+        assert!(validate_admin_action(&AdminAction::Unpause { flags: 0 }, &policy).is_err());
     }
-}
-
-impl<'info> AccountsExit<'info> for ExpireDrop<'info> {}
-
-pub(crate) mod __client_accounts_expire_drop {
-    use super::*;
-    use anchor_lang::prelude::borsh;
 
-    #[derive(anchor_lang::AnchorSerialize)]
-    pub struct ExpireDrop {
-        pub drop: Pubkey,
-        pub nullifier_account: Pubkey,
-        pub config: Pubkey,
-        pub authority: Pubkey,
-        pub rent_collector: Pubkey,
+    use anchor_lang::solana_program::entrypoint::ProgramResult;
+    use anchor_lang::solana_program::instruction::Instruction;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_lang::solana_program::system_instruction::SystemError;
+    use anchor_lang::InstructionData;
+    use darkpool_test_runtime::spl_tlv_account_resolution::state::ExtraAccountMetaList;
+    use darkpool_test_runtime::spl_transfer_hook_interface::{
+        error::TransferHookError, get_extra_account_metas_address, instruction::ExecuteInstruction,
+    };
+    use darkpool_test_runtime::{Account as TestAccount, Failure, Outcome, Runtime};
+    use spl_token_2022::extension::transfer_hook::TransferHook;
+    use spl_token_2022::extension::{BaseStateWithExtensionsMut, StateWithExtensionsMut};
+    use std::convert::TryInto;
+
+    const SECRET: [u8; 32] = [42u8; 32];
+    const LAMPORTS: u64 = 1_000_000_000;
+
+    fn process_darkpool(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        data: &[u8],
+    ) -> ProgramResult {
+        // `entry` wants the infos to outlive their slice, as they do on chain.
+        let accounts: &[AccountInfo] = Box::leak(accounts.to_vec().into_boxed_slice());
+        entry(program_id, accounts, data)
     }
 
-    #[automatically_derived]
-    impl anchor_lang::ToAccountMetas for ExpireDrop {
-        fn to_account_metas(
-            &self,
-            _is_signer: Option<bool>,
-        ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
-            vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.drop, false),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.nullifier_account,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    self.config,
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new(self.authority, true),
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    self.rent_collector,
-                    false,
-                ),
-            ]
-        }
+    fn pda(seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &ID).0
     }
-}
 
-#[account]
-pub struct Config {
-    pub authority: Pubkey,
-    pub is_initialized: bool,
-    pub pending_authority: Pubkey,
-    pub pending_authority_set_at: i64,
-    pub authority_delay_seconds: i64,
-    pub treasury: Pubkey,
-    pub fee_bps: u16,
-    pub sol_vault_bump: u8,
-}
+    fn custom_error(error: DarkPoolError) -> ProgramError {
+        ProgramError::from(Error::from(error))
+    }
 
-fn max_leaves_for_depth(depth: u8) -> Result<u32> {
-    require!(
-        depth > 0 && depth <= SHIELDED_TREE_MAX_DEPTH,
-        DarkPoolError::InvalidTreeDepth
-    );
-    1u32
-        .checked_shl(depth as u32)
-        .ok_or(error!(DarkPoolError::NumericalOverflow))
-}
+    /// The `T` events an instruction emitted, decoded from their `sol_log_data` payloads.
+    fn events<T: Discriminator + AnchorDeserialize>(outcome: &Outcome) -> Vec<T> {
+        outcome
+            .events
+            .iter()
+            .filter_map(|data| data.strip_prefix(&T::DISCRIMINATOR[..]))
+            .map(|mut data| T::deserialize(&mut data).unwrap())
+            .collect()
+    }
 
-#[cfg(feature = "poseidon")]
-fn poseidon_params() -> PoseidonParameters<Fr> {
-    let ark = poseidon_constants_fr::POSEIDON_ARK_FR.to_vec();
-    let mds = poseidon_constants_fr::POSEIDON_MDS_FR
-        .iter()
-        .map(|row| row.to_vec())
-        .collect::<Vec<_>>();
-    PoseidonParameters::new(ark, mds, 8, 57, 3, 5)
-}
+    /// An initialized pool with a funded authority, running `darkpool` natively.
+    struct Pool {
+        runtime: Runtime,
+        authority: Pubkey,
+        treasury: Pubkey,
+    }
 
-#[cfg(feature = "poseidon")]
-fn fr_from_bytes(value: &[u8; 32]) -> Fr {
-    <Fr as ark_ff::PrimeField>::from_be_bytes_mod_order(value)
-}
+    impl Pool {
+        fn new() -> Self {
+            let mut runtime = Runtime::new();
+            runtime.add_program(ID, process_darkpool);
+            let authority = Pubkey::new_unique();
+            runtime.airdrop(&authority, 100 * LAMPORTS);
+            let mut pool = Self {
+                runtime,
+                authority,
+                treasury: Pubkey::new_unique(),
+            };
+            pool.send(
+                accounts::Initialize {
+                    config: pda(&[b"config"]),
+                    authority,
+                    sol_vault: pda(&[b"sol_vault"]),
+                    system_program: system_program::ID,
+                },
+                instruction::Initialize {
+                    treasury: pool.treasury,
+                    fee_bps: 0,
+                },
+            )
+            .unwrap();
+            pool
+        }
 
-#[cfg(feature = "poseidon")]
-fn fr_to_bytes(value: &Fr) -> [u8; 32] {
-    let mut out = [0u8; 32];
-    let bytes = <Fr as ark_ff::PrimeField>::into_bigint(*value).to_bytes_be();
-    let start = 32usize.saturating_sub(bytes.len());
-    out[start..].copy_from_slice(&bytes);
-    out
-}
+        fn send(
+            &mut self,
+            accounts: impl ToAccountMetas,
+            data: impl InstructionData,
+        ) -> std::result::Result<Outcome, Failure> {
+            self.send_with(accounts, data, Vec::new())
+        }
 
-#[cfg(feature = "poseidon")]
-fn hash_pair_bytes(left: &[u8; 32], right: &[u8; 32]) -> Result<[u8; 32]> {
-    let mut poseidon = Poseidon::<Fr>::new(poseidon_params());
-    let left_fr = fr_from_bytes(left);
-    let right_fr = fr_from_bytes(right);
-    let hash = poseidon
-        .hash(&[left_fr, right_fr])
-        .map_err(|_| error!(DarkPoolError::PoseidonHashFailed))?;
-    Ok(fr_to_bytes(&hash))
-}
+        fn send_with(
+            &mut self,
+            accounts: impl ToAccountMetas,
+            data: impl InstructionData,
+            remaining_accounts: Vec<AccountMeta>,
+        ) -> std::result::Result<Outcome, Failure> {
+            let mut metas = accounts.to_account_metas(None);
+            metas.extend(remaining_accounts);
+            self.runtime.process(&Instruction {
+                program_id: ID,
+                accounts: metas,
+                data: data.data(),
+            })
+        }
 
-#[cfg(not(feature = "poseidon"))]
-fn hash_pair_bytes(left: &[u8; 32], right: &[u8; 32]) -> Result<[u8; 32]> {
-    Ok(hashv(&[left, right]).to_bytes())
-}
+        fn wallet(&mut self) -> Pubkey {
+            let wallet = Pubkey::new_unique();
+            self.runtime.airdrop(&wallet, 10 * LAMPORTS);
+            wallet
+        }
 
-fn merkle_insert(
-    tree: &mut ShieldedMerkleTree,
-    commitment: [u8; 32],
-) -> Result<(u32, [u8; 32])> {
-    let depth = tree.depth as usize;
-    require!(
-        depth > 0 && depth <= SHIELDED_TREE_MAX_DEPTH_USIZE,
-        DarkPoolError::InvalidTreeDepth
-    );
-    let max_leaves = max_leaves_for_depth(tree.depth)?;
-    require!(
-        tree.next_leaf_index < max_leaves,
-        DarkPoolError::ShieldedTreeFull
-    );
+        fn now(&self) -> i64 {
+            self.runtime.clock.unix_timestamp
+        }
 
-    let leaf_index = tree.next_leaf_index;
-    let mut index = leaf_index;
-    let mut current = commitment;
-    for level in 0..depth {
-        if index % 2 == 0 {
-            tree.filled_subtrees[level] = current;
-            current = hash_pair_bytes(&current, &tree.zeroes[level])?;
-        } else {
-            let left = tree.filled_subtrees[level];
-            current = hash_pair_bytes(&left, &current)?;
+        fn state<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+            let account = self.runtime.account(key).expect("account exists");
+            T::try_deserialize(&mut &account.data[..]).unwrap()
         }
-        index /= 2;
-    }
-    tree.root = current;
-    tree.next_leaf_index = tree
-        .next_leaf_index
-        .checked_add(1)
-        .ok_or(DarkPoolError::NumericalOverflow)?;
-    Ok((leaf_index, current))
-}
 
-fn verify_shielded_proof(
-    proof: &[u8],
-    nullifier: &[u8; 32],
-    amount: u64,
-    root: &[u8; 32],
-) -> Result<()> {
-    // TODO: replace with actual verifier integration (e.g. Groth16/Plonk CPI).
-    // For wiring, require non-empty proof and bind to public inputs with a hash.
-    require!(!proof.is_empty(), DarkPoolError::InvalidShieldedProof);
-    let _ = hashv(&[proof, nullifier, &amount.to_le_bytes(), root]);
-    Ok(())
-}
+        fn config(&self) -> Config {
+            self.state(&pda(&[b"config"]))
+        }
 
-fn process_spend_shielded(
-    ctx: Context<SpendShielded>,
-    nullifier: [u8; 32],
-    amount: u64,
-    root: [u8; 32],
-) -> Result<()> {
-    let program_id = ctx.program_id;
-    let config = &ctx.accounts.shielded_config;
-    let tree = &ctx.accounts.shielded_tree;
-    let vault_info = ctx.accounts.shielded_vault.to_account_info();
-    let nullifier_info = ctx.accounts.nullifier_account.to_account_info();
-    let recipient_info = ctx.accounts.recipient.to_account_info();
-    let system_program_info = ctx.accounts.system_program.to_account_info();
+        /// A Token-2022 mint without extensions.
+        fn mint(&mut self) -> Pubkey {
+            let mint = Pubkey::new_unique();
+            let mut data = vec![0u8; spl_token_2022::state::Mint::LEN];
+            spl_token_2022::state::Mint {
+                decimals: 6,
+                is_initialized: true,
+                supply: u64::MAX,
+                ..Default::default()
+            }
+            .pack_into_slice(&mut data);
+            self.runtime
+                .set_account(mint, TestAccount::new(LAMPORTS, data, spl_token_2022::ID));
+            mint
+        }
 
-    require!(config.is_initialized, DarkPoolError::ShieldedConfigNotInitialized);
-    require!(amount > 0, DarkPoolError::InvalidAmount);
-    require!(root == config.current_root, DarkPoolError::ShieldedRootMismatch);
-    require!(root == tree.root, DarkPoolError::ShieldedStateMismatch);
+        /// A Token-2022 mint whose transfers call `hook_program`, with a validation account
+        /// that lists no extra accounts.
+        fn hook_mint(&mut self, hook_program: &Pubkey) -> Pubkey {
+            let mint = Pubkey::new_unique();
+            let len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+                ExtensionType::TransferHook,
+            ])
+            .unwrap();
+            let mut data = vec![0u8; len];
+            let mut state =
+                StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack_uninitialized(
+                    &mut data,
+                )
+                .unwrap();
+            state
+                .init_extension::<TransferHook>(true)
+                .unwrap()
+                .program_id = Some(*hook_program).try_into().unwrap();
+            state.base = spl_token_2022::state::Mint {
+                decimals: 6,
+                is_initialized: true,
+                supply: u64::MAX,
+                ..Default::default()
+            };
+            state.pack_base();
+            state.init_account_type().unwrap();
+            self.runtime
+                .set_account(mint, TestAccount::new(LAMPORTS, data, spl_token_2022::ID));
+
+            let mut metas = vec![0u8; ExtraAccountMetaList::size_of(0).unwrap()];
+            ExtraAccountMetaList::init::<ExecuteInstruction>(&mut metas, &[]).unwrap();
+            self.runtime.set_account(
+                get_extra_account_metas_address(&mint, hook_program),
+                TestAccount::new(LAMPORTS, metas, *hook_program),
+            );
+            mint
+        }
 
-    if recipient_info.owner == &system_program::ID
-        && recipient_info.lamports() == 0
-        && recipient_info.data_len() == 0
-    {
-        let rent_min = Rent::get()?.minimum_balance(0);
-        require!(
-            amount >= rent_min,
-            DarkPoolError::ShieldedRecipientNotRentExempt
-        );
-    }
+        fn token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+            let account = Pubkey::new_unique();
+            let mut data = vec![0u8; spl_token_2022::state::Account::LEN];
+            spl_token_2022::state::Account {
+                mint: *mint,
+                owner: *owner,
+                amount,
+                state: spl_token_2022::state::AccountState::Initialized,
+                ..Default::default()
+            }
+            .pack_into_slice(&mut data);
+            self.runtime.set_account(
+                account,
+                TestAccount::new(LAMPORTS, data, spl_token_2022::ID),
+            );
+            account
+        }
 
-    let vault_lamports = **vault_info.lamports.borrow();
-    require!(
-        vault_lamports >= amount,
-        DarkPoolError::InsufficientVaultBalance
-    );
+        fn token_balance(&self, account: &Pubkey) -> u64 {
+            let data = &self.runtime.account(account).expect("token account").data;
+            StateWithExtensions::<spl_token_2022::state::Account>::unpack(data)
+                .unwrap()
+                .base
+                .amount
+        }
 
-    let vault_seeds: &[&[u8]] = &[b"shielded_vault", &[config.vault_bump]];
-    let ix = anchor_lang::solana_program::system_instruction::transfer(
-        &vault_info.key(),
-        &recipient_info.key(),
-        amount,
-    );
-    anchor_lang::solana_program::program::invoke_signed(
-        &ix,
-        &[vault_info, recipient_info, system_program_info.clone()],
-        &[vault_seeds],
-    )?;
+        fn token_ledger(&self, mint: &Pubkey) -> TokenLedger {
+            self.state(&pda(&[b"token_ledger", mint.as_ref()]))
+        }
 
-    let mut nullifier_state: ShieldedNullifier;
-    if nullifier_info.owner == program_id {
-        let mut data_slice: &[u8] = &nullifier_info.try_borrow_data()?;
-        nullifier_state = ShieldedNullifier::try_deserialize(&mut data_slice)?;
-    } else {
-        let space = (8 + ShieldedNullifier::LEN) as u64;
-        let rent_min = Rent::get()?.minimum_balance(space as usize);
-        msg!(
-            "shielded_nullifier pre-create key={} owner={} lamports={} data_len={} space={} rent_min={} payer={} payer_lamports={}",
-            nullifier_info.key(),
-            nullifier_info.owner,
-            nullifier_info.lamports(),
-            nullifier_info.data_len(),
-            space,
-            rent_min,
-            ctx.accounts.spender.key(),
-            ctx.accounts.spender.to_account_info().lamports()
-        );
-        require!(
-            nullifier_info.owner == &system_program::ID && nullifier_info.lamports() == 0,
-            DarkPoolError::AccountAlreadyInitialized
-        );
-        let lamports = rent_min + 1_000_000;
-        system_program::create_account(
-            CpiContext::new(
-                system_program_info.clone(),
-                system_program::CreateAccount {
-                    from: ctx.accounts.spender.to_account_info(),
-                    to: nullifier_info.clone(),
-                },
-            ),
-            lamports,
-            space,
-            program_id,
-        )?;
-        msg!(
-            "shielded_nullifier post-create key={} owner={} lamports={} data_len={} rent_min={} funded={}",
-            nullifier_info.key(),
-            nullifier_info.owner,
-            nullifier_info.lamports(),
-            nullifier_info.data_len(),
-            rent_min,
-            lamports
-        );
-        nullifier_state = ShieldedNullifier {
-            nullifier: [0u8; 32],
-            is_used: false,
-        };
-    }
+        fn deposit_accounts(&self, nullifier: &[u8; 32], payer: Pubkey) -> accounts::DepositPool {
+            accounts::DepositPool {
+                drop: pda(&[b"drop", nullifier]),
+                nullifier_account: pda(&[b"nullifier", nullifier]),
+                config: pda(&[b"config"]),
+                rate_limit_account: pda(&[b"rate_limit", payer.as_ref()]),
+                payer,
+                sol_vault: pda(&[b"sol_vault"]),
+                system_program: system_program::ID,
+                mint: None,
+                token_vault: None,
+                payer_token_account: None,
+                token_program: None,
+                treasury_token_account: None,
+                referrer_partner: None,
+                policy: pda(&[b"policy"]),
+                creator_role: None,
+                token_ledger: None,
+            }
+        }
 
-    require!(
-        !nullifier_state.is_used,
-        DarkPoolError::NullifierAlreadyUsed
-    );
-    nullifier_state.nullifier = nullifier;
-    nullifier_state.is_used = true;
-    let mut nullifier_data = nullifier_info.try_borrow_mut_data()?;
-    let pre_len = nullifier_data.len();
-    msg!(
-        "shielded_nullifier pre-write key={} lamports={} data_len={}",
-        nullifier_info.key(),
-        nullifier_info.lamports(),
-        pre_len
-    );
-    let mut nullifier_cursor: &mut [u8] = &mut nullifier_data;
-    nullifier_state.try_serialize(&mut nullifier_cursor)?;
-    let remaining = nullifier_cursor.len();
-    msg!(
-        "shielded_nullifier post-write key={} lamports={} data_len={} remaining={}",
-        nullifier_info.key(),
-        nullifier_info.lamports(),
-        pre_len,
-        remaining
-    );
+        fn token_deposit_accounts(
+            &self,
+            nullifier: &[u8; 32],
+            payer: Pubkey,
+            mint: Pubkey,
+            payer_token_account: Pubkey,
+        ) -> accounts::DepositPool {
+            accounts::DepositPool {
+                mint: Some(mint),
+                token_vault: Some(pda(&[b"token_vault", mint.as_ref()])),
+                payer_token_account: Some(payer_token_account),
+                token_program: Some(spl_token_2022::ID),
+                token_ledger: Some(pda(&[b"token_ledger", mint.as_ref()])),
+                ..self.deposit_accounts(nullifier, payer)
+            }
+        }
 
-    emit!(ShieldedSpent {
-        nullifier,
-        recipient: ctx.accounts.recipient.key(),
-        amount,
-        root,
-    });
+        fn token_claim_accounts(
+            &self,
+            secret: &[u8; 32],
+            claimer: Pubkey,
+            mint: Pubkey,
+            claimer_token_account: Pubkey,
+            treasury_token_account: Pubkey,
+        ) -> accounts::ClaimDrop {
+            accounts::ClaimDrop {
+                mint: Some(mint),
+                token_vault: Some(pda(&[b"token_vault", mint.as_ref()])),
+                claimer_token_account: Some(claimer_token_account),
+                treasury_token_account: Some(treasury_token_account),
+                token_program: Some(spl_token_2022::ID),
+                token_ledger: Some(pda(&[b"token_ledger", mint.as_ref()])),
+                ..self.claim_drop_accounts(secret, claimer)
+            }
+        }
 
-    Ok(())
-}
+        fn cancel_accounts(&self, nullifier: &[u8; 32], canceller: Pubkey) -> accounts::CancelDrop {
+            accounts::CancelDrop {
+                drop: pda(&[b"drop", nullifier]),
+                nullifier_account: pda(&[b"nullifier", nullifier]),
+                config: pda(&[b"config"]),
+                canceller,
+                sol_vault: pda(&[b"sol_vault"]),
+                system_program: system_program::ID,
+                funder: None,
+                mint: None,
+                token_vault: None,
+                funder_token_account: None,
+                token_program: None,
+                token_ledger: None,
+            }
+        }
 
-impl Config {
-    pub const LEN: usize = 32 + 1 + 32 + 8 + 8 + 32 + 2 + 1;
-}
+        /// Grants `creator` an unlimited creator role.
+        fn add_creator(&mut self, creator: Pubkey) {
+            self.send(
+                accounts::AddCreatorRole {
+                    creator_role: pda(&[b"creator_role", creator.as_ref()]),
+                    config: pda(&[b"config"]),
+                    authority: self.authority,
+                    payer: self.authority,
+                    system_program: system_program::ID,
+                },
+                instruction::AddCreatorRole { creator, quota: 10 },
+            )
+            .unwrap();
+        }
 
-#[account]
-pub struct DropAccount {
-    pub nullifier: [u8; 32],
-    pub recipient: Pubkey,
-    pub amount: u64,
-    pub asset_type: u8,
-    pub status: DropStatus,
-    pub expires_at: i64,
-    pub created_at: i64,
-    pub claimed_at: i64,
-    pub claimer: Pubkey,
-    pub bump: u8,
-}
+        fn expires_at(&self) -> i64 {
+            self.now() + MIN_EXPIRATION_WINDOW + 60
+        }
 
-#[account]
-pub struct ShieldedConfig {
-    pub authority: Pubkey,
-    pub is_initialized: bool,
-    pub tree_depth: u8,
-    pub vault_bump: u8,
-    pub current_root: [u8; 32],
-    pub next_leaf_index: u32,
-}
+        /// Warps past `authority_delay_seconds` from now.
+        fn wait_for_delay(&mut self) {
+            let after_delay = self.now() + self.config().authority_delay_seconds;
+            self.runtime.warp_to_timestamp(after_delay);
+        }
 
-impl ShieldedConfig {
-    pub const LEN: usize = 32 + 1 + 1 + 1 + 32 + 4;
-}
+        /// Proposes `values` as the policy and applies them once the delay has passed.
+        fn set_policy(&mut self, values: PolicyValues) -> std::result::Result<Outcome, Failure> {
+            let (policy, config) = (pda(&[b"policy"]), pda(&[b"config"]));
+            self.send(
+                accounts::ProposePolicyUpdate {
+                    policy,
+                    config,
+                    authority: self.authority,
+                    payer: self.authority,
+                    system_program: system_program::ID,
+                },
+                instruction::ProposePolicyUpdate { values },
+            )?;
+            self.wait_for_delay();
+            self.send(
+                accounts::ApplyPolicyUpdate { policy, config },
+                instruction::ApplyPolicyUpdate {},
+            )
+        }
 
-#[account]
-pub struct ShieldedMerkleTree {
-    pub depth: u8,
-    pub filled_subtrees: [[u8; 32]; 20],
-    pub zeroes: [[u8; 32]; 20],
-    pub root: [u8; 32],
-    pub next_leaf_index: u32,
-}
+        /// Proposes `fee_bps` and applies it once the delay has passed.
+        fn set_fee_bps(&mut self, new_fee_bps: u16) {
+            self.send(
+                self.propose_config_change_accounts(),
+                instruction::ProposeFeeChange { new_fee_bps },
+            )
+            .unwrap();
+            self.wait_for_delay();
+            self.send(
+                self.apply_fee_change_accounts(),
+                instruction::ApplyFeeChange {},
+            )
+            .unwrap();
+        }
 
-impl ShieldedMerkleTree {
-    pub const LEN: usize = 1 + (32 * 20 * 2) + 32 + 4;
-}
+        fn propose_config_change_accounts(&self) -> accounts::ProposeConfigChange {
+            accounts::ProposeConfigChange {
+                config: pda(&[b"config"]),
+                authority: self.authority,
+                policy: pda(&[b"policy"]),
+            }
+        }
 
-#[account]
-pub struct ShieldedNullifier {
-    pub nullifier: [u8; 32],
-    pub is_used: bool,
-}
+        fn apply_fee_change_accounts(&self) -> accounts::ApplyFeeChange {
+            accounts::ApplyFeeChange {
+                config: pda(&[b"config"]),
+                policy: pda(&[b"policy"]),
+            }
+        }
 
-impl ShieldedNullifier {
-    pub const LEN: usize = 32 + 1;
-}
+        /// Proposes an instruction from `member` and executes it through a 1-of-n multisig.
+        fn execute_as_multisig(
+            &mut self,
+            member: Pubkey,
+            accounts: impl ToAccountMetas,
+            data: impl InstructionData,
+        ) -> std::result::Result<Outcome, Failure> {
+            let multisig = pda(&[b"multisig"]);
+            let signer = pda(&[b"multisig_signer", multisig.as_ref()]);
+            let index = self.state::<Multisig>(&multisig).transaction_count;
+            let transaction = pda(&[b"multisig_tx", multisig.as_ref(), &index.to_le_bytes()]);
+            let metas = accounts.to_account_metas(None);
+            self.send(
+                accounts::ProposeMultisigTransaction {
+                    multisig,
+                    transaction,
+                    proposer: member,
+                    system_program: system_program::ID,
+                },
+                instruction::ProposeMultisigTransaction {
+                    accounts: metas
+                        .iter()
+                        .map(|meta| MultisigAccountMeta {
+                            pubkey: meta.pubkey,
+                            is_signer: meta.is_signer,
+                            is_writable: meta.is_writable,
+                        })
+                        .collect(),
+                    data: data.data(),
+                },
+            )?;
+            // Only the program can sign for its PDA; the transaction itself carries no signature.
+            let mut remaining: Vec<AccountMeta> = metas
+                .into_iter()
+                .map(|meta| AccountMeta {
+                    is_signer: meta.is_signer && meta.pubkey != signer,
+                    ..meta
+                })
+                .collect();
+            remaining.push(AccountMeta::new_readonly(ID, false));
+            let mut execute = accounts::ExecuteMultisigTransaction {
+                multisig,
+                transaction,
+                multisig_signer: signer,
+                proposer: member,
+            }
+            .to_account_metas(None);
+            execute.extend(remaining);
+            self.runtime.process(&Instruction {
+                program_id: ID,
+                accounts: execute,
+                data: instruction::ExecuteMultisigTransaction {}.data(),
+            })
+        }
 
-impl DropAccount {
-    pub const LEN: usize = 32 + 32 + 8 + 1 + 1 + 8 + 8 + 8 + 32 + 1;
-}
+        fn create_drop_accounts(
+            &self,
+            nullifier: &[u8; 32],
+            payer: Pubkey,
+        ) -> accounts::CreateDrop {
+            accounts::CreateDrop {
+                drop: pda(&[b"drop", nullifier]),
+                nullifier_account: pda(&[b"nullifier", nullifier]),
+                config: pda(&[b"config"]),
+                rate_limit_account: pda(&[b"rate_limit", payer.as_ref()]),
+                payer,
+                system_program: system_program::ID,
+                sol_vault: pda(&[b"sol_vault"]),
+                referrer_partner: None,
+                creator_role: None,
+                policy: pda(&[b"policy"]),
+            }
+        }
 
-#[account]
-pub struct NullifierAccount {
-    pub nullifier: [u8; 32],
-    pub is_used: bool,
-    pub claimer: Pubkey,
-    pub used_at: i64,
-    pub bump: u8,
-}
+        fn claim_drop_accounts(&self, secret: &[u8; 32], claimer: Pubkey) -> accounts::ClaimDrop {
+            let nullifier = drop_nullifier(secret);
+            accounts::ClaimDrop {
+                drop: pda(&[b"drop", &nullifier]),
+                nullifier_account: pda(&[b"nullifier", &nullifier]),
+                claimer,
+                config: pda(&[b"config"]),
+                sol_vault: pda(&[b"sol_vault"]),
+                system_program: system_program::ID,
+                mint: None,
+                token_vault: None,
+                claimer_token_account: None,
+                treasury_token_account: None,
+                token_program: None,
+                instructions_sysvar: None,
+                claim_commitment: None,
+                claim_record: None,
+                partner: None,
+                partner_token_account: None,
+                token_ledger: None,
+            }
+        }
 
-impl NullifierAccount {
-    pub const LEN: usize = 32 + 1 + 32 + 8 + 1;
-}
+        /// The authority backs a SOL drop by topping up the vault, then creates it.
+        fn create_sol_drop(
+            &mut self,
+            secret: &[u8; 32],
+            amount: u64,
+        ) -> std::result::Result<Outcome, Failure> {
+            self.runtime.airdrop(&pda(&[b"sol_vault"]), amount);
+            let nullifier = drop_nullifier(secret);
+            let expires_at = self.expires_at();
+            self.send(
+                self.create_drop_accounts(&nullifier, self.authority),
+                instruction::CreateDrop {
+                    nullifier,
+                    recipient: Pubkey::new_unique(),
+                    amount,
+                    asset_type: ASSET_TYPE_SOL,
+                    expires_at,
+                    claim_policy: ClaimPolicy::Open,
+                },
+            )
+        }
 
-#[account]
-pub struct RateLimitAccount {
-    pub last_drop_at: i64,
-    pub bump: u8,
-}
+        /// A fresh depositor escrows `amount` SOL for `secret` through `deposit_pool`.
+        fn deposit_sol(
+            &mut self,
+            secret: &[u8; 32],
+            recipient: Pubkey,
+            amount: u64,
+            claim_policy: ClaimPolicy,
+        ) -> std::result::Result<Pubkey, Failure> {
+            let depositor = self.wallet();
+            let nullifier = drop_nullifier(secret);
+            self.send(
+                self.deposit_accounts(&nullifier, depositor),
+                instruction::DepositPool {
+                    nullifier,
+                    recipient,
+                    amount,
+                    asset_type: ASSET_TYPE_SOL,
+                    expires_at: 0,
+                    claim_policy,
+                },
+            )?;
+            Ok(depositor)
+        }
 
-impl RateLimitAccount {
-    pub const LEN: usize = 8 + 1;
-}
+        /// Installs an instructions sysvar whose current instruction (index 1) follows an
+        /// Ed25519 program instruction carrying `ed25519_data`. The runtime does not run the
+        /// Ed25519 program, so this exercises only the introspection.
+        fn set_ed25519_instruction(&mut self, ed25519_data: &[u8]) {
+            let instructions = [
+                sysvar_instructions::BorrowedInstruction {
+                    program_id: &ed25519_program::ID,
+                    accounts: Vec::new(),
+                    data: ed25519_data,
+                },
+                sysvar_instructions::BorrowedInstruction {
+                    program_id: &ID,
+                    accounts: Vec::new(),
+                    data: &[],
+                },
+            ];
+            let mut data = sysvar_instructions::construct_instructions_data(&instructions);
+            sysvar_instructions::store_current_index(&mut data, 1);
+            self.runtime.set_account(
+                sysvar_instructions::ID,
+                TestAccount::new(LAMPORTS, data, anchor_lang::solana_program::sysvar::ID),
+            );
+        }
+    }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum DropStatus {
-    Active,
-    Claimed,
-    Expired,
-}
+    #[test]
+    fn sol_drop_is_claimed_once_with_the_secret() {
+        let mut pool = Pool::new();
+        pool.create_sol_drop(&SECRET, LAMPORTS).unwrap();
+        assert_eq!(pool.config().outstanding_liabilities, LAMPORTS);
+
+        let claimer = pool.wallet();
+        let wrong = pool
+            .send(
+                pool.claim_drop_accounts(&[1u8; 32], claimer),
+                instruction::ClaimDrop { secret: [1u8; 32] },
+            )
+            .unwrap_err();
+        assert_eq!(
+            wrong.error,
+            ProgramError::from(Error::from(ErrorCode::ConstraintOwner))
+        );
 
-#[event]
-pub struct DropCreated {
-    pub nullifier: [u8; 32],
-    pub recipient: Pubkey,
-    pub amount: u64,
-    pub asset_type: u8,
-    pub expires_at: i64,
-    pub payer: Pubkey,
-}
+        pool.send(
+            pool.claim_drop_accounts(&SECRET, claimer),
+            instruction::ClaimDrop { secret: SECRET },
+        )
+        .unwrap();
+        assert_eq!(pool.runtime.lamports(&claimer), 11 * LAMPORTS);
+        assert_eq!(pool.config().outstanding_liabilities, 0);
+
+        let replay = pool
+            .send(
+                pool.claim_drop_accounts(&SECRET, claimer),
+                instruction::ClaimDrop { secret: SECRET },
+            )
+            .unwrap_err();
+        assert_eq!(replay.error, custom_error(DarkPoolError::DropNotActive));
+    }
 
-#[event]
-pub struct DropClaimed {
-    pub nullifier: [u8; 32],
-    pub claimer: Pubkey,
-    pub claimed_at: i64,
-}
+    #[test]
+    fn create_drop_rejects_unescrowed_token_drops() {
+        let mut pool = Pool::new();
+        let nullifier = drop_nullifier(&SECRET);
+        let failure = pool
+            .send(
+                pool.create_drop_accounts(&nullifier, pool.authority),
+                instruction::CreateDrop {
+                    nullifier,
+                    recipient: Pubkey::new_unique(),
+                    amount: 1_000,
+                    asset_type: ASSET_TYPE_TOKEN,
+                    expires_at: pool.expires_at(),
+                    claim_policy: ClaimPolicy::Open,
+                },
+            )
+            .unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidAssetType));
+    }
 
-#[event]
-pub struct DropExpired {
-    pub nullifier: [u8; 32],
-    pub recipient: Pubkey,
-    pub expires_at: i64,
-}
+    #[test]
+    fn token_claims_draw_on_the_mint_ledger() {
+        let mut pool = Pool::new();
+        let mint = pool.mint();
+        let treasury = pool.treasury;
+        let treasury_tokens = pool.token_account(&mint, &treasury, 0);
+        let vault = pda(&[b"token_vault", mint.as_ref()]);
+
+        let deposit = |pool: &mut Pool, secret: &[u8; 32], amount: u64| {
+            let depositor = pool.wallet();
+            let source = pool.token_account(&mint, &depositor, amount);
+            let nullifier = drop_nullifier(secret);
+            pool.send(
+                pool.token_deposit_accounts(&nullifier, depositor, mint, source),
+                instruction::DepositPool {
+                    nullifier,
+                    recipient: depositor,
+                    amount,
+                    asset_type: ASSET_TYPE_TOKEN,
+                    expires_at: 0,
+                    claim_policy: ClaimPolicy::Open,
+                },
+            )
+            .unwrap();
+        };
+        deposit(&mut pool, &SECRET, 700);
+        deposit(&mut pool, &[7u8; 32], 300);
+        assert_eq!(pool.token_ledger(&mint).outstanding_liabilities, 1_000);
+        assert_eq!(pool.token_balance(&vault), 1_000);
+
+        // Without the ledger the claim cannot proceed.
+        let claimer = pool.wallet();
+        let claimer_tokens = pool.token_account(&mint, &claimer, 0);
+        let mut accounts =
+            pool.token_claim_accounts(&SECRET, claimer, mint, claimer_tokens, treasury_tokens);
+        accounts.token_ledger = None;
+        let failure = pool
+            .send(accounts, instruction::ClaimDrop { secret: SECRET })
+            .unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::MissingTokenAccounts)
+        );
+
+        pool.send(
+            pool.token_claim_accounts(&SECRET, claimer, mint, claimer_tokens, treasury_tokens),
+            instruction::ClaimDrop { secret: SECRET },
+        )
+        .unwrap();
+        assert_eq!(pool.token_balance(&claimer_tokens), 700);
+        assert_eq!(pool.token_ledger(&mint).outstanding_liabilities, 300);
+        assert_eq!(pool.token_balance(&vault), 300);
+    }
+
+    #[test]
+    fn multisig_authority_creates_accounts_from_a_separate_payer() {
+        let mut pool = Pool::new();
+        let member = pool.wallet();
+        let payer = pool.wallet();
+        let config = pda(&[b"config"]);
+        let multisig = pda(&[b"multisig"]);
+        let signer = pda(&[b"multisig_signer", multisig.as_ref()]);
+
+        let authority_before = pool.runtime.lamports(&pool.authority);
+        pool.send(
+            accounts::CreateMultisig {
+                multisig,
+                config,
+                authority: pool.authority,
+                payer,
+                system_program: system_program::ID,
+            },
+            instruction::CreateMultisig {
+                members: vec![member],
+                threshold: 1,
+            },
+        )
+        .unwrap();
+        assert_eq!(pool.runtime.lamports(&pool.authority), authority_before);
+
+        pool.send(
+            accounts::ProposeAuthority {
+                config,
+                authority: pool.authority,
+            },
+            instruction::ProposeAuthority {
+                new_authority: signer,
+            },
+        )
+        .unwrap();
+        let accept_at = pool.now() + pool.config().authority_delay_seconds;
+        pool.runtime.warp_to_timestamp(accept_at);
+        pool.execute_as_multisig(
+            member,
+            accounts::AcceptAuthority {
+                config,
+                pending_authority: signer,
+            },
+            instruction::AcceptAuthority {},
+        )
+        .unwrap();
+        assert_eq!(pool.config().authority, signer);
+
+        let creator = Pubkey::new_unique();
+        let creator_role = pda(&[b"creator_role", creator.as_ref()]);
+        let add_role = |payer| accounts::AddCreatorRole {
+            creator_role,
+            config,
+            authority: signer,
+            payer,
+            system_program: system_program::ID,
+        };
+        // The signer PDA holds no SOL, so it cannot fund the role itself.
+        let failure = pool
+            .execute_as_multisig(
+                member,
+                add_role(signer),
+                instruction::AddCreatorRole { creator, quota: 3 },
+            )
+            .unwrap_err();
+        assert_eq!(
+            failure.error,
+            ProgramError::Custom(SystemError::ResultWithNegativeLamports as u32)
+        );
 
-#[event]
-pub struct ShieldedInitialized {
-    pub authority: Pubkey,
-    pub tree_depth: u8,
-}
+        let payer_before = pool.runtime.lamports(&payer);
+        pool.execute_as_multisig(
+            member,
+            add_role(payer),
+            instruction::AddCreatorRole { creator, quota: 3 },
+        )
+        .unwrap();
+        let role: CreatorRole = pool.state(&creator_role);
+        assert_eq!((role.creator, role.quota), (creator, 3));
+        assert_eq!(
+            pool.runtime.lamports(&payer),
+            payer_before - pool.runtime.lamports(&creator_role)
+        );
+        assert_eq!(pool.runtime.lamports(&signer), 0);
+
+        // Revoking the role sends its rent back to the payer rather than the signer PDA.
+        pool.execute_as_multisig(
+            member,
+            accounts::RemoveCreatorRole {
+                creator_role,
+                config,
+                authority: signer,
+                rent_receiver: payer,
+            },
+            instruction::RemoveCreatorRole {},
+        )
+        .unwrap();
+        assert_eq!(pool.runtime.lamports(&creator_role), 0);
+        assert_eq!(pool.runtime.lamports(&payer), payer_before);
+    }
 
-#[event]
-pub struct ShieldedDeposit {
-    pub commitment: [u8; 32],
-    pub amount: u64,
-    pub leaf_index: u32,
-    pub new_root: [u8; 32],
-}
+    #[test]
+    fn rotate_key_action_waits_for_acceptance() {
+        let mut pool = Pool::new();
+        let config = pda(&[b"config"]);
+        let policy = pda(&[b"policy"]);
+        let rotate = |pool: &mut Pool, authority: Pubkey, new_authority: Pubkey| {
+            let index = pool.config().admin_action_count;
+            let admin_action = pda(&[b"admin_action", &index.to_le_bytes()]);
+            pool.send(
+                accounts::EnqueueAdminAction {
+                    config,
+                    admin_action,
+                    policy,
+                    authority,
+                    payer: authority,
+                    system_program: system_program::ID,
+                },
+                instruction::EnqueueAdminAction {
+                    action: AdminAction::RotateKey { new_authority },
+                },
+            )
+            .unwrap();
+            let eta = pool.state::<QueuedAdminAction>(&admin_action).eta;
+            pool.runtime.warp_to_timestamp(eta);
+            pool.send(
+                accounts::ExecuteAdminAction {
+                    config,
+                    admin_action,
+                    payer: authority,
+                    policy,
+                    executor: authority,
+                },
+                instruction::ExecuteAdminAction {},
+            )
+            .unwrap();
+        };
+        let accept = |pool: &mut Pool, pending_authority: Pubkey| {
+            pool.send(
+                accounts::AcceptAuthority {
+                    config,
+                    pending_authority,
+                },
+                instruction::AcceptAuthority {},
+            )
+        };
 
-#[event]
-pub struct ShieldedSpent {
-    pub nullifier: [u8; 32],
-    pub recipient: Pubkey,
-    pub amount: u64,
-    pub root: [u8; 32],
-}
+        let authority = pool.authority;
+        let new_authority = pool.wallet();
+        rotate(&mut pool, authority, new_authority);
+        let state = pool.config();
+        assert_eq!(state.authority, authority);
+        assert_eq!(state.pending_authority, new_authority);
+        assert_eq!(
+            state.authority_proposal_expires_at(),
+            pool.now() + state.acceptance_window()
+        );
 
-#[event]
-pub struct AuthorityProposed {
-    pub current_authority: Pubkey,
-    pub pending_authority: Pubkey,
-    pub delay_seconds: i64,
-}
+        let stranger = pool.wallet();
+        let failure = accept(&mut pool, stranger).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::NoPendingAuthority)
+        );
+        pool.runtime
+            .warp_to_timestamp(state.authority_proposal_expires_at() - 1);
+        accept(&mut pool, new_authority).unwrap();
+        let state = pool.config();
+        assert_eq!(state.authority, new_authority);
+        assert_eq!(state.pending_authority, Pubkey::default());
+
+        // A rotation nobody accepts lapses with the acceptance window.
+        let unaccepted = pool.wallet();
+        rotate(&mut pool, new_authority, unaccepted);
+        let expires_at = pool.config().authority_proposal_expires_at();
+        pool.runtime.warp_to_timestamp(expires_at);
+        let failure = accept(&mut pool, unaccepted).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::ProposalExpired));
+        assert_eq!(pool.config().authority, new_authority);
+    }
 
-#[event]
-pub struct AuthorityProposalCancelled {
-    pub authority: Pubkey,
-    pub cancelled_authority: Pubkey,
-}
+    #[test]
+    fn admin_actions_cancel_competing_proposals_and_lapse() {
+        let mut pool = Pool::new();
+        let config = pda(&[b"config"]);
+        let policy = pda(&[b"policy"]);
+        let enqueue = |pool: &mut Pool, action: AdminAction| {
+            let index = pool.config().admin_action_count;
+            let admin_action = pda(&[b"admin_action", &index.to_le_bytes()]);
+            pool.send(
+                accounts::EnqueueAdminAction {
+                    config,
+                    admin_action,
+                    policy,
+                    authority: pool.authority,
+                    payer: pool.authority,
+                    system_program: system_program::ID,
+                },
+                instruction::EnqueueAdminAction { action },
+            )
+            .unwrap();
+            admin_action
+        };
+        let execute = |pool: &mut Pool, admin_action: Pubkey, executor: Pubkey| {
+            pool.send(
+                accounts::ExecuteAdminAction {
+                    config,
+                    admin_action,
+                    payer: pool.authority,
+                    policy,
+                    executor,
+                },
+                instruction::ExecuteAdminAction {},
+            )
+        };
+        let executor = pool.wallet();
+
+        // Executing a fee change drops the competing proposal instead of letting it revert.
+        let set_fee = enqueue(&mut pool, AdminAction::SetFee { fee_bps: 100 });
+        pool.send(
+            pool.propose_config_change_accounts(),
+            instruction::ProposeFeeChange { new_fee_bps: 200 },
+        )
+        .unwrap();
+        pool.wait_for_delay();
+        let outcome = execute(&mut pool, set_fee, executor).unwrap();
+        let cancelled = events::<FeeChangeCancelled>(&outcome);
+        assert_eq!(cancelled[0].cancelled_fee_bps, 200);
+        let state = pool.config();
+        assert_eq!(state.fee_bps, 100);
+        assert_eq!(state.pending_fee_effective_at, 0);
+
+        // Unpausing through the queue credits whoever executed it.
+        pool.send(
+            accounts::SetPause {
+                config,
+                signer: pool.authority,
+            },
+            instruction::Pause { flags: PAUSE_CLAIM },
+        )
+        .unwrap();
+        let unpause = enqueue(&mut pool, AdminAction::Unpause { flags: PAUSE_CLAIM });
+        pool.wait_for_delay();
+        let outcome = execute(&mut pool, unpause, executor).unwrap();
+        let unpaused = events::<ProgramUnpaused>(&outcome);
+        assert_eq!(unpaused[0].unpaused_by, executor);
+        assert_eq!(unpaused[0].paused, 0);
+
+        // An action left past its expiry can only be cancelled, even after its eta.
+        let set_treasury = enqueue(
+            &mut pool,
+            AdminAction::SetTreasury {
+                treasury: Pubkey::new_unique(),
+            },
+        );
+        let expires_at = pool.state::<QueuedAdminAction>(&set_treasury).expires_at;
+        assert_eq!(
+            expires_at,
+            pool.now() + pool.config().authority_delay_seconds + pool.config().acceptance_window()
+        );
+        pool.runtime.warp_to_timestamp(expires_at);
+        let failure = execute(&mut pool, set_treasury, executor).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::AdminActionExpired));
+        pool.send(
+            accounts::CancelAdminAction {
+                config,
+                admin_action: set_treasury,
+                payer: pool.authority,
+                authority: pool.authority,
+            },
+            instruction::CancelAdminAction {},
+        )
+        .unwrap();
+        assert_eq!(pool.runtime.lamports(&set_treasury), 0);
+
+        // Rotating the key cancels a pending authority proposal.
+        let proposed = pool.wallet();
+        pool.send(
+            accounts::ProposeAuthority {
+                config,
+                authority: pool.authority,
+            },
+            instruction::ProposeAuthority {
+                new_authority: proposed,
+            },
+        )
+        .unwrap();
+        let rotated = pool.wallet();
+        let rotate = enqueue(
+            &mut pool,
+            AdminAction::RotateKey {
+                new_authority: rotated,
+            },
+        );
+        pool.wait_for_delay();
+        let outcome = execute(&mut pool, rotate, executor).unwrap();
+        let cancelled = events::<AuthorityProposalCancelled>(&outcome);
+        assert_eq!(cancelled[0].cancelled_authority, proposed);
+        assert_eq!(pool.config().pending_authority, rotated);
+
+        // Actions queued by a previous authority lapse once the key changes hands.
+        let stale = enqueue(&mut pool, AdminAction::SetFee { fee_bps: 50 });
+        pool.send(
+            accounts::AcceptAuthority {
+                config,
+                pending_authority: rotated,
+            },
+            instruction::AcceptAuthority {},
+        )
+        .unwrap();
+        pool.wait_for_delay();
+        let failure = execute(&mut pool, stale, executor).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::AdminActionAuthorityChanged)
+        );
+    }
 
-#[event]
-pub struct AuthorityAccepted {
-    pub previous_authority: Pubkey,
-    pub new_authority: Pubkey,
-}
+    #[test]
+    fn transfer_hook_mints_run_their_hook_on_deposit_and_claim() {
+        let mut pool = Pool::new();
+        let hook_program = Pubkey::new_unique();
+        let mint = pool.hook_mint(&hook_program);
+        let hook_accounts = vec![
+            AccountMeta::new_readonly(hook_program, false),
+            AccountMeta::new_readonly(get_extra_account_metas_address(&mint, &hook_program), false),
+        ];
+        let treasury = pool.treasury;
+        let treasury_tokens = pool.token_account(&mint, &treasury, 0);
+
+        let depositor = pool.wallet();
+        let source = pool.token_account(&mint, &depositor, 500);
+        let nullifier = drop_nullifier(&SECRET);
+        let outcome = pool
+            .send_with(
+                pool.token_deposit_accounts(&nullifier, depositor, mint, source),
+                instruction::DepositPool {
+                    nullifier,
+                    recipient: depositor,
+                    amount: 500,
+                    asset_type: ASSET_TYPE_TOKEN,
+                    expires_at: 0,
+                    claim_policy: ClaimPolicy::Open,
+                },
+                hook_accounts.clone(),
+            )
+            .unwrap();
+        assert_eq!(outcome.hook_calls.len(), 1);
+        assert_eq!(
+            (outcome.hook_calls[0].mint, outcome.hook_calls[0].amount),
+            (mint, 500)
+        );
 
-#[event]
-pub struct AuthorityDelayUpdated {
-    pub authority: Pubkey,
-    pub new_delay_seconds: i64,
-}
+        // Token-2022 cannot resolve the hook without its accounts.
+        let claimer = pool.wallet();
+        let claimer_tokens = pool.token_account(&mint, &claimer, 0);
+        let failure = pool
+            .send(
+                pool.token_claim_accounts(&SECRET, claimer, mint, claimer_tokens, treasury_tokens),
+                instruction::ClaimDrop { secret: SECRET },
+            )
+            .unwrap_err();
+        assert_eq!(
+            failure.error,
+            ProgramError::from(TransferHookError::IncorrectAccount)
+        );
 
-#[error_code]
-pub enum DarkPoolError {
-    #[msg("This nullifier has already been used")]
-    NullifierAlreadyUsed,
+        let outcome = pool
+            .send_with(
+                pool.token_claim_accounts(&SECRET, claimer, mint, claimer_tokens, treasury_tokens),
+                instruction::ClaimDrop { secret: SECRET },
+                hook_accounts,
+            )
+            .unwrap();
+        assert_eq!(outcome.hook_calls.len(), 1);
+        assert_eq!(outcome.hook_calls[0].program_id, hook_program);
+        assert_eq!(pool.token_balance(&claimer_tokens), 500);
+        assert_eq!(pool.token_ledger(&mint).outstanding_liabilities, 0);
+    }
 
-    #[msg("Drop is not active")]
-    DropNotActive,
+    #[test]
+    fn migrate_config_is_authority_only_and_seeds_liabilities() {
+        let mut pool = Pool::new();
+        let authority = pool.authority;
+        let config = pda(&[b"config"]);
+        let data = baseline_account::<Config>(baseline_config(authority), Config::LEGACY_LENS[0]);
+        let rent = Rent::default().minimum_balance(data.len());
+        pool.runtime
+            .set_account(config, TestAccount::new(rent, data, ID));
+        let migrate = |pool: &mut Pool, payer: Pubkey| {
+            pool.send(
+                accounts::MigrateAccount {
+                    account: config,
+                    payer,
+                    system_program: system_program::ID,
+                },
+                instruction::MigrateConfig {
+                    outstanding_liabilities: 5 * LAMPORTS,
+                },
+            )
+        };
 
-    #[msg("Invalid nullifier")]
-    InvalidNullifier,
+        let stranger = pool.wallet();
+        let failure = migrate(&mut pool, stranger).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::UnauthorizedCreator)
+        );
 
-    #[msg("Amount must be greater than zero")]
-    InvalidAmount,
+        migrate(&mut pool, authority).unwrap();
+        let state = pool.config();
+        assert_eq!(state.authority, authority);
+        assert_eq!(state.fee_bps, 250);
+        assert_eq!(state.outstanding_liabilities, 5 * LAMPORTS);
+        assert_eq!(state.version, Config::VERSION);
+        assert_eq!(
+            pool.runtime.account(&config).unwrap().data.len(),
+            8 + Config::LEN
+        );
 
-    #[msg("Asset type is not supported")]
-    InvalidAssetType,
+        let failure = migrate(&mut pool, authority).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::AccountAlreadyMigrated)
+        );
+    }
 
-    #[msg("Recipient must differ from payer")]
-    InvalidRecipient,
+    #[test]
+    fn migrated_drops_claim_with_their_raw_key_and_new_drops_do_not() {
+        let mut pool = Pool::new();
+        let raw = [5u8; 32];
+        let (drop, drop_bump) = Pubkey::find_program_address(&[b"drop", &raw], &ID);
+        let (nullifier_account, nullifier_bump) =
+            Pubkey::find_program_address(&[b"nullifier", &raw], &ID);
+        let baseline = [
+            (
+                drop,
+                baseline_account::<DropAccount>(
+                    BaselineDropAccount {
+                        nullifier: raw,
+                        recipient: Pubkey::new_unique(),
+                        amount: LAMPORTS,
+                        asset_type: ASSET_TYPE_SOL,
+                        status: DropStatus::Active,
+                        expires_at: 0,
+                        created_at: pool.now(),
+                        claimed_at: 0,
+                        claimer: Pubkey::default(),
+                        bump: drop_bump,
+                    },
+                    DropAccount::LEGACY_LENS[0],
+                ),
+            ),
+            (
+                nullifier_account,
+                baseline_account::<NullifierAccount>(
+                    BaselineNullifierAccount {
+                        nullifier: raw,
+                        is_used: false,
+                        claimer: Pubkey::default(),
+                        used_at: 0,
+                        bump: nullifier_bump,
+                    },
+                    NullifierAccount::LEGACY_LENS[0],
+                ),
+            ),
+        ];
+        for (key, data) in baseline {
+            let rent = Rent::default().minimum_balance(data.len());
+            pool.runtime
+                .set_account(key, TestAccount::new(rent, data, ID));
+        }
+        pool.runtime.airdrop(&pda(&[b"sol_vault"]), LAMPORTS);
+        // `migrate_config` seeds the liability owed to drops created before it was tracked.
+        let config = pda(&[b"config"]);
+        let mut config_state = pool.config();
+        config_state.outstanding_liabilities += LAMPORTS;
+        let mut config_data = Vec::new();
+        config_state.try_serialize(&mut config_data).unwrap();
+        let config_rent = pool.runtime.lamports(&config);
+        pool.runtime
+            .set_account(config, TestAccount::new(config_rent, config_data, ID));
+
+        let payer = pool.wallet();
+        let migrate_accounts = |account| accounts::MigrateAccount {
+            account,
+            payer,
+            system_program: system_program::ID,
+        };
+        pool.send(migrate_accounts(drop), instruction::MigrateDrop {})
+            .unwrap();
+        pool.send(
+            migrate_accounts(nullifier_account),
+            instruction::MigrateNullifier {},
+        )
+        .unwrap();
+        assert!(pool.state::<DropAccount>(&drop).legacy_seed);
+
+        let claimer = pool.wallet();
+        pool.send(
+            accounts::ClaimDrop {
+                drop,
+                nullifier_account,
+                ..pool.claim_drop_accounts(&raw, claimer)
+            },
+            instruction::ClaimDrop { secret: raw },
+        )
+        .unwrap();
+        assert_eq!(pool.runtime.lamports(&claimer), 11 * LAMPORTS);
+        assert!(pool.state::<NullifierAccount>(&nullifier_account).is_used);
+
+        // A new drop's key is public, so passing it as the secret must not open the drop.
+        pool.create_sol_drop(&SECRET, LAMPORTS).unwrap();
+        let nullifier = drop_nullifier(&SECRET);
+        let failure = pool
+            .send(
+                accounts::ClaimDrop {
+                    drop: pda(&[b"drop", &nullifier]),
+                    nullifier_account: pda(&[b"nullifier", &nullifier]),
+                    ..pool.claim_drop_accounts(&nullifier, claimer)
+                },
+                instruction::ClaimDrop { secret: nullifier },
+            )
+            .unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidNullifier));
+    }
 
-    #[msg("Expiration timestamp is invalid")]
-    InvalidExpiration,
+    #[test]
+    fn fee_changes_are_rechecked_against_the_policy_cap_when_applied() {
+        let mut pool = Pool::new();
+        pool.send(
+            pool.propose_config_change_accounts(),
+            instruction::ProposeFeeChange { new_fee_bps: 500 },
+        )
+        .unwrap();
+        pool.send(
+            pool.propose_config_change_accounts(),
+            instruction::ProposeFeeSchedule {
+                asset_type: ASSET_TYPE_SOL,
+                schedule: FeeSchedule {
+                    bps: 400,
+                    enabled: true,
+                    ..FeeSchedule::default()
+                },
+            },
+        )
+        .unwrap();
+        // The cap drops below both pending changes before they can be applied.
+        pool.set_policy(PolicyValues {
+            max_fee_bps: 300,
+            ..PolicyValues::default()
+        })
+        .unwrap();
+
+        let failure = pool
+            .send(
+                pool.apply_fee_change_accounts(),
+                instruction::ApplyFeeChange {},
+            )
+            .unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidFeeBps));
+        let failure = pool
+            .send(
+                pool.apply_fee_change_accounts(),
+                instruction::ApplyFeeSchedule {},
+            )
+            .unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidFeeBps));
+        assert_eq!(pool.config().fee_bps, 0);
+        assert!(!pool.config().sol_fee_schedule.enabled);
+
+        pool.send(
+            pool.propose_config_change_accounts(),
+            instruction::ProposeFeeChange { new_fee_bps: 300 },
+        )
+        .unwrap();
+        pool.wait_for_delay();
+        pool.send(
+            pool.apply_fee_change_accounts(),
+            instruction::ApplyFeeChange {},
+        )
+        .unwrap();
+        assert_eq!(pool.config().fee_bps, 300);
+    }
 
-    #[msg("Program config is not initialized")]
-    ConfigNotInitialized,
+    #[test]
+    fn pending_changes_keep_the_delay_they_were_proposed_with() {
+        let mut pool = Pool::new();
+        let config = pda(&[b"config"]);
+        let new_treasury = Pubkey::new_unique();
+        pool.send(
+            pool.propose_config_change_accounts(),
+            instruction::ProposeFeeChange { new_fee_bps: 300 },
+        )
+        .unwrap();
+        pool.send(
+            pool.propose_config_change_accounts(),
+            instruction::ProposeTreasuryChange { new_treasury },
+        )
+        .unwrap();
+        let treasury_effective_at = pool.config().pending_treasury_effective_at;
+        pool.send(
+            accounts::UpdateAuthorityDelay {
+                config,
+                authority: pool.authority,
+            },
+            instruction::UpdateAuthorityDelay {
+                new_delay_seconds: MIN_AUTHORITY_DELAY_SECONDS,
+            },
+        )
+        .unwrap();
+
+        // Shortening the delay does not bring forward changes already pending.
+        pool.wait_for_delay();
+        let failure = pool
+            .send(
+                pool.apply_fee_change_accounts(),
+                instruction::ApplyFeeChange {},
+            )
+            .unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::AuthorityDelayNotElapsed)
+        );
+        let failure = pool
+            .send(
+                accounts::ApplyConfigChange { config },
+                instruction::ApplyTreasuryChange {},
+            )
+            .unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::AuthorityDelayNotElapsed)
+        );
 
-    #[msg("Caller is not authorized to create drops")]
-    UnauthorizedCreator,
+        // A replacement reports the value it drops and runs on the delay now in force.
+        let outcome = pool
+            .send(
+                pool.propose_config_change_accounts(),
+                instruction::ProposeFeeChange { new_fee_bps: 200 },
+            )
+            .unwrap();
+        let cancelled = events::<FeeChangeCancelled>(&outcome);
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].cancelled_fee_bps, 300);
+        let proposed = events::<FeeChangeProposed>(&outcome);
+        assert_eq!(
+            proposed[0].effective_at,
+            pool.now() + MIN_AUTHORITY_DELAY_SECONDS
+        );
+        pool.wait_for_delay();
+        pool.send(
+            pool.apply_fee_change_accounts(),
+            instruction::ApplyFeeChange {},
+        )
+        .unwrap();
+        assert_eq!(pool.config().fee_bps, 200);
+
+        pool.runtime.warp_to_timestamp(treasury_effective_at);
+        pool.send(
+            accounts::ApplyConfigChange { config },
+            instruction::ApplyTreasuryChange {},
+        )
+        .unwrap();
+        assert_eq!(pool.config().treasury, new_treasury);
+    }
 
-    #[msg("Create drop rate limit exceeded")]
-    RateLimitExceeded,
+    #[test]
+    fn policy_updates_cannot_lower_the_cap_below_fees_in_force() {
+        let mut pool = Pool::new();
+        pool.send(
+            pool.propose_config_change_accounts(),
+            instruction::ProposeFeeSchedule {
+                asset_type: ASSET_TYPE_TOKEN,
+                schedule: FeeSchedule {
+                    bps: 400,
+                    enabled: true,
+                    ..FeeSchedule::default()
+                },
+            },
+        )
+        .unwrap();
+        pool.wait_for_delay();
+        pool.send(
+            pool.apply_fee_change_accounts(),
+            instruction::ApplyFeeSchedule {},
+        )
+        .unwrap();
+
+        let lowered = PolicyValues {
+            max_fee_bps: 300,
+            ..PolicyValues::default()
+        };
+        let failure = pool.set_policy(lowered).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidFeeBps));
+        assert_eq!(
+            pool.state::<PolicyParams>(&pda(&[b"policy"])).values,
+            PolicyValues::default()
+        );
 
-    #[msg("Drop has expired")]
-    DropExpired,
+        // Once the schedule is back under the new cap the update goes through.
+        pool.send(
+            pool.propose_config_change_accounts(),
+            instruction::ProposeFeeSchedule {
+                asset_type: ASSET_TYPE_TOKEN,
+                schedule: FeeSchedule {
+                    bps: 300,
+                    enabled: true,
+                    ..FeeSchedule::default()
+                },
+            },
+        )
+        .unwrap();
+        pool.wait_for_delay();
+        pool.send(
+            pool.apply_fee_change_accounts(),
+            instruction::ApplyFeeSchedule {},
+        )
+        .unwrap();
+        pool.set_policy(lowered).unwrap();
+        assert_eq!(
+            pool.state::<PolicyParams>(&pda(&[b"policy"])).values,
+            lowered
+        );
+    }
 
-    #[msg("Authority cannot be the default public key")]
-    InvalidAuthority,
+    #[test]
+    fn recipient_signer_deposits_pay_only_the_recipient() {
+        let mut pool = Pool::new();
+        let recipient = pool.wallet();
+
+        // A recipient-bound drop must name its recipient.
+        let failure = pool
+            .deposit_sol(&SECRET, Pubkey::default(), LAMPORTS, ClaimPolicy::RecipientSigner)
+            .unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidRecipient));
+
+        pool.deposit_sol(&SECRET, recipient, LAMPORTS, ClaimPolicy::RecipientSigner)
+            .unwrap();
+        let drop_state: DropAccount = pool.state(&pda(&[b"drop", &drop_nullifier(&SECRET)]));
+        assert_eq!(drop_state.recipient, recipient);
+
+        let stranger = pool.wallet();
+        let failure = pool
+            .send(
+                pool.claim_drop_accounts(&SECRET, stranger),
+                instruction::ClaimDrop { secret: SECRET },
+            )
+            .unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::RecipientMismatch)
+        );
+        pool.send(
+            pool.claim_drop_accounts(&SECRET, recipient),
+            instruction::ClaimDrop { secret: SECRET },
+        )
+        .unwrap();
+        assert_eq!(pool.runtime.lamports(&recipient), 11 * LAMPORTS);
+    }
 
-    #[msg("A pending authority already exists")]
-    PendingAuthorityExists,
+    #[test]
+    fn recipient_signature_deposits_need_the_recipients_ed25519_instruction() {
+        let mut pool = Pool::new();
+        let recipient = Pubkey::new_unique();
+        pool.deposit_sol(&SECRET, recipient, LAMPORTS, ClaimPolicy::RecipientSignature)
+            .unwrap();
+        let drop = pda(&[b"drop", &drop_nullifier(&SECRET)]);
+        let claimer = pool.wallet();
+        let claim = |pool: &mut Pool| {
+            pool.send(
+                accounts::ClaimDrop {
+                    instructions_sysvar: Some(sysvar_instructions::ID),
+                    ..pool.claim_drop_accounts(&SECRET, claimer)
+                },
+                instruction::ClaimDrop { secret: SECRET },
+            )
+        };
+        let message = [drop.as_ref(), claimer.as_ref()].concat();
+
+        let failure = pool
+            .send(
+                pool.claim_drop_accounts(&SECRET, claimer),
+                instruction::ClaimDrop { secret: SECRET },
+            )
+            .unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::MissingRecipientSignature)
+        );
 
-    #[msg("No pending authority to process")]
-    NoPendingAuthority,
+        // A signature by anyone else, or over another claimer, does not authorize the claim.
+        let stranger = Pubkey::new_unique();
+        pool.set_ed25519_instruction(&ed25519_ix_data(&stranger, &message, u16::MAX));
+        let failure = claim(&mut pool).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::MissingRecipientSignature)
+        );
+        let other_claimer = [drop.as_ref(), stranger.as_ref()].concat();
+        pool.set_ed25519_instruction(&ed25519_ix_data(&recipient, &other_claimer, u16::MAX));
+        let failure = claim(&mut pool).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::MissingRecipientSignature)
+        );
 
-    #[msg("Authority delay has not elapsed")]
-    AuthorityDelayNotElapsed,
+        pool.set_ed25519_instruction(&ed25519_ix_data(&recipient, &message, u16::MAX));
+        claim(&mut pool).unwrap();
+        assert_eq!(pool.runtime.lamports(&claimer), 11 * LAMPORTS);
+    }
 
-    #[msg("Authority delay is out of bounds")]
-    InvalidAuthorityDelay,
+    #[test]
+    fn vesting_drops_pay_the_grantee_and_lock_cancellation_at_the_cliff() {
+        let mut pool = Pool::new();
+        let grantee = pool.wallet();
+        let start = pool.now();
+        let schedule = VestingSchedule {
+            cliff_at: start + 100,
+            vest_start: start,
+            vest_end: start + 1_000,
+        };
+        let deposit = |pool: &mut Pool, secret: &[u8; 32]| {
+            let depositor = pool.wallet();
+            let nullifier = drop_nullifier(secret);
+            pool.send(
+                pool.deposit_accounts(&nullifier, depositor),
+                instruction::DepositVesting {
+                    nullifier,
+                    amount: LAMPORTS,
+                    asset_type: ASSET_TYPE_SOL,
+                    expires_at: 0,
+                    schedule,
+                    grantee,
+                },
+            )
+            .unwrap();
+            (depositor, nullifier)
+        };
+        let cancel = |pool: &mut Pool, depositor: Pubkey, nullifier: [u8; 32]| {
+            pool.send(
+                accounts::CancelDrop {
+                    funder: Some(depositor),
+                    ..pool.cancel_accounts(&nullifier, depositor)
+                },
+                instruction::CancelDrop { nullifier },
+            )
+        };
 
-    #[msg("Drop has not yet expired")]
-    DropNotExpired,
+        // Before the cliff nothing has vested and the funder may take the drop back.
+        let (early_depositor, early) = deposit(&mut pool, &[7u8; 32]);
+        cancel(&mut pool, early_depositor, early).unwrap();
+        // Only the rate-limit and nullifier accounts, which stay open, are not returned.
+        let kept_rent = pool
+            .runtime
+            .lamports(&pda(&[b"rate_limit", early_depositor.as_ref()]))
+            + pool.runtime.lamports(&pda(&[b"nullifier", &early]));
+        assert_eq!(
+            pool.runtime.lamports(&early_depositor),
+            10 * LAMPORTS - kept_rent
+        );
 
-    #[msg("Invalid fee bps")]
-    InvalidFeeBps,
+        let (depositor, nullifier) = deposit(&mut pool, &SECRET);
+        let drop_state: DropAccount = pool.state(&pda(&[b"drop", &nullifier]));
+        assert_eq!(drop_state.recipient, grantee);
+        assert_eq!(drop_state.claimer, Pubkey::default());
+        assert!(drop_state.claim_policy == ClaimPolicy::RecipientSigner);
+
+        pool.runtime.warp_to_timestamp(start + 500);
+        let failure = cancel(&mut pool, depositor, nullifier).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::VestingStarted));
+
+        let stranger = pool.wallet();
+        let failure = pool
+            .send(
+                pool.claim_drop_accounts(&SECRET, stranger),
+                instruction::ClaimDrop { secret: SECRET },
+            )
+            .unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::RecipientMismatch)
+        );
+        pool.send(
+            pool.claim_drop_accounts(&SECRET, grantee),
+            instruction::ClaimDrop { secret: SECRET },
+        )
+        .unwrap();
+        assert_eq!(pool.runtime.lamports(&grantee), 10 * LAMPORTS + LAMPORTS / 2);
+        assert_eq!(pool.config().outstanding_liabilities, LAMPORTS / 2);
+    }
 
-    #[msg("Invalid treasury address")]
-    InvalidTreasury,
+    #[test]
+    fn role_creator_cancels_its_own_drop() {
+        let mut pool = Pool::new();
+        let creator = pool.wallet();
+        let other = pool.wallet();
+        pool.add_creator(creator);
+        pool.add_creator(other);
+
+        let amount = LAMPORTS;
+        let sol_vault = pda(&[b"sol_vault"]);
+        pool.runtime.airdrop(&sol_vault, amount);
+        let nullifier = drop_nullifier(&SECRET);
+        let drop = pda(&[b"drop", &nullifier]);
+        pool.send(
+            accounts::CreateDrop {
+                creator_role: Some(pda(&[b"creator_role", creator.as_ref()])),
+                ..pool.create_drop_accounts(&nullifier, creator)
+            },
+            instruction::CreateDrop {
+                nullifier,
+                recipient: Pubkey::new_unique(),
+                amount,
+                asset_type: ASSET_TYPE_SOL,
+                expires_at: pool.expires_at(),
+                claim_policy: ClaimPolicy::Open,
+            },
+        )
+        .unwrap();
+        let drop_state: DropAccount = pool.state(&drop);
+        assert_eq!(drop_state.funder, creator);
+        assert!(!drop_state.escrowed);
+
+        let failure = pool
+            .send(
+                accounts::CancelDrop {
+                    funder: Some(creator),
+                    ..pool.cancel_accounts(&nullifier, other)
+                },
+                instruction::CancelDrop { nullifier },
+            )
+            .unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::UnauthorizedCanceller)
+        );
+        let failure = pool
+            .send(
+                pool.cancel_accounts(&nullifier, creator),
+                instruction::CancelDrop { nullifier },
+            )
+            .unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidFunder));
+
+        let creator_before = pool.runtime.lamports(&creator);
+        let drop_rent = pool.runtime.lamports(&drop);
+        let vault_before = pool.runtime.lamports(&sol_vault);
+        pool.send(
+            accounts::CancelDrop {
+                funder: Some(creator),
+                ..pool.cancel_accounts(&nullifier, creator)
+            },
+            instruction::CancelDrop { nullifier },
+        )
+        .unwrap();
+        // Only the rent comes back: the drop drew on vault surplus, which stays put.
+        assert_eq!(pool.runtime.lamports(&creator), creator_before + drop_rent);
+        assert_eq!(pool.runtime.lamports(&drop), 0);
+        assert_eq!(pool.runtime.lamports(&sol_vault), vault_before);
+        assert_eq!(pool.config().outstanding_liabilities, 0);
+    }
 
-    #[msg("Insufficient vault balance")]
-    InsufficientVaultBalance,
+    #[test]
+    fn partner_shares_accrue_in_the_vault_until_withdrawn() {
+        let mut pool = Pool::new();
+        pool.set_fee_bps(1_000);
+
+        let wallet = pool.wallet();
+        let partner = pda(&[b"partner", wallet.as_ref()]);
+        let register = |pool: &mut Pool, authority: Pubkey, share_bps: u16| {
+            pool.send(
+                accounts::RegisterPartner {
+                    partner,
+                    config: pda(&[b"config"]),
+                    authority,
+                    payer: authority,
+                    system_program: system_program::ID,
+                },
+                instruction::RegisterPartner { wallet, share_bps },
+            )
+        };
+        let authority = pool.authority;
+        let stranger = pool.wallet();
+        let failure = register(&mut pool, stranger, 5_000).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::UnauthorizedCreator));
+        let failure = register(&mut pool, authority, 10_001).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidPartnerShare));
+        register(&mut pool, authority, 5_000).unwrap();
+        let partner_rent = pool.runtime.lamports(&partner);
+
+        let referred_claim = |pool: &mut Pool, secret: &[u8; 32]| {
+            pool.runtime.airdrop(&pda(&[b"sol_vault"]), LAMPORTS);
+            let nullifier = drop_nullifier(secret);
+            let expires_at = pool.expires_at();
+            pool.send(
+                accounts::CreateDrop {
+                    referrer_partner: Some(partner),
+                    ..pool.create_drop_accounts(&nullifier, pool.authority)
+                },
+                instruction::CreateDrop {
+                    nullifier,
+                    recipient: Pubkey::new_unique(),
+                    amount: LAMPORTS,
+                    asset_type: ASSET_TYPE_SOL,
+                    expires_at,
+                    claim_policy: ClaimPolicy::Open,
+                },
+            )
+            .unwrap();
+            let claimer = pool.wallet();
+            pool.send(
+                accounts::ClaimDrop {
+                    partner: Some(partner),
+                    ..pool.claim_drop_accounts(secret, claimer)
+                },
+                instruction::ClaimDrop { secret: *secret },
+            )
+            .unwrap()
+        };
+        // A 10% fee on 1 SOL, split evenly with the partner.
+        let fee = LAMPORTS / 10;
+        let share = fee / 2;
+        let outcome = referred_claim(&mut pool, &SECRET);
+        let paid = events::<PartnerPaid>(&outcome);
+        assert_eq!(paid.len(), 1);
+        assert_eq!(paid[0].wallet, wallet);
+        assert_eq!(paid[0].nullifier, drop_nullifier(&SECRET));
+        assert_eq!(paid[0].asset_type, ASSET_TYPE_SOL);
+        assert_eq!(paid[0].amount, share);
+
+        // The share stays in the vault and counts against it.
+        assert_eq!(pool.runtime.lamports(&partner), partner_rent);
+        let partner_state: Partner = pool.state(&partner);
+        assert_eq!(partner_state.sol_earned, share);
+        assert_eq!(partner_state.sol_accrued, share);
+        assert_eq!(pool.config().accrued_fees, fee - share);
+        assert_eq!(pool.config().partner_accrued_fees, share);
+        let solvency = pool
+            .send(
+                accounts::CheckSolvency {
+                    config: pda(&[b"config"]),
+                    sol_vault: pda(&[b"sol_vault"]),
+                },
+                instruction::CheckSolvency {},
+            )
+            .unwrap();
+        assert_eq!(
+            solvency.return_data.unwrap().1,
+            0i64.to_le_bytes().to_vec()
+        );
 
-    #[msg("Account is already initialized")]
-    AccountAlreadyInitialized,
+        let withdraw = |pool: &mut Pool, signer: Pubkey| {
+            pool.send(
+                accounts::WithdrawPartnerEarnings {
+                    partner,
+                    config: pda(&[b"config"]),
+                    sol_vault: pda(&[b"sol_vault"]),
+                    wallet: signer,
+                    system_program: system_program::ID,
+                },
+                instruction::WithdrawPartnerEarnings {},
+            )
+        };
+        let failure = withdraw(&mut pool, stranger).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidPartner));
+        let outcome = withdraw(&mut pool, wallet).unwrap();
+        let withdrawn = events::<PartnerEarningsWithdrawn>(&outcome);
+        assert_eq!(withdrawn.len(), 1);
+        assert_eq!(withdrawn[0].amount, share);
+        assert_eq!(pool.runtime.lamports(&wallet), 10 * LAMPORTS + share);
+        assert_eq!(pool.state::<Partner>(&partner).sol_accrued, 0);
+        assert_eq!(pool.config().partner_accrued_fees, 0);
+        let failure = withdraw(&mut pool, wallet).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidAmount));
+
+        // Removing the partner pays out what it has accrued since, along with its rent.
+        pool.wait_for_delay();
+        referred_claim(&mut pool, &[7u8; 32]);
+        pool.send(
+            accounts::RemovePartner {
+                partner,
+                config: pda(&[b"config"]),
+                authority: pool.authority,
+                wallet,
+                sol_vault: pda(&[b"sol_vault"]),
+                system_program: system_program::ID,
+            },
+            instruction::RemovePartner {},
+        )
+        .unwrap();
+        assert_eq!(
+            pool.runtime.lamports(&wallet),
+            10 * LAMPORTS + 2 * share + partner_rent
+        );
+        assert_eq!(pool.runtime.lamports(&partner), 0);
+        assert_eq!(pool.config().partner_accrued_fees, 0);
+    }
 
-    #[msg("Numerical overflow")]
-    NumericalOverflow,
+    #[test]
+    fn sol_drops_claim_into_a_shielded_note() {
+        let mut pool = Pool::new();
+        let (shielded_config, shielded_tree, shielded_vault) = (
+            pda(&[b"shielded_config"]),
+            pda(&[b"shielded_tree"]),
+            pda(&[b"shielded_vault"]),
+        );
+        pool.send(
+            accounts::InitializeShielded {
+                shielded_config,
+                authority: pool.authority,
+                shielded_vault,
+                shielded_tree,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeShielded { tree_depth: 4 },
+        )
+        .unwrap();
+        pool.create_sol_drop(&SECRET, LAMPORTS).unwrap();
+
+        let claimer = pool.wallet();
+        let nullifier = drop_nullifier(&SECRET);
+        let shield = |pool: &mut Pool, commitment: [u8; 32]| {
+            pool.send(
+                accounts::ClaimDropToShielded {
+                    drop: pda(&[b"drop", &nullifier]),
+                    nullifier_account: pda(&[b"nullifier", &nullifier]),
+                    claimer,
+                    config: pda(&[b"config"]),
+                    sol_vault: pda(&[b"sol_vault"]),
+                    shielded_config,
+                    shielded_tree,
+                    shielded_vault,
+                    system_program: system_program::ID,
+                    instructions_sysvar: None,
+                    partner: None,
+                },
+                instruction::ClaimDropToShielded {
+                    secret: SECRET,
+                    commitment,
+                },
+            )
+        };
+        let failure = shield(&mut pool, [0u8; 32]).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidCommitment));
+
+        let vault_before = pool.runtime.lamports(&shielded_vault);
+        let root_before = pool.state::<ShieldedConfig>(&shielded_config).current_root;
+        let outcome = shield(&mut pool, [9u8; 32]).unwrap();
+        let deposits = events::<ShieldedDeposit>(&outcome);
+        assert_eq!(deposits.len(), 1);
+        assert_eq!(deposits[0].commitment, [9u8; 32]);
+        assert_eq!(deposits[0].amount, LAMPORTS);
+        assert_eq!(deposits[0].leaf_index, 0);
+        // The claimer only pays the transaction; the note holds the payout.
+        assert_eq!(events::<DropClaimed>(&outcome)[0].claimer, Pubkey::default());
+        assert_eq!(pool.runtime.lamports(&claimer), 10 * LAMPORTS);
+        assert_eq!(
+            pool.runtime.lamports(&shielded_vault),
+            vault_before + LAMPORTS
+        );
+        let shielded: ShieldedConfig = pool.state(&shielded_config);
+        assert_eq!(shielded.next_leaf_index, 1);
+        assert_eq!(shielded.current_root, deposits[0].new_root);
+        assert_ne!(shielded.current_root, root_before);
+        assert_eq!(pool.config().outstanding_liabilities, 0);
+
+        let replay = shield(&mut pool, [9u8; 32]).unwrap_err();
+        assert_eq!(replay.error, custom_error(DarkPoolError::DropNotActive));
+    }
 
-    #[msg("Shielded config is not initialized")]
-    ShieldedConfigNotInitialized,
+    #[test]
+    fn expired_deposits_refund_their_funder() {
+        let mut pool = Pool::new();
+        let depositor = pool.wallet();
+        let nullifier = drop_nullifier(&SECRET);
+        let expires_at = pool.expires_at();
+        pool.send(
+            pool.deposit_accounts(&nullifier, depositor),
+            instruction::DepositPool {
+                nullifier,
+                recipient: depositor,
+                amount: LAMPORTS,
+                asset_type: ASSET_TYPE_SOL,
+                expires_at,
+                claim_policy: ClaimPolicy::Open,
+            },
+        )
+        .unwrap();
+        let deposited = pool.runtime.lamports(&depositor);
+        let rent_collector = Pubkey::new_unique();
+        let expire = |pool: &mut Pool| {
+            pool.send(
+                accounts::ExpireDrop {
+                    drop: pda(&[b"drop", &nullifier]),
+                    nullifier_account: pda(&[b"nullifier", &nullifier]),
+                    config: pda(&[b"config"]),
+                    authority: pool.authority,
+                    rent_collector,
+                    sol_vault: pda(&[b"sol_vault"]),
+                    system_program: system_program::ID,
+                    funder: Some(depositor),
+                    mint: None,
+                    token_vault: None,
+                    funder_token_account: None,
+                    token_program: None,
+                    token_ledger: None,
+                },
+                instruction::ExpireDrop { nullifier },
+            )
+        };
+        let failure = expire(&mut pool).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::DropNotExpired));
+
+        pool.runtime.warp_to_timestamp(expires_at + 1);
+        let rent = pool.runtime.lamports(&pda(&[b"drop", &nullifier]))
+            + pool.runtime.lamports(&pda(&[b"nullifier", &nullifier]));
+        let outcome = expire(&mut pool).unwrap();
+        let expired = events::<DropExpired>(&outcome);
+        assert_eq!(expired[0].funder, depositor);
+        assert_eq!(expired[0].refunded, LAMPORTS);
+        // The depositor paid both accounts' rent, so it gets that back with the escrow.
+        assert_eq!(pool.runtime.lamports(&depositor), deposited + LAMPORTS + rent);
+        assert_eq!(pool.config().outstanding_liabilities, 0);
+        assert_eq!(pool.runtime.lamports(&rent_collector), 0);
+        assert_eq!(pool.runtime.lamports(&pda(&[b"drop", &nullifier])), 0);
+    }
 
-    #[msg("Invalid shielded tree depth")]
-    InvalidTreeDepth,
+    #[test]
+    fn commit_reveal_drops_are_claimed_only_by_a_later_reveal() {
+        let mut pool = Pool::new();
+        pool.deposit_sol(
+            &SECRET,
+            Pubkey::new_unique(),
+            LAMPORTS,
+            ClaimPolicy::CommitReveal,
+        )
+        .unwrap();
+        let claimer = pool.wallet();
+        let drop = pda(&[b"drop", &drop_nullifier(&SECRET)]);
+        let claim_commitment = pda(&[b"claim_commit", drop.as_ref(), claimer.as_ref()]);
+        pool.send(
+            accounts::CommitClaim {
+                drop,
+                claim_commitment,
+                claimer,
+                system_program: system_program::ID,
+            },
+            instruction::CommitClaim {
+                commitment: claim_commitment_hash(&SECRET, &claimer),
+            },
+        )
+        .unwrap();
+
+        let failure = pool
+            .send(
+                pool.claim_drop_accounts(&SECRET, claimer),
+                instruction::ClaimDrop { secret: SECRET },
+            )
+            .unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::CommitRevealRequired)
+        );
+        let reveal = |pool: &mut Pool, secret: [u8; 32]| {
+            pool.send(
+                accounts::ClaimDrop {
+                    claim_commitment: Some(claim_commitment),
+                    ..pool.claim_drop_accounts(&SECRET, claimer)
+                },
+                instruction::RevealClaim { secret },
+            )
+        };
+        let failure = reveal(&mut pool, SECRET).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::RevealTooEarly));
+
+        // Each warp advances the slot.
+        let later = pool.now() + 1;
+        pool.runtime.warp_to_timestamp(later);
+        let failure = reveal(&mut pool, [1u8; 32]).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::InvalidClaimCommitment)
+        );
+        let commitment_rent = pool.runtime.lamports(&claim_commitment);
+        let before = pool.runtime.lamports(&claimer);
+        reveal(&mut pool, SECRET).unwrap();
+        assert_eq!(
+            pool.runtime.lamports(&claimer),
+            before + LAMPORTS + commitment_rent
+        );
+        assert_eq!(pool.runtime.lamports(&claim_commitment), 0);
+        assert_eq!(pool.config().outstanding_liabilities, 0);
+    }
 
-    #[msg("Shielded tree is full")]
-    ShieldedTreeFull,
+    #[test]
+    fn claim_fees_stay_in_the_vault_until_swept() {
+        let mut pool = Pool::new();
+        pool.set_fee_bps(1_000);
+        let sweep = |pool: &mut Pool, treasury: Pubkey| {
+            pool.send(
+                accounts::SweepFees {
+                    config: pda(&[b"config"]),
+                    sol_vault: pda(&[b"sol_vault"]),
+                    treasury,
+                    system_program: system_program::ID,
+                },
+                instruction::SweepFees {},
+            )
+        };
+        let treasury = pool.treasury;
+        let failure = sweep(&mut pool, treasury).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::NoFeesAccrued));
+
+        pool.create_sol_drop(&SECRET, LAMPORTS).unwrap();
+        let claimer = pool.wallet();
+        pool.send(
+            pool.claim_drop_accounts(&SECRET, claimer),
+            instruction::ClaimDrop { secret: SECRET },
+        )
+        .unwrap();
+        let fee = LAMPORTS / 10;
+        assert_eq!(pool.runtime.lamports(&claimer), 10 * LAMPORTS + LAMPORTS - fee);
+        assert_eq!(pool.runtime.lamports(&treasury), 0);
+        assert_eq!(pool.config().accrued_fees, fee);
+
+        let failure = sweep(&mut pool, Pubkey::new_unique()).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidTreasury));
+        let outcome = sweep(&mut pool, treasury).unwrap();
+        assert_eq!(events::<FeesSwept>(&outcome)[0].amount, fee);
+        assert_eq!(pool.runtime.lamports(&treasury), fee);
+        assert_eq!(pool.config().accrued_fees, 0);
+        let failure = sweep(&mut pool, treasury).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::NoFeesAccrued));
+    }
 
-    #[msg("Invalid shielded commitment")]
-    InvalidCommitment,
+    #[test]
+    fn guardian_pauses_claims_and_only_the_authority_resumes_them() {
+        let mut pool = Pool::new();
+        let config = pda(&[b"config"]);
+        let guardian = pool.wallet();
+        pool.send(
+            accounts::SetPauseGuardian {
+                config,
+                authority: pool.authority,
+            },
+            instruction::SetPauseGuardian { guardian },
+        )
+        .unwrap();
+        let stranger = pool.wallet();
+        let failure = pool
+            .send(
+                accounts::SetPause {
+                    config,
+                    signer: stranger,
+                },
+                instruction::Pause { flags: PAUSE_CLAIM },
+            )
+            .unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::UnauthorizedPauser));
+        pool.send(
+            accounts::SetPause {
+                config,
+                signer: guardian,
+            },
+            instruction::Pause { flags: PAUSE_CLAIM },
+        )
+        .unwrap();
+
+        // Only the paused group stops.
+        pool.create_sol_drop(&SECRET, LAMPORTS).unwrap();
+        let claimer = pool.wallet();
+        let failure = pool
+            .send(
+                pool.claim_drop_accounts(&SECRET, claimer),
+                instruction::ClaimDrop { secret: SECRET },
+            )
+            .unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::ProgramPaused));
+
+        let failure = pool
+            .send(
+                accounts::SetPause {
+                    config,
+                    signer: guardian,
+                },
+                instruction::Unpause { flags: PAUSE_CLAIM },
+            )
+            .unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::UnauthorizedCreator));
+        pool.send(
+            accounts::SetPause {
+                config,
+                signer: pool.authority,
+            },
+            instruction::Unpause { flags: PAUSE_CLAIM },
+        )
+        .unwrap();
+        assert_eq!(pool.config().paused, 0);
+        pool.send(
+            pool.claim_drop_accounts(&SECRET, claimer),
+            instruction::ClaimDrop { secret: SECRET },
+        )
+        .unwrap();
+    }
 
-    #[msg("Shielded state mismatch")]
-    ShieldedStateMismatch,
+    #[test]
+    fn outflow_breaker_trips_past_the_limit_until_reset() {
+        let mut pool = Pool::new();
+        let secrets = [[1u8; 32], [2u8; 32], [3u8; 32]];
+        for secret in &secrets {
+            pool.create_sol_drop(secret, LAMPORTS).unwrap();
+            let later = pool.now() + MIN_RATE_LIMIT_SECONDS;
+            pool.runtime.warp_to_timestamp(later);
+        }
+        let set_limit = |pool: &mut Pool, window_seconds: i64, limit: u64| {
+            pool.send(
+                accounts::ConfigureOutflowLimit {
+                    config: pda(&[b"config"]),
+                    authority: pool.authority,
+                },
+                instruction::SetOutflowLimit {
+                    window_seconds,
+                    limit,
+                },
+            )
+        };
+        let failure = set_limit(&mut pool, 0, LAMPORTS).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::InvalidOutflowLimit));
+        set_limit(&mut pool, 60 * 60, LAMPORTS + LAMPORTS / 2).unwrap();
+
+        let claimer = pool.wallet();
+        let claim = |pool: &mut Pool, secret: &[u8; 32]| {
+            pool.send(
+                pool.claim_drop_accounts(secret, claimer),
+                instruction::ClaimDrop { secret: *secret },
+            )
+        };
+        let outcome = claim(&mut pool, &secrets[0]).unwrap();
+        assert!(events::<OutflowLimitTripped>(&outcome).is_empty());
+        // The claim that crosses the limit still completes.
+        let outcome = claim(&mut pool, &secrets[1]).unwrap();
+        let tripped = events::<OutflowLimitTripped>(&outcome);
+        assert_eq!(tripped[0].outflow, 2 * LAMPORTS);
+        assert!(pool.config().outflow_tripped);
+        let failure = claim(&mut pool, &secrets[2]).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::OutflowLimitTripped));
+
+        pool.send(
+            accounts::ConfigureOutflowLimit {
+                config: pda(&[b"config"]),
+                authority: pool.authority,
+            },
+            instruction::ResetOutflowBreaker {},
+        )
+        .unwrap();
+        assert_eq!(pool.config().outflow_in_window, 0);
+        claim(&mut pool, &secrets[2]).unwrap();
+        assert_eq!(pool.runtime.lamports(&claimer), 13 * LAMPORTS);
+    }
 
-    #[msg("Shielded root mismatch")]
-    ShieldedRootMismatch,
+    #[test]
+    fn unaccepted_authority_proposals_expire_and_can_be_cleared() {
+        let mut pool = Pool::new();
+        let config = pda(&[b"config"]);
+        let window = |pool: &mut Pool, new_window_seconds: i64| {
+            pool.send(
+                accounts::UpdateAuthorityDelay {
+                    config,
+                    authority: pool.authority,
+                },
+                instruction::UpdateAcceptanceWindow { new_window_seconds },
+            )
+        };
+        let failure = window(&mut pool, MIN_ACCEPTANCE_WINDOW_SECONDS - 1).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::InvalidAcceptanceWindow)
+        );
+        window(&mut pool, MIN_ACCEPTANCE_WINDOW_SECONDS).unwrap();
 
-    #[msg("Recipient amount must cover rent exemption when creating a new account")]
-    ShieldedRecipientNotRentExempt,
+        let propose = |pool: &mut Pool, new_authority: Pubkey| {
+            pool.send(
+                accounts::ProposeAuthority {
+                    config,
+                    authority: pool.authority,
+                },
+                instruction::ProposeAuthority { new_authority },
+            )
+        };
+        let clear = |pool: &mut Pool| {
+            pool.send(
+                accounts::ApplyConfigChange { config },
+                instruction::ClearExpiredProposal {},
+            )
+        };
+        let new_authority = pool.wallet();
+        propose(&mut pool, new_authority).unwrap();
+        let expires_at = pool.config().authority_proposal_expires_at();
+        assert_eq!(
+            expires_at,
+            pool.now() + pool.config().authority_delay_seconds + MIN_ACCEPTANCE_WINDOW_SECONDS
+        );
+        let failure = clear(&mut pool).unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::ProposalNotExpired));
+        // Widening the window afterwards does not extend a proposal already made.
+        window(&mut pool, 2 * MIN_ACCEPTANCE_WINDOW_SECONDS).unwrap();
+        assert_eq!(pool.config().authority_proposal_expires_at(), expires_at);
+
+        pool.runtime.warp_to_timestamp(expires_at);
+        let failure = pool
+            .send(
+                accounts::AcceptAuthority {
+                    config,
+                    pending_authority: new_authority,
+                },
+                instruction::AcceptAuthority {},
+            )
+            .unwrap_err();
+        assert_eq!(failure.error, custom_error(DarkPoolError::ProposalExpired));
+        let outcome = clear(&mut pool).unwrap();
+        let expired = events::<AuthorityProposalExpired>(&outcome);
+        assert_eq!(expired[0].expired_authority, new_authority);
+        assert_eq!(expired[0].expired_at, expires_at);
+        assert_eq!(pool.config().pending_authority, Pubkey::default());
+
+        // The authority is free to propose again.
+        propose(&mut pool, new_authority).unwrap();
+    }
 
-    #[msg("Invalid shielded proof")]
-    InvalidShieldedProof,
+    #[test]
+    fn creator_roles_must_cap_what_they_draw_from_the_vault() {
+        let mut pool = Pool::new();
+        let creator = pool.wallet();
+        let creator_role = pda(&[b"creator_role", creator.as_ref()]);
+        let add = |pool: &mut Pool, quota: u64| {
+            pool.send(
+                accounts::AddCreatorRole {
+                    creator_role,
+                    config: pda(&[b"config"]),
+                    authority: pool.authority,
+                    payer: pool.authority,
+                    system_program: system_program::ID,
+                },
+                instruction::AddCreatorRole { creator, quota },
+            )
+        };
+        let set_limits = |pool: &mut Pool, limits: QuotaLimits| {
+            pool.send(
+                accounts::SetCreatorRoleLimits {
+                    creator_role,
+                    config: pda(&[b"config"]),
+                    authority: pool.authority,
+                },
+                instruction::SetCreatorRoleLimits { limits },
+            )
+        };
+        let failure = add(&mut pool, 0).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::UnboundedCreatorRole)
+        );
+        add(&mut pool, 5).unwrap();
+
+        // A drop-count window does not cap lamports; an amount cap stands in for the quota.
+        set_limits(
+            &mut pool,
+            QuotaLimits {
+                window_seconds: 60 * 60,
+                max_drops: 10,
+                max_amount: 0,
+            },
+        )
+        .unwrap();
+        let failure = add(&mut pool, 0).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::UnboundedCreatorRole)
+        );
+        set_limits(
+            &mut pool,
+            QuotaLimits {
+                window_seconds: 60 * 60,
+                max_drops: 0,
+                max_amount: 2 * LAMPORTS,
+            },
+        )
+        .unwrap();
+        add(&mut pool, 0).unwrap();
+        let failure = set_limits(&mut pool, QuotaLimits::default()).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::UnboundedCreatorRole)
+        );
+        assert_eq!(
+            pool.state::<CreatorRole>(&creator_role).limits.max_amount,
+            2 * LAMPORTS
+        );
+    }
 
-    #[msg("Poseidon hash failed")]
-    PoseidonHashFailed,
-}
+    #[test]
+    fn executed_multisig_transactions_return_their_rent_to_the_proposer() {
+        let mut pool = Pool::new();
+        let (proposer, approver) = (pool.wallet(), pool.wallet());
+        let multisig = pda(&[b"multisig"]);
+        let signer = pda(&[b"multisig_signer", multisig.as_ref()]);
+        pool.send(
+            accounts::CreateMultisig {
+                multisig,
+                config: pda(&[b"config"]),
+                authority: pool.authority,
+                payer: pool.authority,
+                system_program: system_program::ID,
+            },
+            instruction::CreateMultisig {
+                members: vec![proposer, approver],
+                threshold: 2,
+            },
+        )
+        .unwrap();
+
+        let transaction = pda(&[b"multisig_tx", multisig.as_ref(), &0u64.to_le_bytes()]);
+        let set_members = accounts::SetMultisigMembers {
+            multisig,
+            multisig_signer: signer,
+        };
+        let metas = set_members.to_account_metas(None);
+        pool.send(
+            accounts::ProposeMultisigTransaction {
+                multisig,
+                transaction,
+                proposer,
+                system_program: system_program::ID,
+            },
+            instruction::ProposeMultisigTransaction {
+                accounts: metas
+                    .iter()
+                    .map(|meta| MultisigAccountMeta {
+                        pubkey: meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                    .collect(),
+                data: instruction::SetMultisigMembers {
+                    members: vec![proposer, approver],
+                    threshold: 1,
+                }
+                .data(),
+            },
+        )
+        .unwrap();
+        let rent = pool.runtime.lamports(&transaction);
+        assert_eq!(pool.runtime.lamports(&proposer), 10 * LAMPORTS - rent);
+
+        let execute = |pool: &mut Pool, rent_receiver: Pubkey| {
+            let mut metas = accounts::ExecuteMultisigTransaction {
+                multisig,
+                transaction,
+                multisig_signer: signer,
+                proposer: rent_receiver,
+            }
+            .to_account_metas(None);
+            metas.push(AccountMeta::new(multisig, false));
+            metas.push(AccountMeta::new_readonly(signer, false));
+            metas.push(AccountMeta::new_readonly(ID, false));
+            pool.runtime.process(&Instruction {
+                program_id: ID,
+                accounts: metas,
+                data: instruction::ExecuteMultisigTransaction {}.data(),
+            })
+        };
+        let failure = execute(&mut pool, proposer).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::MultisigThresholdNotMet)
+        );
+        pool.send(
+            accounts::ApproveMultisigTransaction {
+                multisig,
+                transaction,
+                member: approver,
+            },
+            instruction::ApproveMultisigTransaction {},
+        )
+        .unwrap();
+        let failure = execute(&mut pool, approver).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::InvalidMultisigProposer)
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        execute(&mut pool, proposer).unwrap();
+        assert_eq!(pool.state::<Multisig>(&multisig).threshold, 1);
+        assert_eq!(pool.runtime.lamports(&transaction), 0);
+        assert_eq!(pool.runtime.lamports(&proposer), 10 * LAMPORTS);
+    }
+
+    #[test]
+    fn withdrawn_or_stale_multisig_transactions_return_their_rent_to_the_proposer() {
+        let mut pool = Pool::new();
+        let (proposer, approver) = (pool.wallet(), pool.wallet());
+        let multisig = pda(&[b"multisig"]);
+        let signer = pda(&[b"multisig_signer", multisig.as_ref()]);
+        pool.send(
+            accounts::CreateMultisig {
+                multisig,
+                config: pda(&[b"config"]),
+                authority: pool.authority,
+                payer: pool.authority,
+                system_program: system_program::ID,
+            },
+            instruction::CreateMultisig {
+                members: vec![proposer, approver],
+                threshold: 2,
+            },
+        )
+        .unwrap();
+        let propose = |pool: &mut Pool, threshold: u8| {
+            let index = pool.state::<Multisig>(&multisig).transaction_count;
+            let transaction = pda(&[b"multisig_tx", multisig.as_ref(), &index.to_le_bytes()]);
+            let metas = accounts::SetMultisigMembers {
+                multisig,
+                multisig_signer: signer,
+            }
+            .to_account_metas(None);
+            pool.send(
+                accounts::ProposeMultisigTransaction {
+                    multisig,
+                    transaction,
+                    proposer,
+                    system_program: system_program::ID,
+                },
+                instruction::ProposeMultisigTransaction {
+                    accounts: metas
+                        .iter()
+                        .map(|meta| MultisigAccountMeta {
+                            pubkey: meta.pubkey,
+                            is_signer: meta.is_signer,
+                            is_writable: meta.is_writable,
+                        })
+                        .collect(),
+                    data: instruction::SetMultisigMembers {
+                        members: vec![proposer, approver],
+                        threshold,
+                    }
+                    .data(),
+                },
+            )
+            .unwrap();
+            transaction
+        };
+        let close = |pool: &mut Pool, transaction: Pubkey, closer: Pubkey| {
+            pool.send(
+                accounts::CloseMultisigTransaction {
+                    multisig,
+                    transaction,
+                    proposer,
+                    closer,
+                },
+                instruction::CloseMultisigTransaction {},
+            )
+        };
+
+        // The proposer can withdraw a live transaction; other members cannot close it.
+        let withdrawn = propose(&mut pool, 1);
+        let failure = close(&mut pool, withdrawn, approver).unwrap_err();
+        assert_eq!(
+            failure.error,
+            custom_error(DarkPoolError::MultisigTransactionActive)
+        );
+        let outcome = close(&mut pool, withdrawn, proposer).unwrap();
+        let closed = events::<MultisigTransactionClosed>(&outcome);
+        assert_eq!(closed[0].closed_by, proposer);
+        assert_eq!(pool.runtime.lamports(&withdrawn), 0);
+        assert_eq!(pool.runtime.lamports(&proposer), 10 * LAMPORTS);
+
+        // Once the member set changes, anyone can close what it left behind.
+        let executed = propose(&mut pool, 1);
+        let stale = propose(&mut pool, 2);
+        pool.send(
+            accounts::ApproveMultisigTransaction {
+                multisig,
+                transaction: executed,
+                member: approver,
+            },
+            instruction::ApproveMultisigTransaction {},
+        )
+        .unwrap();
+        let mut metas = accounts::ExecuteMultisigTransaction {
+            multisig,
+            transaction: executed,
+            multisig_signer: signer,
+            proposer,
+        }
+        .to_account_metas(None);
+        metas.push(AccountMeta::new(multisig, false));
+        metas.push(AccountMeta::new_readonly(signer, false));
+        metas.push(AccountMeta::new_readonly(ID, false));
+        pool.runtime
+            .process(&Instruction {
+                program_id: ID,
+                accounts: metas,
+                data: instruction::ExecuteMultisigTransaction {}.data(),
+            })
+            .unwrap();
+        let stranger = pool.wallet();
+        close(&mut pool, stale, stranger).unwrap();
+        assert_eq!(pool.runtime.lamports(&stale), 0);
+        assert_eq!(pool.runtime.lamports(&proposer), 10 * LAMPORTS);
+    }
 }
@@ -0,0 +1,683 @@
+//! A small in-process Solana runtime for instruction tests.
+//!
+//! Programs run natively against account buffers laid out the way the SBF loader
+//! serializes them, so `AccountInfo::realloc` and `assign` behave as on chain. The
+//! syscall stubs supply `Clock` and `Rent`, capture logs, events and return data, and
+//! route CPIs to the emulated system and token programs or to registered programs.
+//! A failed instruction leaves every account untouched.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Once;
+
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE};
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction::{SystemError, SystemInstruction};
+use solana_program::{bpf_loader, program_utils::limited_deserialize, system_program};
+use spl_token_2022::extension::transfer_fee::{TransferFeeAmount, TransferFeeConfig};
+use spl_token_2022::extension::transfer_hook::{self, TransferHookAccount};
+use spl_token_2022::extension::{
+    BaseStateWithExtensions, BaseStateWithExtensionsMut, ExtensionType, StateWithExtensions,
+    StateWithExtensionsMut,
+};
+use spl_token_2022::instruction::TokenInstruction;
+use spl_token_2022::state::{Account as TokenAccount, AccountState, Mint};
+
+pub use spl_tlv_account_resolution;
+pub use spl_token_2022;
+pub use spl_transfer_hook_interface;
+
+/// The original SPL Token program; its instructions are emulated like Token-2022's.
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Native entrypoint of a registered program.
+pub type ProcessInstruction = fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Account {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+impl Account {
+    pub fn new(lamports: u64, data: Vec<u8>, owner: Pubkey) -> Self {
+        Self {
+            lamports,
+            data,
+            owner,
+            executable: false,
+        }
+    }
+}
+
+/// A transfer-hook execution requested by the emulated token program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HookCall {
+    pub program_id: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// Accounts forwarded after source, mint, destination and authority.
+    pub extra_accounts: Vec<Pubkey>,
+}
+
+/// What a successful instruction produced.
+#[derive(Clone, Debug, Default)]
+pub struct Outcome {
+    pub logs: Vec<String>,
+    /// `sol_log_data` payloads, i.e. anchor events, in emission order.
+    pub events: Vec<Vec<u8>>,
+    pub return_data: Option<(Pubkey, Vec<u8>)>,
+    pub hook_calls: Vec<HookCall>,
+}
+
+/// A failed instruction and the logs it wrote before failing.
+pub struct Failure {
+    pub error: ProgramError,
+    pub logs: Vec<String>,
+}
+
+impl fmt::Debug for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:?}", self.error)?;
+        for log in &self.logs {
+            writeln!(f, "  {}", log)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Runtime {
+    accounts: HashMap<Pubkey, Account>,
+    programs: HashMap<Pubkey, ProcessInstruction>,
+    pub clock: Clock,
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runtime {
+    pub fn new() -> Self {
+        install_stubs();
+        let mut runtime = Self {
+            accounts: HashMap::new(),
+            programs: HashMap::new(),
+            clock: Clock {
+                slot: 1,
+                unix_timestamp: 1_700_000_000,
+                ..Clock::default()
+            },
+        };
+        for program_id in [system_program::ID, spl_token_2022::ID, TOKEN_PROGRAM_ID] {
+            runtime.add_executable(program_id);
+        }
+        runtime
+    }
+
+    /// Registers a native program and its executable account.
+    pub fn add_program(&mut self, program_id: Pubkey, process: ProcessInstruction) {
+        self.programs.insert(program_id, process);
+        self.add_executable(program_id);
+    }
+
+    fn add_executable(&mut self, program_id: Pubkey) {
+        self.accounts.insert(
+            program_id,
+            Account {
+                lamports: 1,
+                data: Vec::new(),
+                owner: bpf_loader::ID,
+                executable: true,
+            },
+        );
+    }
+
+    pub fn set_account(&mut self, key: Pubkey, account: Account) {
+        self.accounts.insert(key, account);
+    }
+
+    pub fn account(&self, key: &Pubkey) -> Option<&Account> {
+        self.accounts.get(key)
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.accounts.get(key).map_or(0, |account| account.lamports)
+    }
+
+    /// Adds lamports to a system account, creating it if needed.
+    pub fn airdrop(&mut self, key: &Pubkey, lamports: u64) {
+        let account = self.accounts.entry(*key).or_insert_with(|| Account {
+            owner: system_program::ID,
+            ..Account::default()
+        });
+        account.lamports += lamports;
+    }
+
+    pub fn warp_to_timestamp(&mut self, unix_timestamp: i64) {
+        self.clock.unix_timestamp = unix_timestamp;
+        self.clock.slot += 1;
+    }
+
+    /// Runs one top-level instruction, committing account changes only on success.
+    pub fn process(&mut self, instruction: &Instruction) -> Result<Outcome, Failure> {
+        CONTEXT.with(|context| {
+            *context.borrow_mut() = InvokeContext {
+                programs: self.programs.clone(),
+                clock: self.clock.clone(),
+                ..InvokeContext::default()
+            }
+        });
+
+        let mut keys: Vec<Pubkey> = Vec::new();
+        for meta in &instruction.accounts {
+            if !keys.contains(&meta.pubkey) {
+                keys.push(meta.pubkey);
+            }
+        }
+        let mut slots: Vec<Slot> = keys
+            .iter()
+            .map(|key| {
+                let account = self.accounts.get(key).cloned().unwrap_or(Account {
+                    owner: system_program::ID,
+                    ..Account::default()
+                });
+                Slot::new(*key, account)
+            })
+            .collect();
+        let before: Vec<Account> = slots.iter().map(Slot::account).collect();
+
+        let result = match self.programs.get(&instruction.program_id) {
+            Some(process) => invoke_top_level(*process, instruction, &mut slots),
+            None => Err(ProgramError::IncorrectProgramId),
+        };
+        let after: Vec<Account> = slots.iter().map(Slot::account).collect();
+        let result = result.and_then(|()| check_changes(instruction, &keys, &before, &after));
+
+        let context = CONTEXT.with(|context| std::mem::take(&mut *context.borrow_mut()));
+        match result {
+            Ok(()) => {
+                for (key, account) in keys.into_iter().zip(after) {
+                    self.accounts.insert(key, account);
+                }
+                Ok(Outcome {
+                    logs: context.logs,
+                    events: context.events,
+                    return_data: context.return_data,
+                    hook_calls: context.hook_calls,
+                })
+            }
+            Err(error) => Err(Failure {
+                error,
+                logs: context.logs,
+            }),
+        }
+    }
+}
+
+/// Enforces the runtime's post-instruction rules that the tests rely on.
+fn check_changes(
+    instruction: &Instruction,
+    keys: &[Pubkey],
+    before: &[Account],
+    after: &[Account],
+) -> ProgramResult {
+    let total = |accounts: &[Account]| -> u128 {
+        accounts
+            .iter()
+            .map(|account| account.lamports as u128)
+            .sum()
+    };
+    if total(before) != total(after) {
+        return Err(ProgramError::Custom(UNBALANCED_INSTRUCTION));
+    }
+    for (index, key) in keys.iter().enumerate() {
+        let writable = instruction
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == *key && meta.is_writable);
+        if !writable && before[index] != after[index] {
+            return Err(ProgramError::Custom(READONLY_MODIFIED));
+        }
+    }
+    Ok(())
+}
+
+/// Error code for an instruction that created or destroyed lamports.
+pub const UNBALANCED_INSTRUCTION: u32 = 0x7fff_0001;
+/// Error code for a change to an account the instruction did not mark writable.
+pub const READONLY_MODIFIED: u32 = 0x7fff_0002;
+
+#[repr(C)]
+struct KeyCell {
+    _padding: u32,
+    // Read by `AccountInfo::realloc` from the four bytes before the key.
+    original_data_len: u32,
+    key: Pubkey,
+}
+
+/// Account memory in the loader's layout: the data length sits in the eight bytes
+/// before the data, followed by room to grow.
+struct Slot {
+    key: Box<KeyCell>,
+    lamports: Box<u64>,
+    owner: Box<Pubkey>,
+    buffer: Vec<u8>,
+    executable: bool,
+}
+
+impl Slot {
+    fn new(key: Pubkey, account: Account) -> Self {
+        let len = account.data.len();
+        let mut buffer = vec![0u8; 8 + len + MAX_PERMITTED_DATA_INCREASE];
+        buffer[..8].copy_from_slice(&(len as u64).to_le_bytes());
+        buffer[8..8 + len].copy_from_slice(&account.data);
+        Self {
+            key: Box::new(KeyCell {
+                _padding: 0,
+                original_data_len: len as u32,
+                key,
+            }),
+            lamports: Box::new(account.lamports),
+            owner: Box::new(account.owner),
+            buffer,
+            executable: account.executable,
+        }
+    }
+
+    fn data_len(&self) -> usize {
+        let mut len = [0u8; 8];
+        len.copy_from_slice(&self.buffer[..8]);
+        u64::from_le_bytes(len) as usize
+    }
+
+    fn account(&self) -> Account {
+        let len = self.data_len();
+        // `AccountInfo::assign` writes the owner through a shared reference.
+        let owner = unsafe { std::ptr::read_volatile(&*self.owner) };
+        Account {
+            lamports: *self.lamports,
+            data: self.buffer[8..8 + len].to_vec(),
+            owner,
+            executable: self.executable,
+        }
+    }
+}
+
+fn invoke_top_level(
+    process: ProcessInstruction,
+    instruction: &Instruction,
+    slots: &mut [Slot],
+) -> ProgramResult {
+    let mut unique: Vec<AccountInfo> = Vec::with_capacity(slots.len());
+    for slot in slots.iter_mut() {
+        let len = slot.data_len();
+        let Slot {
+            key,
+            lamports,
+            owner,
+            buffer,
+            executable,
+        } = slot;
+        let key = &key.key;
+        let is_signer = instruction
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == *key && meta.is_signer);
+        let is_writable = instruction
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == *key && meta.is_writable);
+        unique.push(AccountInfo::new(
+            key,
+            is_signer,
+            is_writable,
+            lamports,
+            &mut buffer[8..8 + len],
+            owner,
+            *executable,
+            0,
+        ));
+    }
+    let infos: Vec<AccountInfo> = instruction
+        .accounts
+        .iter()
+        .map(|meta| {
+            unique
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .cloned()
+                .expect("every meta has a slot")
+        })
+        .collect();
+    with_program(instruction.program_id, || {
+        process(&instruction.program_id, &infos, &instruction.data)
+    })
+}
+
+fn with_program(program_id: Pubkey, run: impl FnOnce() -> ProgramResult) -> ProgramResult {
+    CONTEXT.with(|context| context.borrow_mut().program_stack.push(program_id));
+    let result = run();
+    CONTEXT.with(|context| context.borrow_mut().program_stack.pop());
+    result
+}
+
+#[derive(Default)]
+struct InvokeContext {
+    programs: HashMap<Pubkey, ProcessInstruction>,
+    clock: Clock,
+    program_stack: Vec<Pubkey>,
+    logs: Vec<String>,
+    events: Vec<Vec<u8>>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+    hook_calls: Vec<HookCall>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<InvokeContext> = RefCell::new(InvokeContext::default());
+}
+
+fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(Stubs));
+    });
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        CONTEXT.with(|context| context.borrow_mut().logs.push(message.to_string()));
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        CONTEXT.with(|context| context.borrow_mut().events.push(fields.concat()));
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = CONTEXT.with(|context| context.borrow().clock.clone());
+        unsafe { std::ptr::write_unaligned(var_addr as *mut Clock, clock) };
+        solana_program::entrypoint::SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { std::ptr::write_unaligned(var_addr as *mut Rent, Rent::default()) };
+        solana_program::entrypoint::SUCCESS
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        CONTEXT.with(|context| context.borrow().return_data.clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        CONTEXT.with(|context| {
+            let mut context = context.borrow_mut();
+            let program_id = context.program_stack.last().copied().unwrap_or_default();
+            context.return_data = Some((program_id, data.to_vec()));
+        });
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        CONTEXT.with(|context| context.borrow().program_stack.len() as u64)
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = CONTEXT
+            .with(|context| context.borrow().program_stack.last().copied())
+            .ok_or(ProgramError::InvalidArgument)?;
+        let signed_pdas = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        let mut callee_infos = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let mut info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .cloned()
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if meta.is_signer && !info.is_signer && !signed_pdas.contains(info.key) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if meta.is_writable && !info.is_writable {
+                return Err(ProgramError::Custom(READONLY_MODIFIED));
+            }
+            info.is_signer = meta.is_signer;
+            info.is_writable = meta.is_writable;
+            callee_infos.push(info);
+        }
+
+        let program_id = instruction.program_id;
+        if program_id == system_program::ID {
+            return process_system(instruction, &callee_infos);
+        }
+        if program_id == spl_token_2022::ID || program_id == TOKEN_PROGRAM_ID {
+            return process_token(&program_id, instruction, &callee_infos);
+        }
+        let process = CONTEXT
+            .with(|context| context.borrow().programs.get(&program_id).copied())
+            .ok_or(ProgramError::IncorrectProgramId)?;
+        with_program(program_id, || {
+            process(&program_id, &callee_infos, &instruction.data)
+        })
+    }
+}
+
+fn debit(from: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // The system program only moves lamports out of plain wallets.
+    if *from.owner != system_program::ID || !from.data_is_empty() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let balance = from.lamports();
+    if balance < lamports {
+        return Err(ProgramError::Custom(
+            SystemError::ResultWithNegativeLamports as u32,
+        ));
+    }
+    **from.try_borrow_mut_lamports()? = balance - lamports;
+    Ok(())
+}
+
+fn credit(to: &AccountInfo, lamports: u64) -> ProgramResult {
+    let balance = to.lamports();
+    **to.try_borrow_mut_lamports()? = balance
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+fn process_system(instruction: &Instruction, infos: &[AccountInfo]) -> ProgramResult {
+    let system_instruction: SystemInstruction = limited_deserialize(&instruction.data, 1232)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    match system_instruction {
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => {
+            let (from, to) = (&infos[0], &infos[1]);
+            if !to.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if to.lamports() > 0 || !to.data_is_empty() || *to.owner != system_program::ID {
+                return Err(ProgramError::Custom(
+                    SystemError::AccountAlreadyInUse as u32,
+                ));
+            }
+            debit(from, lamports)?;
+            credit(to, lamports)?;
+            to.realloc(space as usize, true)?;
+            to.assign(&owner);
+            Ok(())
+        }
+        SystemInstruction::Transfer { lamports } => {
+            debit(&infos[0], lamports)?;
+            credit(&infos[1], lamports)
+        }
+        SystemInstruction::Assign { owner } => {
+            if !infos[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            infos[0].assign(&owner);
+            Ok(())
+        }
+        SystemInstruction::Allocate { space } => {
+            if !infos[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            infos[0].realloc(space as usize, true)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn process_token(
+    program_id: &Pubkey,
+    instruction: &Instruction,
+    infos: &[AccountInfo],
+) -> ProgramResult {
+    for info in infos.iter().take(3) {
+        if info.owner != program_id && info.data_len() > 0 {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+    }
+    match TokenInstruction::unpack(&instruction.data)? {
+        TokenInstruction::InitializeAccount3 { owner } => {
+            initialize_token_account(&infos[0], &infos[1], owner)
+        }
+        TokenInstruction::TransferChecked { amount, decimals } => {
+            transfer_tokens(infos, amount, decimals)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn initialize_token_account(
+    account: &AccountInfo,
+    mint: &AccountInfo,
+    owner: Pubkey,
+) -> ProgramResult {
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    let required =
+        ExtensionType::get_required_init_account_extensions(&mint_state.get_extension_types()?);
+    let mut data = account.try_borrow_mut_data()?;
+    let mut state = StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data)?;
+    for extension in required {
+        match extension {
+            ExtensionType::TransferFeeAmount => {
+                state.init_extension::<TransferFeeAmount>(true)?;
+            }
+            ExtensionType::TransferHookAccount => {
+                state.init_extension::<TransferHookAccount>(true)?;
+            }
+            _ => return Err(ProgramError::InvalidAccountData),
+        }
+    }
+    state.base = TokenAccount {
+        mint: *mint.key,
+        owner,
+        state: AccountState::Initialized,
+        ..TokenAccount::default()
+    };
+    state.pack_base();
+    state.init_account_type()?;
+    Ok(())
+}
+
+fn transfer_tokens(infos: &[AccountInfo], amount: u64, decimals: u8) -> ProgramResult {
+    let (source, mint, destination, authority) = (&infos[0], &infos[1], &infos[2], &infos[3]);
+    let (fee, hook_program) = {
+        let mint_data = mint.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+        if mint_state.base.decimals != decimals {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let epoch = CONTEXT.with(|context| context.borrow().clock.epoch);
+        let fee = match mint_state.get_extension::<TransferFeeConfig>() {
+            Ok(config) => config
+                .calculate_epoch_fee(epoch, amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+            Err(_) => 0,
+        };
+        (fee, transfer_hook::get_program_id(&mint_state))
+    };
+    {
+        let mut source_data = source.try_borrow_mut_data()?;
+        let mut source_state = StateWithExtensionsMut::<TokenAccount>::unpack(&mut source_data)?;
+        if source_state.base.mint != *mint.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if source_state.base.owner != *authority.key || !authority.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        source_state.base.amount = source_state
+            .base
+            .amount
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        source_state.pack_base();
+    }
+    {
+        let mut destination_data = destination.try_borrow_mut_data()?;
+        let mut destination_state =
+            StateWithExtensionsMut::<TokenAccount>::unpack(&mut destination_data)?;
+        if destination_state.base.mint != *mint.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        destination_state.base.amount = destination_state
+            .base
+            .amount
+            .checked_add(amount - fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        destination_state.pack_base();
+        if fee > 0 {
+            let withheld = destination_state.get_extension_mut::<TransferFeeAmount>()?;
+            let total = u64::from(withheld.withheld_amount) + fee;
+            withheld.withheld_amount = total.into();
+        }
+    }
+    if let Some(program_id) = hook_program {
+        // Token-2022 resolves the hook program and its validation account from the
+        // accounts passed after the authority; without them the transfer fails.
+        let validation =
+            spl_transfer_hook_interface::get_extra_account_metas_address(mint.key, &program_id);
+        let forwarded = &infos[4..];
+        if !forwarded.iter().any(|info| *info.key == program_id)
+            || !forwarded.iter().any(|info| *info.key == validation)
+        {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        CONTEXT.with(|context| {
+            context.borrow_mut().hook_calls.push(HookCall {
+                program_id,
+                mint: *mint.key,
+                amount,
+                extra_accounts: infos[4..].iter().map(|info| *info.key).collect(),
+            })
+        });
+    }
+    Ok(())
+}
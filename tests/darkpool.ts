@@ -24,20 +24,26 @@ describe("darkpool", () => {
 
   let configPDA: PublicKey;
   let solVaultPDA: PublicKey;
+  let policyPDA: PublicKey;
+  let secret: Uint8Array;
   let nullifier: Uint8Array;
   let dropPDA: PublicKey;
   let nullifierPDA: PublicKey;
 
   before(async () => {
-    // Generate a test nullifier
-    nullifier = new Uint8Array(32);
+    // Generate a test secret; drops are keyed by its sha256 hash
+    secret = new Uint8Array(32);
     // Use crypto.getRandomValues for browser or crypto.randomBytes for Node
     if (typeof window !== "undefined" && window.crypto) {
-      window.crypto.getRandomValues(nullifier);
+      window.crypto.getRandomValues(secret);
     } else {
       const crypto = await import("crypto");
-      crypto.randomFillSync(nullifier);
+      crypto.randomFillSync(secret);
     }
+    const crypto = await import("crypto");
+    nullifier = new Uint8Array(
+      crypto.createHash("sha256").update(secret).digest()
+    );
 
     // Derive PDAs
     [configPDA] = PublicKey.findProgramAddressSync(
@@ -48,6 +54,10 @@ describe("darkpool", () => {
       [Buffer.from("sol_vault")],
       program.programId
     );
+    [policyPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("policy")],
+      program.programId
+    );
 
     [dropPDA] = PublicKey.findProgramAddressSync(
       [Buffer.from("drop"), Buffer.from(nullifier)],
@@ -58,6 +68,13 @@ describe("darkpool", () => {
       [Buffer.from("nullifier"), Buffer.from(nullifier)],
       program.programId
     );
+
+    // The claimer signs its claim and pays the transaction fee
+    const airdrop = await provider.connection.requestAirdrop(
+      claimer.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdrop);
   });
 
   it("Initializes the program", async () => {
@@ -76,6 +93,8 @@ describe("darkpool", () => {
     expect(config.isInitialized).to.be.true;
   });
 
+  // Drops are funded through deposit_pool: create_drop escrows nothing and only draws
+  // on vault surplus, which a freshly initialized vault does not have.
   it("Deposits to pool (SOL)", async () => {
     const clock = await provider.connection.getSlot();
     const blockTime = await provider.connection.getBlockTime(clock);
@@ -89,9 +108,11 @@ describe("darkpool", () => {
     const tx = await program.methods
       .depositPool(
         Array.from(nullifier),
+        authority.publicKey,
         new BN(1000000), // 0.001 SOL (in lamports)
         0, // SOL
-        expiresAt
+        expiresAt,
+        { open: {} }
       )
       .accounts({
         drop: dropPDA,
@@ -100,6 +121,7 @@ describe("darkpool", () => {
         rateLimitAccount: rateLimitPDA,
         payer: authority.publicKey,
         solVault: solVaultPDA,
+        policy: policyPDA,
         systemProgram: SystemProgram.programId,
       })
       .rpc();
@@ -109,18 +131,20 @@ describe("darkpool", () => {
     expect(drop.recipient.toString()).to.equal(authority.publicKey.toString());
     expect(drop.amount.toNumber()).to.equal(1000000);
     expect(drop.status.active).to.not.be.undefined;
+
+    const config = await program.account.config.fetch(configPDA);
+    expect(config.outstandingLiabilities.toNumber()).to.equal(1000000);
   });
 
   it("Claims a pool drop and prevents double-claim", async () => {
     await program.methods
-      .claimDrop(Array.from(nullifier))
+      .claimDrop(Array.from(secret))
       .accounts({
         drop: dropPDA,
         nullifierAccount: nullifierPDA,
         claimer: claimer.publicKey,
         config: configPDA,
         solVault: solVaultPDA,
-        treasury: treasury.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([claimer])
@@ -129,22 +153,24 @@ describe("darkpool", () => {
     // Second claim should fail
     try {
       await program.methods
-        .claimDrop(Array.from(nullifier))
+        .claimDrop(Array.from(secret))
         .accounts({
           drop: dropPDA,
           nullifierAccount: nullifierPDA,
           claimer: claimer.publicKey,
           config: configPDA,
           solVault: solVaultPDA,
-          treasury: treasury.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([claimer])
         .rpc();
       expect.fail("Should have thrown an error");
     } catch (err) {
-      expect(err.toString()).to.include("NullifierAlreadyUsed");
+      expect(err.toString()).to.include("DropNotActive");
     }
+
+    const config = await program.account.config.fetch(configPDA);
+    expect(config.outstandingLiabilities.toNumber()).to.equal(0);
   });
 
   it("Rejects invalid amount", async () => {
@@ -160,6 +186,10 @@ describe("darkpool", () => {
       [Buffer.from("drop"), Buffer.from(invalidNullifier)],
       program.programId
     );
+    const [invalidNullifierPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("nullifier"), Buffer.from(invalidNullifier)],
+      program.programId
+    );
 
     const clock = await provider.connection.getSlot();
     const blockTime = await provider.connection.getBlockTime(clock);
@@ -174,17 +204,20 @@ describe("darkpool", () => {
       await program.methods
         .depositPool(
           Array.from(invalidNullifier),
+          authority.publicKey,
           new BN(0), // Invalid: amount must be > 0
           0,
-          expiresAt
+          expiresAt,
+          { open: {} }
         )
         .accounts({
           drop: invalidDropPDA,
-          nullifierAccount: nullifierPDA,
+          nullifierAccount: invalidNullifierPDA,
           config: configPDA,
           rateLimitAccount: rateLimitPDA,
           payer: authority.publicKey,
           solVault: solVaultPDA,
+          policy: policyPDA,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
@@ -207,9 +240,21 @@ describe("darkpool", () => {
       [Buffer.from("drop"), Buffer.from(testNullifier)],
       program.programId
     );
+    const [testNullifierPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("nullifier"), Buffer.from(testNullifier)],
+      program.programId
+    );
+
+    // A fresh payer, so the authority's earlier deposit cannot trip the limit first
+    const payer = Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(
+      payer.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdrop);
 
     const [rateLimitPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("rate_limit"), authority.publicKey.toBuffer()],
+      [Buffer.from("rate_limit"), payer.publicKey.toBuffer()],
       program.programId
     );
 
@@ -221,19 +266,23 @@ describe("darkpool", () => {
     await program.methods
       .depositPool(
         Array.from(testNullifier),
+        payer.publicKey,
         new BN(1000000),
         0,
-        expiresAt
+        expiresAt,
+        { open: {} }
       )
       .accounts({
         drop: testDropPDA,
-        nullifierAccount: nullifierPDA,
+        nullifierAccount: testNullifierPDA,
         config: configPDA,
         rateLimitAccount: rateLimitPDA,
-        payer: authority.publicKey,
+        payer: payer.publicKey,
         solVault: solVaultPDA,
+        policy: policyPDA,
         systemProgram: SystemProgram.programId,
       })
+      .signers([payer])
       .rpc();
 
     // Second drop immediately after should fail
@@ -249,24 +298,32 @@ describe("darkpool", () => {
       [Buffer.from("drop"), Buffer.from(testNullifier2)],
       program.programId
     );
+    const [testNullifierPDA2] = PublicKey.findProgramAddressSync(
+      [Buffer.from("nullifier"), Buffer.from(testNullifier2)],
+      program.programId
+    );
 
     try {
       await program.methods
         .depositPool(
           Array.from(testNullifier2),
+          payer.publicKey,
           new BN(1000000),
           0,
-          expiresAt
+          expiresAt,
+          { open: {} }
         )
         .accounts({
           drop: testDropPDA2,
-          nullifierAccount: nullifierPDA,
+          nullifierAccount: testNullifierPDA2,
           config: configPDA,
           rateLimitAccount: rateLimitPDA,
-          payer: authority.publicKey,
+          payer: payer.publicKey,
           solVault: solVaultPDA,
+          policy: policyPDA,
           systemProgram: SystemProgram.programId,
         })
+        .signers([payer])
         .rpc();
       expect.fail("Should have thrown an error");
     } catch (err) {